@@ -7,8 +7,9 @@
 
 use std::sync::Arc;
 
-use warpx_runtime::{opaque::Block, AccountId, Balance, Nonce};
+use warpx_runtime::{opaque::Block, AccountId, AssetId, Balance, Nonce, OrderBookSnapshot};
 
+use frame_support::traits::fungible::NativeOrWithId;
 use sc_transaction_pool_api::TransactionPool;
 use sp_api::ProvideRuntimeApi;
 use sp_block_builder::BlockBuilder;
@@ -38,9 +39,17 @@ where
         + 'static,
     C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
     C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>,
+    C::Api: pallet_hybrid_orderbook_rpc::HybridOrderbookRuntimeApi<
+        Block,
+        Balance,
+        NativeOrWithId<AssetId>,
+        OrderBookSnapshot,
+        AccountId,
+    >,
     C::Api: BlockBuilder<Block>,
     P: TransactionPool + Sync + Send + 'static,
 {
+    use pallet_hybrid_orderbook_rpc::{HybridOrderbook, HybridOrderbookApiServer};
     use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApiServer};
     use substrate_frame_rpc_system::{System, SystemApiServer};
 
@@ -48,6 +57,7 @@ where
     let FullDeps { client, pool } = deps;
 
     module.merge(System::new(client.clone(), pool).into_rpc())?;
-    module.merge(TransactionPayment::new(client).into_rpc())?;
+    module.merge(TransactionPayment::new(client.clone()).into_rpc())?;
+    module.merge(HybridOrderbook::new(client).into_rpc())?;
     Ok(module)
 }
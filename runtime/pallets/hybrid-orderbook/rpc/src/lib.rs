@@ -0,0 +1,474 @@
+//! RPC interface for the hybrid orderbook pallet.
+//!
+//! Wraps [`HybridOrderbookRuntimeApi`] so that clients don't need to decode the SCALE-encoded
+//! critbit trees of the orderbook. Every value is returned as a decimal string, where prices are
+//! scaled by `pool_decimals`, quantities by the decimals of the base asset and amounts by the
+//! decimals of the quote asset of the pool.
+
+#![warn(missing_docs)]
+
+use std::{marker::PhantomData, sync::Arc};
+
+use codec::Codec;
+use frame_support::traits::{fungible::NativeOrWithId, tokens::Balance as BalanceT};
+use jsonrpsee::{
+    core::RpcResult,
+    proc_macros::rpc,
+    types::error::{ErrorCode, ErrorObject, ErrorObjectOwned},
+};
+use pallet_hybrid_orderbook::{
+    LiquidityMode, MakerFeeRate, PoolDepth, PoolMetadata, PoolReserves,
+};
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{
+    traits::{Block as BlockT, MaybeDisplay, SaturatedConversion, UniqueSaturatedInto},
+    Permill,
+};
+
+pub use pallet_hybrid_orderbook::HybridOrderbookApi as HybridOrderbookRuntimeApi;
+
+/// Default number of price levels of each side returned by `hybridOrderbook_depth`
+const DEFAULT_DEPTH_LEVELS: u32 = 20;
+
+/// Hybrid orderbook RPC methods.
+#[rpc(client, server)]
+pub trait HybridOrderbookApi<BlockHash, AssetId> {
+    /// Reserves, price and the best bid/ask of the pool
+    #[method(name = "hybridOrderbook_getPool")]
+    fn pool(
+        &self,
+        base: Asset<AssetId>,
+        quote: Asset<AssetId>,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Pool>;
+
+    /// Fee rates, sizes and decimals of the pool
+    #[method(name = "hybridOrderbook_getMetadata")]
+    fn metadata(
+        &self,
+        base: Asset<AssetId>,
+        quote: Asset<AssetId>,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Metadata>;
+
+    /// Amount of quote asset to pay(bid) or receive(ask) including the taker fee for the market
+    /// order of `quantity` of base asset
+    #[method(name = "hybridOrderbook_quote")]
+    fn quote(
+        &self,
+        base: Asset<AssetId>,
+        quote: Asset<AssetId>,
+        quantity: String,
+        is_bid: bool,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Quote>;
+
+    /// Price levels of the orderbook from the best bid and the best ask. Up to `levels` for each
+    /// side, 20 by default.
+    #[method(name = "hybridOrderbook_depth")]
+    fn depth(
+        &self,
+        base: Asset<AssetId>,
+        quote: Asset<AssetId>,
+        levels: Option<u32>,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Depth>;
+}
+
+/// Asset of the pool, e.g. `"native"` or `{ "withId": 1 }`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Asset<AssetId> {
+    /// Native asset of the chain
+    Native,
+    /// Asset of `pallet-assets` for the given id
+    WithId(AssetId),
+}
+
+impl<AssetId: Ord> From<Asset<AssetId>> for NativeOrWithId<AssetId> {
+    fn from(asset: Asset<AssetId>) -> Self {
+        match asset {
+            Asset::Native => NativeOrWithId::Native,
+            Asset::WithId(id) => NativeOrWithId::WithId(id),
+        }
+    }
+}
+
+/// Response of `hybridOrderbook_getPool`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pool {
+    /// Base asset reserve of the pool
+    pub base_reserve: String,
+    /// Quote asset reserve of the pool
+    pub quote_reserve: String,
+    /// Price of the pool
+    pub pool_price: String,
+    /// Highest price of the bid orders, if any
+    pub best_bid: Option<String>,
+    /// Lowest price of the ask orders, if any
+    pub best_ask: Option<String>,
+}
+
+/// Response of `hybridOrderbook_getMetadata`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Metadata {
+    /// Fee rate of the taker, e.g. `"0.003"`
+    pub taker_fee_rate: String,
+    /// Fee rate of the maker. Negative if it is rebate.
+    pub maker_fee_rate: String,
+    /// Order quantity should be multiple of `lot_size`
+    pub lot_size: String,
+    /// Order price should be multiple of `tick_size`
+    pub tick_size: String,
+    /// Decimals of the price
+    pub pool_decimals: u8,
+    /// Decimals of the base asset
+    pub base_decimals: u8,
+    /// Decimals of the quote asset
+    pub quote_decimals: u8,
+    /// Whether the liquidity of the pool is concentrated on the price ranges of the positions
+    pub concentrated: bool,
+}
+
+/// Response of `hybridOrderbook_quote`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Quote {
+    /// Quantity of base asset of the order
+    pub quantity: String,
+    /// Amount of quote asset to pay(bid) or receive(ask) including the taker fee
+    pub amount: String,
+    /// Average price of the order including the taker fee
+    pub average_price: String,
+}
+
+/// Price level of the orderbook
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Level {
+    /// Price of the level
+    pub price: String,
+    /// Total quantity of the open orders on the level
+    pub quantity: String,
+    /// Number of the open orders on the level
+    pub orders: u32,
+}
+
+/// Response of `hybridOrderbook_depth`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Depth {
+    /// Bid levels from the highest price
+    pub bids: Vec<Level>,
+    /// Ask levels from the lowest price
+    pub asks: Vec<Level>,
+}
+
+/// Error type of this RPC api.
+pub enum Error {
+    /// The call to runtime failed.
+    RuntimeError,
+    /// The pool of the given assets doesn't exist or the order is not valid for the pool.
+    PoolError,
+}
+
+impl From<Error> for i32 {
+    fn from(e: Error) -> i32 {
+        match e {
+            Error::RuntimeError => 1,
+            Error::PoolError => 2,
+        }
+    }
+}
+
+/// Provides RPC methods to query the pools of the hybrid orderbook.
+pub struct HybridOrderbook<C, Block, AssetId, Balance, Orderbook, AccountId> {
+    client: Arc<C>,
+    _marker: PhantomData<(Block, AssetId, Balance, Orderbook, AccountId)>,
+}
+
+impl<C, Block, AssetId, Balance, Orderbook, AccountId>
+    HybridOrderbook<C, Block, AssetId, Balance, Orderbook, AccountId>
+{
+    /// Create new `HybridOrderbook` with the given reference to the client.
+    pub fn new(client: Arc<C>) -> Self {
+        Self { client, _marker: Default::default() }
+    }
+}
+
+impl<C, Block, AssetId, Balance, Orderbook, AccountId>
+    HybridOrderbook<C, Block, AssetId, Balance, Orderbook, AccountId>
+where
+    Block: BlockT,
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    C::Api: HybridOrderbookRuntimeApi<
+        Block,
+        Balance,
+        NativeOrWithId<AssetId>,
+        Orderbook,
+        AccountId,
+    >,
+    AssetId: Codec + Ord + Clone,
+    Balance: BalanceT + MaybeDisplay,
+    Orderbook: Codec,
+    AccountId: Codec,
+{
+    fn pool_metadata(
+        &self,
+        at: Block::Hash,
+        base: Asset<AssetId>,
+        quote: Asset<AssetId>,
+    ) -> RpcResult<PoolMetadata<Balance>> {
+        self.client
+            .runtime_api()
+            .get_pool_metadata(at, base.into(), quote.into())
+            .map_err(runtime_error)?
+            .ok_or_else(|| pool_error("Pool not found."))
+    }
+
+    fn pool_reserves(
+        &self,
+        at: Block::Hash,
+        base: Asset<AssetId>,
+        quote: Asset<AssetId>,
+    ) -> RpcResult<PoolReserves<Balance>> {
+        self.client
+            .runtime_api()
+            .get_pool_reserves(at, base.into(), quote.into())
+            .map_err(runtime_error)?
+            .ok_or_else(|| pool_error("Pool not found."))
+    }
+
+    fn pool_depth(
+        &self,
+        at: Block::Hash,
+        base: Asset<AssetId>,
+        quote: Asset<AssetId>,
+        levels: u32,
+    ) -> RpcResult<PoolDepth<Balance>> {
+        self.client
+            .runtime_api()
+            .get_depth(at, base.into(), quote.into(), levels)
+            .map_err(runtime_error)?
+            .ok_or_else(|| pool_error("Pool not found."))
+    }
+}
+
+impl<C, Block, AssetId, Balance, Orderbook, AccountId>
+    HybridOrderbookApiServer<<Block as BlockT>::Hash, AssetId>
+    for HybridOrderbook<C, Block, AssetId, Balance, Orderbook, AccountId>
+where
+    Block: BlockT,
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    C::Api: HybridOrderbookRuntimeApi<
+        Block,
+        Balance,
+        NativeOrWithId<AssetId>,
+        Orderbook,
+        AccountId,
+    >,
+    AssetId: Codec + Ord + Clone + Send + Sync + 'static,
+    Balance: BalanceT + MaybeDisplay,
+    Orderbook: Codec + Send + Sync + 'static,
+    AccountId: Codec + Send + Sync + 'static,
+{
+    fn pool(
+        &self,
+        base: Asset<AssetId>,
+        quote: Asset<AssetId>,
+        at: Option<Block::Hash>,
+    ) -> RpcResult<Pool> {
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+        let metadata = self.pool_metadata(at, base.clone(), quote.clone())?;
+        let reserves = self.pool_reserves(at, base.clone(), quote.clone())?;
+        let depth = self.pool_depth(at, base, quote, 1)?;
+        let price = |(price, _, _): &(Balance, Balance, u32)| {
+            to_decimal_string(*price, metadata.pool_decimals)
+        };
+        Ok(Pool {
+            base_reserve: to_decimal_string(reserves.base_reserve, metadata.base_decimals),
+            quote_reserve: to_decimal_string(reserves.quote_reserve, metadata.quote_decimals),
+            pool_price: to_decimal_string(reserves.pool_price, metadata.pool_decimals),
+            best_bid: depth.bids.first().map(price),
+            best_ask: depth.asks.first().map(price),
+        })
+    }
+
+    fn metadata(
+        &self,
+        base: Asset<AssetId>,
+        quote: Asset<AssetId>,
+        at: Option<Block::Hash>,
+    ) -> RpcResult<Metadata> {
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+        let metadata = self.pool_metadata(at, base, quote)?;
+        let maker_fee_rate = match metadata.maker_fee_rate {
+            MakerFeeRate::Rebate(rate) if rate.deconstruct() > 0 => {
+                format!("-{}", to_rate_string(rate))
+            }
+            MakerFeeRate::Fee(rate) | MakerFeeRate::Rebate(rate) => to_rate_string(rate),
+        };
+        Ok(Metadata {
+            taker_fee_rate: to_rate_string(metadata.taker_fee_rate),
+            maker_fee_rate,
+            lot_size: to_decimal_string(metadata.lot_size, metadata.base_decimals),
+            tick_size: to_decimal_string(metadata.tick_size, metadata.pool_decimals),
+            pool_decimals: metadata.pool_decimals,
+            base_decimals: metadata.base_decimals,
+            quote_decimals: metadata.quote_decimals,
+            concentrated: matches!(metadata.liquidity_mode, LiquidityMode::Concentrated { .. }),
+        })
+    }
+
+    fn quote(
+        &self,
+        base: Asset<AssetId>,
+        quote: Asset<AssetId>,
+        quantity: String,
+        is_bid: bool,
+        at: Option<Block::Hash>,
+    ) -> RpcResult<Quote> {
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+        let metadata = self.pool_metadata(at, base.clone(), quote.clone())?;
+        let raw_quantity = from_decimal_string(&quantity, metadata.base_decimals)
+            .filter(|q| *q > 0)
+            .ok_or_else(|| {
+                ErrorObject::owned(
+                    ErrorCode::InvalidParams.code(),
+                    "Invalid quantity.",
+                    Some(format!(
+                        "Expected a positive decimal with up to {} decimals",
+                        metadata.base_decimals
+                    )),
+                )
+            })?;
+        let raw_quantity: Balance = raw_quantity
+            .try_into()
+            .map_err(|_| pool_error("Quantity is too large."))?;
+        let amount = self
+            .client
+            .runtime_api()
+            .quote_market_order(at, base.into(), quote.into(), raw_quantity, is_bid)
+            .map_err(runtime_error)?
+            .ok_or_else(|| pool_error("Unable to quote the market order."))?;
+        let amount: u128 = amount.saturated_into();
+        // `price * quantity = amount` where the price is scaled by `pool_decimals`
+        let average_price = amount
+            .saturating_mul(10u128.saturating_pow(
+                (metadata.pool_decimals + metadata.base_decimals) as u32,
+            ))
+            .checked_div(
+                raw_quantity
+                    .saturated_into::<u128>()
+                    .saturating_mul(10u128.saturating_pow(metadata.quote_decimals as u32)),
+            )
+            .unwrap_or_default();
+        Ok(Quote {
+            quantity: to_decimal_string(raw_quantity, metadata.base_decimals),
+            amount: to_decimal_string(amount, metadata.quote_decimals),
+            average_price: to_decimal_string(average_price, metadata.pool_decimals),
+        })
+    }
+
+    fn depth(
+        &self,
+        base: Asset<AssetId>,
+        quote: Asset<AssetId>,
+        levels: Option<u32>,
+        at: Option<Block::Hash>,
+    ) -> RpcResult<Depth> {
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+        let levels = levels.unwrap_or(DEFAULT_DEPTH_LEVELS);
+        let metadata = self.pool_metadata(at, base.clone(), quote.clone())?;
+        let depth = self.pool_depth(at, base, quote, levels)?;
+        let to_levels = |side: Vec<(Balance, Balance, u32)>| {
+            side
+                .into_iter()
+                .map(|(price, quantity, orders)| Level {
+                    price: to_decimal_string(price, metadata.pool_decimals),
+                    quantity: to_decimal_string(quantity, metadata.base_decimals),
+                    orders,
+                })
+                .collect()
+        };
+        Ok(Depth {
+            bids: to_levels(depth.bids),
+            asks: to_levels(depth.asks),
+        })
+    }
+}
+
+fn runtime_error(e: impl std::fmt::Display) -> ErrorObjectOwned {
+    ErrorObject::owned(
+        Error::RuntimeError.into(),
+        "Unable to query the hybrid orderbook.",
+        Some(e.to_string()),
+    )
+}
+
+fn pool_error(message: &str) -> ErrorObjectOwned {
+    ErrorObject::owned(Error::PoolError.into(), message.to_string(), None::<()>)
+}
+
+fn to_rate_string(rate: Permill) -> String {
+    to_decimal_string(rate.deconstruct(), 6)
+}
+
+/// Format `value` which is scaled by `10^decimals`, e.g. `1500` with `3` decimals is `"1.5"`
+fn to_decimal_string(value: impl UniqueSaturatedInto<u128>, decimals: u8) -> String {
+    let digits = value.unique_saturated_into().to_string();
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits;
+    }
+    let digits = format!("{digits:0>width$}", width = decimals + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        integer.to_string()
+    } else {
+        format!("{integer}.{fraction}")
+    }
+}
+
+/// Parse decimal string into the value scaled by `10^decimals`. Return `None` if it has more
+/// fractional digits than `decimals`.
+fn from_decimal_string(value: &str, decimals: u8) -> Option<u128> {
+    let (integer, fraction) = value.split_once('.').unwrap_or((value, ""));
+    let is_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    if integer.is_empty()
+        || fraction.len() > decimals as usize
+        || !is_digits(integer)
+        || !is_digits(fraction)
+    {
+        return None;
+    }
+    format!("{integer}{fraction:0<width$}", width = decimals as usize).parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decimal_string_works() {
+        assert_eq!(to_decimal_string(1_500u128, 3), "1.5");
+        assert_eq!(to_decimal_string(1_000u128, 3), "1");
+        assert_eq!(to_decimal_string(5u128, 3), "0.005");
+        assert_eq!(to_decimal_string(0u128, 3), "0");
+        assert_eq!(to_decimal_string(42u128, 0), "42");
+        assert_eq!(to_rate_string(Permill::from_parts(3_000)), "0.003");
+
+        assert_eq!(from_decimal_string("1.5", 3), Some(1_500));
+        assert_eq!(from_decimal_string("1", 3), Some(1_000));
+        assert_eq!(from_decimal_string("0.005", 3), Some(5));
+        assert_eq!(from_decimal_string("0.0005", 3), None);
+        assert_eq!(from_decimal_string(".5", 3), None);
+        assert_eq!(from_decimal_string("-1", 3), None);
+        assert_eq!(from_decimal_string("1e3", 3), None);
+    }
+}
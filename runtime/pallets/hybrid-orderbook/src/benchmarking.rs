@@ -15,17 +15,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! Asset Conversion pallet benchmarking.
+//! Hybrid Orderbook pallet benchmarking.
 
 use super::*;
-use crate::Pallet as AssetConversion;
-use frame_benchmarking::{v2::*, whitelisted_caller};
+use crate::Pallet as HybridOrderbook;
+use frame_benchmarking::{account, v2::*, whitelisted_caller};
 use frame_support::{
 	assert_ok,
 	traits::{
-		fungible::NativeOrWithId,
+		fungible::{self, NativeOrWithId},
 		fungibles::{Create, Inspect, Mutate, Refund},
 	},
+	weights::Weight,
 };
 use frame_system::RawOrigin as SystemOrigin;
 use sp_core::Get;
@@ -65,12 +66,12 @@ impl<AssetId: From<u32> + Ord> BenchmarkHelper<NativeOrWithId<AssetId>>
 }
 
 /// Provides a pair of amounts expected to serve as sufficient initial liquidity for a pool.
-fn valid_liquidity_amount<T: Config>(ed1: T::Balance, ed2: T::Balance) -> (T::Balance, T::Balance)
+fn valid_liquidity_amount<T: Config>(ed1: T::Unit, ed2: T::Unit) -> (T::Unit, T::Unit)
 where
 	T::Assets: Inspect<T::AccountId>,
 {
 	let l =
-		ed1.max(ed2) + T::MintMinLiquidity::get() + T::MintMinLiquidity::get() + T::Balance::one();
+		ed1.max(ed2) + T::MintMinLiquidity::get() + T::MintMinLiquidity::get() + T::Unit::one();
 	(l, l)
 }
 
@@ -78,7 +79,7 @@ where
 fn create_asset<T: Config>(
 	caller: &T::AccountId,
 	asset: &T::AssetKind,
-	amount: T::Balance,
+	amount: T::Unit,
 	is_sufficient: bool,
 ) where
 	T::Assets: Create<T::AccountId> + Mutate<T::AccountId>,
@@ -88,7 +89,7 @@ fn create_asset<T: Config>(
 			asset.clone(),
 			caller.clone(),
 			is_sufficient,
-			T::Balance::one()
+			T::Unit::one()
 		));
 	}
 	assert_ok!(T::Assets::mint_into(
@@ -105,7 +106,7 @@ where
 {
 	let fee_asset = T::PoolSetupFeeAsset::get();
 	if !T::Assets::asset_exists(fee_asset.clone()) {
-		assert_ok!(T::Assets::create(fee_asset.clone(), caller.clone(), true, T::Balance::one()));
+		assert_ok!(T::Assets::create(fee_asset.clone(), caller.clone(), true, T::Unit::one()));
 	}
 	assert_ok!(T::Assets::mint_into(
 		fee_asset.clone(),
@@ -142,7 +143,7 @@ fn create_asset_and_pool<T: Config>(
 	caller: &T::AccountId,
 	asset1: &T::AssetKind,
 	asset2: &T::AssetKind,
-) -> (T::PoolAssetId, T::Balance, T::Balance)
+) -> (T::PoolAssetId, T::Unit, T::Unit)
 where
 	T::Assets: Create<T::AccountId> + Mutate<T::AccountId>,
 {
@@ -152,19 +153,193 @@ where
 	);
 	create_asset::<T>(caller, asset1, liquidity1, true);
 	create_asset::<T>(caller, asset2, liquidity2, true);
-	let lp_token = AssetConversion::<T>::get_next_pool_asset_id();
+	let lp_token = HybridOrderbook::<T>::get_next_pool_asset_id();
 
 	mint_setup_fee_asset::<T>(caller, asset1, asset2, &lp_token);
 
-	assert_ok!(AssetConversion::<T>::create_pool(
+	assert_ok!(HybridOrderbook::<T>::create_pool(
 		SystemOrigin::Signed(caller.clone()).into(),
 		Box::new(asset1.clone()),
-		Box::new(asset2.clone())
+		0,
+		Box::new(asset2.clone()),
+		0,
+		Permill::zero(),
+		MakerFeeRate::default(),
+		T::Unit::one(),
+		T::Unit::one(),
+		0,
+		LiquidityMode::ConstantProduct,
 	));
 
 	(lp_token, liquidity1, liquidity2)
 }
 
+const SEED: u32 = 0;
+/// Base asset reserve of the pool created by [`create_orderbook_pool`].
+const BASE_RESERVE: u32 = 1_000_000;
+/// Price of the pool created by [`create_orderbook_pool`].
+const POOL_PRICE: u32 = 1_000;
+
+/// Mint the native asset for `who` to hold the deposit of an open order.
+fn fund_order_deposit<T: Config>(who: &T::AccountId)
+where
+	T::Currency: fungible::Mutate<T::AccountId>,
+{
+	assert_ok!(<T::Currency as fungible::Mutate<_>>::mint_into(
+		who,
+		T::OrderDeposit::get() + <T::Currency as fungible::Inspect<_>>::minimum_balance()
+	));
+}
+
+/// Creates a pool of tick and lot size of one unit whose price is [`POOL_PRICE`], and mints enough
+/// quote asset for the `caller` to buy the whole orderbook placed by [`place_asks`].
+fn create_orderbook_pool<T: Config>(caller: &T::AccountId) -> (T::AssetKind, T::AssetKind)
+where
+	T::Assets: Create<T::AccountId> + Mutate<T::AccountId>,
+	T::Currency: fungible::Mutate<T::AccountId>,
+{
+	let (base, quote) = T::BenchmarkHelper::create_pair(0, 1);
+	create_fee_asset::<T>(caller);
+	create_asset_and_pool::<T>(caller, &base, &quote);
+	let base_reserve: T::Unit = BASE_RESERVE.into();
+	let quote_reserve = base_reserve * POOL_PRICE.into();
+	create_asset::<T>(caller, &base, base_reserve, true);
+	create_asset::<T>(caller, &quote, quote_reserve + quote_reserve, true);
+	assert_ok!(HybridOrderbook::<T>::add_liquidity(
+		SystemOrigin::Signed(caller.clone()).into(),
+		Box::new(base.clone()),
+		Box::new(quote.clone()),
+		base_reserve,
+		quote_reserve,
+		T::Unit::one(),
+		T::Unit::one(),
+		caller.clone(),
+	));
+	fund_order_deposit::<T>(caller);
+	(base, quote)
+}
+
+/// Place `orders` of ask orders of one lot each, spread over `levels` of price levels right above
+/// the pool price, from one account per order.
+fn place_asks<T: Config>(base: &T::AssetKind, quote: &T::AssetKind, levels: u32, orders: u32)
+where
+	T::Assets: Create<T::AccountId> + Mutate<T::AccountId>,
+	T::Currency: fungible::Mutate<T::AccountId>,
+{
+	for i in 0..orders {
+		let maker: T::AccountId = account("maker", i, SEED);
+		fund_order_deposit::<T>(&maker);
+		create_asset::<T>(&maker, base, T::Unit::one(), true);
+		let price: T::Unit = (POOL_PRICE + i % levels + 1).into();
+		assert_ok!(HybridOrderbook::<T>::limit_order(
+			SystemOrigin::Signed(maker).into(),
+			Box::new(base.clone()),
+			Box::new(quote.clone()),
+			false,
+			price,
+			T::Unit::one(),
+			TimeInForce::GoodTilCancel,
+		));
+	}
+}
+
+/// Place `orders` of stop limit ask orders of one lot each right below the pool price, from one
+/// account per order.
+fn place_stop_asks<T: Config>(base: &T::AssetKind, quote: &T::AssetKind, orders: u32)
+where
+	T::Assets: Create<T::AccountId> + Mutate<T::AccountId>,
+	T::Currency: fungible::Mutate<T::AccountId>,
+{
+	let price: T::Unit = (POOL_PRICE - 1).into();
+	for i in 0..orders {
+		let maker: T::AccountId = account("stop_maker", i, SEED);
+		fund_order_deposit::<T>(&maker);
+		create_asset::<T>(&maker, base, T::Unit::one(), true);
+		assert_ok!(HybridOrderbook::<T>::stop_limit_order(
+			SystemOrigin::Signed(maker).into(),
+			Box::new(base.clone()),
+			Box::new(quote.clone()),
+			false,
+			price,
+			price,
+			T::Unit::one(),
+		));
+	}
+}
+
+/// The number of orders filled over `l` price levels, from one order on each price level up to
+/// the full price levels.
+fn orders_filled<T: Config>(l: u32, o: u32) -> u32 {
+	o.max(l).min(l.saturating_mul(T::MaxOrdersPerTick::get()))
+}
+
+/// Base asset provided by each position opened by [`open_positions`].
+const POSITION_LIQUIDITY: u32 = 1_000;
+
+/// Creates a pool of tick and lot size of one unit with [`LiquidityMode::Concentrated`] starting
+/// from [`POOL_PRICE`], and mints enough base asset for the `caller` to open `positions` by
+/// [`open_positions`] and enough quote asset to buy all of them.
+fn create_concentrated_pool<T: Config>(
+	caller: &T::AccountId,
+	positions: u32,
+) -> (T::AssetKind, T::AssetKind)
+where
+	T::Assets: Create<T::AccountId> + Mutate<T::AccountId>,
+{
+	let (base, quote) = T::BenchmarkHelper::create_pair(0, 1);
+	create_fee_asset::<T>(caller);
+	let base_amount: T::Unit = (POSITION_LIQUIDITY * (positions + 1)).into();
+	create_asset::<T>(caller, &base, base_amount, true);
+	create_asset::<T>(caller, &quote, base_amount * (POOL_PRICE * 2).into(), true);
+	let lp_token = HybridOrderbook::<T>::get_next_pool_asset_id();
+	mint_setup_fee_asset::<T>(caller, &base, &quote, &lp_token);
+	assert_ok!(HybridOrderbook::<T>::create_pool(
+		SystemOrigin::Signed(caller.clone()).into(),
+		Box::new(base.clone()),
+		0,
+		Box::new(quote.clone()),
+		0,
+		Permill::zero(),
+		MakerFeeRate::default(),
+		T::Unit::one(),
+		T::Unit::one(),
+		0,
+		LiquidityMode::Concentrated { initial_price: POOL_PRICE.into() },
+	));
+	(base, quote)
+}
+
+/// Open `positions` of [`POSITION_LIQUIDITY`] of base asset each, on disjoint price ranges of one
+/// tick above the pool price, so that each position has its own two boundaries.
+fn open_positions<T: Config>(
+	caller: &T::AccountId,
+	base: &T::AssetKind,
+	quote: &T::AssetKind,
+	positions: u32,
+) {
+	for i in 0..positions {
+		let lower: T::Unit = (POOL_PRICE + 2 * i + 1).into();
+		assert_ok!(HybridOrderbook::<T>::open_position(
+			SystemOrigin::Signed(caller.clone()).into(),
+			Box::new(base.clone()),
+			Box::new(quote.clone()),
+			lower,
+			lower + T::Unit::one(),
+			POSITION_LIQUIDITY.into(),
+			T::Unit::zero(),
+			T::Unit::zero(),
+			T::Unit::zero(),
+		));
+	}
+}
+
+/// Move to the next block, so that the first reserve change of the benchmarked call observes the
+/// pool price on top of recording its trades.
+fn next_block<T: Config>() {
+	let now = frame_system::Pallet::<T>::block_number();
+	frame_system::Pallet::<T>::set_block_number(now + One::one());
+}
+
 fn assert_last_event<T: Config>(generic_event: <T as Config>::RuntimeEvent) {
 	let events = frame_system::Pallet::<T>::events();
 	let system_event: <T as frame_system::Config>::RuntimeEvent = generic_event.into();
@@ -173,7 +348,11 @@ fn assert_last_event<T: Config>(generic_event: <T as Config>::RuntimeEvent) {
 	assert_eq!(event, &system_event);
 }
 
-#[benchmarks(where T::Assets: Create<T::AccountId> + Mutate<T::AccountId>, T::PoolAssetId: Into<u32>,)]
+#[benchmarks(where
+	T::Assets: Create<T::AccountId> + Mutate<T::AccountId>,
+	T::PoolAssetId: Into<u32>,
+	T::Currency: fungible::Mutate<T::AccountId>,
+)]
 mod benchmarks {
 	use super::*;
 
@@ -184,17 +363,40 @@ mod benchmarks {
 		create_asset::<T>(&caller, &asset1, T::Assets::minimum_balance(asset1.clone()), true);
 		create_asset::<T>(&caller, &asset2, T::Assets::minimum_balance(asset2.clone()), true);
 
-		let lp_token = AssetConversion::<T>::get_next_pool_asset_id();
+		let lp_token = HybridOrderbook::<T>::get_next_pool_asset_id();
 		create_fee_asset::<T>(&caller);
 		mint_setup_fee_asset::<T>(&caller, &asset1, &asset2, &lp_token);
 
 		#[extrinsic_call]
-		_(SystemOrigin::Signed(caller.clone()), Box::new(asset1.clone()), Box::new(asset2.clone()));
+		_(
+			SystemOrigin::Signed(caller.clone()),
+			Box::new(asset1.clone()),
+			0,
+			Box::new(asset2.clone()),
+			0,
+			Permill::zero(),
+			MakerFeeRate::default(),
+			T::Unit::one(),
+			T::Unit::one(),
+			0,
+			LiquidityMode::ConstantProduct,
+		);
 
 		let pool_id = T::PoolLocator::pool_id(&asset1, &asset2).unwrap();
 		let pool_account = T::PoolLocator::address(&pool_id).unwrap();
 		assert_last_event::<T>(
-			Event::PoolCreated { creator: caller, pool_account, pool_id, lp_token }.into(),
+			Event::PoolCreated {
+				creator: caller,
+				pool_account,
+				pool_id,
+				lp_token,
+				taker_fee_rate: Permill::zero(),
+				maker_fee_rate: MakerFeeRate::default(),
+				tick_size: T::Unit::one(),
+				lot_size: T::Unit::one(),
+				liquidity_mode: LiquidityMode::ConstantProduct,
+			}
+			.into(),
 		);
 	}
 
@@ -206,6 +408,23 @@ mod benchmarks {
 		create_fee_asset::<T>(&caller);
 		let (lp_token, liquidity1, liquidity2) =
 			create_asset_and_pool::<T>(&caller, &asset1, &asset2);
+		// Pool with the reserves, whose price is observed on the change of the reserves
+		assert_ok!(HybridOrderbook::<T>::add_liquidity(
+			SystemOrigin::Signed(caller.clone()).into(),
+			Box::new(asset1.clone()),
+			Box::new(asset2.clone()),
+			liquidity1,
+			liquidity2,
+			T::Unit::one(),
+			T::Unit::zero(),
+			caller.clone(),
+		));
+		let lp_minted =
+			HybridOrderbook::<T>::calc_lp_amount_for_zero_supply(&liquidity1, &liquidity2).unwrap();
+		assert_eq!(T::PoolAssets::balance(lp_token.clone(), &caller), lp_minted);
+		create_asset::<T>(&caller, &asset1, liquidity1, true);
+		create_asset::<T>(&caller, &asset2, liquidity2, true);
+		next_block::<T>();
 
 		#[extrinsic_call]
 		_(
@@ -214,17 +433,15 @@ mod benchmarks {
 			Box::new(asset2.clone()),
 			liquidity1,
 			liquidity2,
-			T::Balance::one(),
-			T::Balance::zero(),
+			T::Unit::one(),
+			T::Unit::zero(),
 			caller.clone(),
 		);
 
 		let pool_account = T::PoolLocator::pool_address(&asset1, &asset2).unwrap();
-		let lp_minted =
-			AssetConversion::<T>::calc_lp_amount_for_zero_supply(&liquidity1, &liquidity2).unwrap();
-		assert_eq!(T::PoolAssets::balance(lp_token, &caller), lp_minted);
-		assert_eq!(T::Assets::balance(asset1, &pool_account), liquidity1);
-		assert_eq!(T::Assets::balance(asset2, &pool_account), liquidity2);
+		assert!(T::PoolAssets::balance(lp_token, &caller) > lp_minted);
+		assert_eq!(T::Assets::balance(asset1, &pool_account), liquidity1 + liquidity1);
+		assert_eq!(T::Assets::balance(asset2, &pool_account), liquidity2 + liquidity2);
 	}
 
 	#[benchmark]
@@ -236,20 +453,21 @@ mod benchmarks {
 		let (lp_token, liquidity1, liquidity2) =
 			create_asset_and_pool::<T>(&caller, &asset1, &asset2);
 
-		let remove_lp_amount = T::Balance::one();
+		let remove_lp_amount = T::Unit::one();
 
-		assert_ok!(AssetConversion::<T>::add_liquidity(
+		assert_ok!(HybridOrderbook::<T>::add_liquidity(
 			SystemOrigin::Signed(caller.clone()).into(),
 			Box::new(asset1.clone()),
 			Box::new(asset2.clone()),
 			liquidity1,
 			liquidity2,
-			T::Balance::one(),
-			T::Balance::zero(),
+			T::Unit::one(),
+			T::Unit::zero(),
 			caller.clone(),
 		));
 		let total_supply =
 			<T::PoolAssets as Inspect<T::AccountId>>::total_issuance(lp_token.clone());
+		next_block::<T>();
 
 		#[extrinsic_call]
 		_(
@@ -257,8 +475,8 @@ mod benchmarks {
 			Box::new(asset1),
 			Box::new(asset2),
 			remove_lp_amount,
-			T::Balance::zero(),
-			T::Balance::zero(),
+			T::Unit::zero(),
+			T::Unit::zero(),
 			caller.clone(),
 		);
 
@@ -268,14 +486,14 @@ mod benchmarks {
 
 	#[benchmark]
 	fn swap_exact_tokens_for_tokens(n: Linear<2, { T::MaxSwapPathLength::get() }>) {
-		let mut swap_amount = T::Balance::one();
+		let mut swap_amount = T::Unit::one();
 		let mut path = vec![];
 
 		let caller: T::AccountId = whitelisted_caller();
 		create_fee_asset::<T>(&caller);
 		for n in 1..n {
 			let (asset1, asset2) = T::BenchmarkHelper::create_pair(n - 1, n);
-			swap_amount = swap_amount + T::Balance::one();
+			swap_amount = swap_amount + T::Unit::one();
 			if path.len() == 0 {
 				path = vec![Box::new(asset1.clone()), Box::new(asset2.clone())];
 			} else {
@@ -284,14 +502,14 @@ mod benchmarks {
 
 			let (_, liquidity1, liquidity2) = create_asset_and_pool::<T>(&caller, &asset1, &asset2);
 
-			assert_ok!(AssetConversion::<T>::add_liquidity(
+			assert_ok!(HybridOrderbook::<T>::add_liquidity(
 				SystemOrigin::Signed(caller.clone()).into(),
 				Box::new(asset1.clone()),
 				Box::new(asset2.clone()),
 				liquidity1,
 				liquidity2,
-				T::Balance::one(),
-				T::Balance::zero(),
+				T::Unit::one(),
+				T::Unit::zero(),
 				caller.clone(),
 			));
 		}
@@ -300,16 +518,17 @@ mod benchmarks {
 		assert_ok!(T::Assets::mint_into(
 			asset_in.clone(),
 			&caller,
-			swap_amount + T::Balance::one()
+			swap_amount + T::Unit::one()
 		));
 		let init_caller_balance = T::Assets::balance(asset_in.clone(), &caller);
+		next_block::<T>();
 
 		#[extrinsic_call]
 		_(
 			SystemOrigin::Signed(caller.clone()),
 			path,
 			swap_amount,
-			T::Balance::one(),
+			T::Unit::one(),
 			caller.clone(),
 			true,
 		);
@@ -320,14 +539,14 @@ mod benchmarks {
 
 	#[benchmark]
 	fn swap_tokens_for_exact_tokens(n: Linear<2, { T::MaxSwapPathLength::get() }>) {
-		let mut max_swap_amount = T::Balance::one();
+		let mut max_swap_amount = T::Unit::one();
 		let mut path = vec![];
 
 		let caller: T::AccountId = whitelisted_caller();
 		create_fee_asset::<T>(&caller);
 		for n in 1..n {
 			let (asset1, asset2) = T::BenchmarkHelper::create_pair(n - 1, n);
-			max_swap_amount = max_swap_amount + T::Balance::one() + T::Balance::one();
+			max_swap_amount = max_swap_amount + T::Unit::one() + T::Unit::one();
 			if path.len() == 0 {
 				path = vec![Box::new(asset1.clone()), Box::new(asset2.clone())];
 			} else {
@@ -336,14 +555,14 @@ mod benchmarks {
 
 			let (_, liquidity1, liquidity2) = create_asset_and_pool::<T>(&caller, &asset1, &asset2);
 
-			assert_ok!(AssetConversion::<T>::add_liquidity(
+			assert_ok!(HybridOrderbook::<T>::add_liquidity(
 				SystemOrigin::Signed(caller.clone()).into(),
 				Box::new(asset1.clone()),
 				Box::new(asset2.clone()),
 				liquidity1,
 				liquidity2,
-				T::Balance::one(),
-				T::Balance::zero(),
+				T::Unit::one(),
+				T::Unit::zero(),
 				caller.clone(),
 			));
 		}
@@ -352,19 +571,20 @@ mod benchmarks {
 		let asset_out = *path.last().unwrap().clone();
 		assert_ok!(T::Assets::mint_into(asset_in, &caller, max_swap_amount));
 		let init_caller_balance = T::Assets::balance(asset_out.clone(), &caller);
+		next_block::<T>();
 
 		#[extrinsic_call]
 		_(
 			SystemOrigin::Signed(caller.clone()),
 			path,
-			T::Balance::one(),
+			T::Unit::one(),
 			max_swap_amount,
 			caller.clone(),
 			true,
 		);
 
 		let actual_balance = T::Assets::balance(asset_out, &caller);
-		assert_eq!(actual_balance, init_caller_balance + T::Balance::one());
+		assert_eq!(actual_balance, init_caller_balance + T::Unit::one());
 	}
 
 	#[benchmark]
@@ -375,15 +595,23 @@ mod benchmarks {
 		let pool_account = T::PoolLocator::address(&pool_id).unwrap();
 
 		create_fee_asset::<T>(&caller);
-		create_asset::<T>(&caller, &asset1, <T as Config>::Balance::one(), false);
-		create_asset::<T>(&caller, &asset2, <T as Config>::Balance::one(), false);
-		let lp_token = AssetConversion::<T>::get_next_pool_asset_id();
+		create_asset::<T>(&caller, &asset1, T::Unit::one(), false);
+		create_asset::<T>(&caller, &asset2, T::Unit::one(), false);
+		let lp_token = HybridOrderbook::<T>::get_next_pool_asset_id();
 		mint_setup_fee_asset::<T>(&caller, &asset1, &asset2, &lp_token);
 
-		assert_ok!(AssetConversion::<T>::create_pool(
+		assert_ok!(HybridOrderbook::<T>::create_pool(
 			SystemOrigin::Signed(caller.clone()).into(),
 			Box::new(asset1.clone()),
-			Box::new(asset2.clone())
+			0,
+			Box::new(asset2.clone()),
+			0,
+			Permill::zero(),
+			MakerFeeRate::default(),
+			T::Unit::one(),
+			T::Unit::one(),
+			0,
+			LiquidityMode::ConstantProduct,
 		));
 
 		if n > 0 &&
@@ -409,5 +637,419 @@ mod benchmarks {
 		assert_last_event::<T>(Event::Touched { pool_id, who: caller }.into());
 	}
 
-	impl_benchmark_test_suite!(AssetConversion, crate::mock::new_test_ext(), crate::mock::Test);
+	#[benchmark]
+	fn market_order(
+		l: Linear<1, { T::MaxOrdersFilled::get() }>,
+		o: Linear<1, { T::MaxOrdersFilled::get() }>,
+	) {
+		let caller: T::AccountId = whitelisted_caller();
+		let (base, quote) = create_orderbook_pool::<T>(&caller);
+		place_asks::<T>(&base, &quote, l, orders_filled::<T>(l, o));
+		// Crosses all price levels and fills the rest from the pool
+		let quantity: T::Unit = (BASE_RESERVE / 5).into();
+		next_block::<T>();
+
+		#[extrinsic_call]
+		_(
+			SystemOrigin::Signed(caller.clone()),
+			Box::new(base.clone()),
+			Box::new(quote.clone()),
+			quantity,
+			true,
+			None,
+			None,
+		);
+
+		let pool_id = T::PoolLocator::pool_id(&base, &quote).unwrap();
+		assert!(Pools::<T>::get(&pool_id).unwrap().next_ask_order().is_none());
+		assert_last_event::<T>(Event::MarketOrder { taker: caller }.into());
+	}
+
+	#[benchmark]
+	fn limit_order(
+		l: Linear<1, { T::MaxOrdersFilled::get() }>,
+		o: Linear<1, { T::MaxOrdersFilled::get() }>,
+	) {
+		let caller: T::AccountId = whitelisted_caller();
+		let (base, quote) = create_orderbook_pool::<T>(&caller);
+		place_asks::<T>(&base, &quote, l, orders_filled::<T>(l, o));
+		// Crosses all price levels and places the rest on the orderbook
+		let price: T::Unit = (POOL_PRICE + l).into();
+		let quantity: T::Unit = (BASE_RESERVE / 5).into();
+		next_block::<T>();
+
+		#[extrinsic_call]
+		_(
+			SystemOrigin::Signed(caller.clone()),
+			Box::new(base.clone()),
+			Box::new(quote.clone()),
+			true,
+			price,
+			quantity,
+			TimeInForce::GoodTilCancel,
+		);
+
+		let pool_id = T::PoolLocator::pool_id(&base, &quote).unwrap();
+		let pool = Pools::<T>::get(&pool_id).unwrap();
+		assert!(pool.next_ask_order().is_none());
+		assert_eq!(pool.next_bid_order().map(|(price, _)| price), Some(price));
+		assert_last_event::<T>(
+			Event::LimitOrder {
+				pool_id,
+				maker: caller,
+				order_price: price,
+				order_quantity: quantity,
+				is_bid: true,
+			}
+			.into(),
+		);
+	}
+
+	#[benchmark]
+	fn cancel_order() {
+		let caller: T::AccountId = whitelisted_caller();
+		let (base, quote) = create_orderbook_pool::<T>(&caller);
+		let price: T::Unit = (POOL_PRICE + 1).into();
+		assert_ok!(HybridOrderbook::<T>::limit_order(
+			SystemOrigin::Signed(caller.clone()).into(),
+			Box::new(base.clone()),
+			Box::new(quote.clone()),
+			false,
+			price,
+			T::Unit::one(),
+			TimeInForce::GoodTilCancel,
+		));
+		// The price level of the order is full
+		place_asks::<T>(&base, &quote, 1, T::MaxOrdersPerTick::get() - 1);
+		let pool_id = T::PoolLocator::pool_id(&base, &quote).unwrap();
+		let order_id =
+			OpenOrders::<T>::iter_key_prefix((caller.clone(), pool_id.clone())).next().unwrap();
+
+		#[extrinsic_call]
+		_(
+			SystemOrigin::Signed(caller.clone()),
+			Box::new(base.clone()),
+			Box::new(quote.clone()),
+			price,
+			order_id,
+			T::Unit::one(),
+		);
+
+		assert_last_event::<T>(
+			Event::OrderCancelled { pool_id, owner: caller, order_id, released: T::Unit::one() }
+				.into(),
+		);
+	}
+
+	#[benchmark]
+	fn cancel_order_by_id() {
+		let caller: T::AccountId = whitelisted_caller();
+		let (base, quote) = create_orderbook_pool::<T>(&caller);
+		let price: T::Unit = (POOL_PRICE + 1).into();
+		assert_ok!(HybridOrderbook::<T>::limit_order(
+			SystemOrigin::Signed(caller.clone()).into(),
+			Box::new(base.clone()),
+			Box::new(quote.clone()),
+			false,
+			price,
+			T::Unit::one(),
+			TimeInForce::GoodTilCancel,
+		));
+		// The price level of the order is full
+		place_asks::<T>(&base, &quote, 1, T::MaxOrdersPerTick::get() - 1);
+		let pool_id = T::PoolLocator::pool_id(&base, &quote).unwrap();
+		let order_id =
+			OpenOrders::<T>::iter_key_prefix((caller.clone(), pool_id.clone())).next().unwrap();
+
+		#[extrinsic_call]
+		_(
+			SystemOrigin::Signed(caller.clone()),
+			Box::new(base.clone()),
+			Box::new(quote.clone()),
+			order_id,
+		);
+
+		assert_last_event::<T>(
+			Event::OrderCancelled { pool_id, owner: caller, order_id, released: T::Unit::one() }
+				.into(),
+		);
+	}
+
+	#[benchmark]
+	fn cancel_all_orders(n: Linear<1, { T::MaxOpenOrders::get() }>) {
+		let caller: T::AccountId = whitelisted_caller();
+		let (base, quote) = create_orderbook_pool::<T>(&caller);
+		create_asset::<T>(&caller, &base, n.into(), true);
+		// Each order is on its own price level
+		for i in 0..n {
+			if i > 0 {
+				fund_order_deposit::<T>(&caller);
+			}
+			assert_ok!(HybridOrderbook::<T>::limit_order(
+				SystemOrigin::Signed(caller.clone()).into(),
+				Box::new(base.clone()),
+				Box::new(quote.clone()),
+				false,
+				(POOL_PRICE + i + 1).into(),
+				T::Unit::one(),
+				TimeInForce::GoodTilCancel,
+			));
+		}
+
+		#[extrinsic_call]
+		_(SystemOrigin::Signed(caller.clone()), Box::new(base.clone()), Box::new(quote.clone()));
+
+		let pool_id = T::PoolLocator::pool_id(&base, &quote).unwrap();
+		assert!(OpenOrders::<T>::iter_key_prefix((caller, pool_id)).next().is_none());
+	}
+
+	#[benchmark]
+	fn amend_order() {
+		let caller: T::AccountId = whitelisted_caller();
+		let (base, quote) = create_orderbook_pool::<T>(&caller);
+		create_asset::<T>(&caller, &base, 2u32.into(), true);
+		let price: T::Unit = (POOL_PRICE + 1).into();
+		assert_ok!(HybridOrderbook::<T>::limit_order(
+			SystemOrigin::Signed(caller.clone()).into(),
+			Box::new(base.clone()),
+			Box::new(quote.clone()),
+			false,
+			price,
+			T::Unit::one(),
+			TimeInForce::GoodTilCancel,
+		));
+		// The price level of the order is full
+		place_asks::<T>(&base, &quote, 1, T::MaxOrdersPerTick::get() - 1);
+		let pool_id = T::PoolLocator::pool_id(&base, &quote).unwrap();
+		let order_id =
+			OpenOrders::<T>::iter_key_prefix((caller.clone(), pool_id.clone())).next().unwrap();
+		// Moving the price and increasing the quantity re-places the order on a new price level
+		let new_price: T::Unit = (POOL_PRICE + 2).into();
+		let new_quantity: T::Unit = 2u32.into();
+
+		#[extrinsic_call]
+		_(
+			SystemOrigin::Signed(caller.clone()),
+			Box::new(base.clone()),
+			Box::new(quote.clone()),
+			order_id,
+			new_price,
+			new_quantity,
+		);
+
+		let new_order_id =
+			OpenOrders::<T>::iter_key_prefix((caller.clone(), pool_id.clone())).next().unwrap();
+		assert_last_event::<T>(
+			Event::OrderAmended {
+				pool_id,
+				owner: caller,
+				order_id,
+				new_order_id,
+				price: new_price,
+				quantity: new_quantity,
+				is_bid: false,
+			}
+			.into(),
+		);
+	}
+
+	#[benchmark]
+	fn stop_limit_order(s: Linear<0, { T::MaxStopOrders::get() - 1 }>) {
+		let caller: T::AccountId = whitelisted_caller();
+		let (base, quote) = create_orderbook_pool::<T>(&caller);
+		place_stop_asks::<T>(&base, &quote, s);
+		let price: T::Unit = (POOL_PRICE + 1).into();
+
+		#[extrinsic_call]
+		_(
+			SystemOrigin::Signed(caller.clone()),
+			Box::new(base.clone()),
+			Box::new(quote.clone()),
+			true,
+			price,
+			price,
+			T::Unit::one(),
+		);
+
+		let pool_id = T::PoolLocator::pool_id(&base, &quote).unwrap();
+		assert_last_event::<T>(
+			Event::StopLimitOrderPlaced {
+				pool_id,
+				owner: caller,
+				stop_order_id: s.into(),
+				stop_price: price,
+				limit_price: price,
+				quantity: T::Unit::one(),
+				is_bid: true,
+			}
+			.into(),
+		);
+	}
+
+	#[benchmark]
+	fn cancel_stop_limit_order(s: Linear<1, { T::MaxStopOrders::get() }>) {
+		let caller: T::AccountId = whitelisted_caller();
+		let (base, quote) = create_orderbook_pool::<T>(&caller);
+		place_stop_asks::<T>(&base, &quote, s - 1);
+		let price: T::Unit = (POOL_PRICE + 1).into();
+		assert_ok!(HybridOrderbook::<T>::stop_limit_order(
+			SystemOrigin::Signed(caller.clone()).into(),
+			Box::new(base.clone()),
+			Box::new(quote.clone()),
+			true,
+			price,
+			price,
+			T::Unit::one(),
+		));
+		let stop_order_id: StopOrderId = (s - 1).into();
+
+		#[extrinsic_call]
+		_(
+			SystemOrigin::Signed(caller.clone()),
+			Box::new(base.clone()),
+			Box::new(quote.clone()),
+			stop_order_id,
+		);
+
+		let pool_id = T::PoolLocator::pool_id(&base, &quote).unwrap();
+		assert_eq!(TriggerBooks::<T>::get(&pool_id).len(), s - 1);
+		assert_last_event::<T>(
+			Event::StopLimitOrderCancelled {
+				pool_id,
+				owner: caller,
+				stop_order_id,
+				released: price,
+			}
+			.into(),
+		);
+	}
+
+	#[benchmark]
+	fn expire_order() {
+		let caller: T::AccountId = whitelisted_caller();
+		let (base, quote) = create_orderbook_pool::<T>(&caller);
+		let price: T::Unit = (POOL_PRICE + 1).into();
+		// The price level of the order is full
+		place_asks::<T>(&base, &quote, 1, T::MaxOrdersPerTick::get() - 1);
+		// Placed a block later than the others, so it is the only order swept
+		let placed_at = frame_system::Pallet::<T>::block_number() + One::one();
+		frame_system::Pallet::<T>::set_block_number(placed_at);
+		assert_ok!(HybridOrderbook::<T>::limit_order(
+			SystemOrigin::Signed(caller.clone()).into(),
+			Box::new(base.clone()),
+			Box::new(quote.clone()),
+			false,
+			price,
+			T::Unit::one(),
+			TimeInForce::GoodTilCancel,
+		));
+		let expired_at = placed_at + T::OrderExpiration::get();
+		frame_system::Pallet::<T>::set_block_number(expired_at);
+		ExpirySweepCursor::<T>::put(expired_at);
+		let pool_id = T::PoolLocator::pool_id(&base, &quote).unwrap();
+		let order_id =
+			OpenOrders::<T>::iter_key_prefix((caller.clone(), pool_id.clone())).next().unwrap();
+
+		#[block]
+		{
+			HybridOrderbook::<T>::do_sweep_expired_orders(expired_at, Weight::MAX);
+		}
+
+		assert_last_event::<T>(
+			Event::OrderExpired { pool_id, owner: caller, order_id, released: T::Unit::one() }
+				.into(),
+		);
+	}
+
+	#[benchmark]
+	fn open_position(b: Linear<2, { T::MaxLiquidityBoundaries::get() }>) {
+		let caller: T::AccountId = whitelisted_caller();
+		let positions = (b - 2) / 2;
+		let (base, quote) = create_concentrated_pool::<T>(&caller, positions);
+		open_positions::<T>(&caller, &base, &quote, positions);
+		let lower: T::Unit = (POOL_PRICE + 2 * positions + 1).into();
+		next_block::<T>();
+
+		#[extrinsic_call]
+		_(
+			SystemOrigin::Signed(caller.clone()),
+			Box::new(base.clone()),
+			Box::new(quote.clone()),
+			lower,
+			lower + T::Unit::one(),
+			POSITION_LIQUIDITY.into(),
+			T::Unit::zero(),
+			T::Unit::zero(),
+			T::Unit::zero(),
+		);
+
+		let pool_id = T::PoolLocator::pool_id(&base, &quote).unwrap();
+		assert!(Positions::<T>::contains_key(&pool_id, positions as PositionId));
+	}
+
+	#[benchmark]
+	fn close_position(b: Linear<2, { T::MaxLiquidityBoundaries::get() }>) {
+		let caller: T::AccountId = whitelisted_caller();
+		let positions = b / 2;
+		let (base, quote) = create_concentrated_pool::<T>(&caller, positions);
+		open_positions::<T>(&caller, &base, &quote, positions);
+		let position_id = (positions - 1) as PositionId;
+		next_block::<T>();
+
+		#[extrinsic_call]
+		_(
+			SystemOrigin::Signed(caller.clone()),
+			Box::new(base.clone()),
+			Box::new(quote.clone()),
+			position_id,
+			T::Unit::zero(),
+			T::Unit::zero(),
+		);
+
+		let pool_id = T::PoolLocator::pool_id(&base, &quote).unwrap();
+		assert!(!Positions::<T>::contains_key(&pool_id, position_id));
+	}
+
+	#[benchmark]
+	fn swap_concentrated(b: Linear<2, { T::MaxLiquidityBoundaries::get() }>) {
+		let caller: T::AccountId = whitelisted_caller();
+		let positions = b / 2;
+		let (base, quote) = create_concentrated_pool::<T>(&caller, positions);
+		open_positions::<T>(&caller, &base, &quote, positions);
+		// Ends within the range of the last position, crossing all the other boundaries
+		let amount_out: T::Unit = (POSITION_LIQUIDITY * positions - POSITION_LIQUIDITY / 2).into();
+		let init_caller_balance = T::Assets::balance(base.clone(), &caller);
+		next_block::<T>();
+
+		#[block]
+		{
+			assert_ok!(HybridOrderbook::<T>::do_swap_tokens_for_exact_tokens(
+				&caller,
+				vec![quote.clone(), base.clone()],
+				amount_out,
+				None,
+				&caller,
+				true,
+			));
+		}
+
+		assert_eq!(T::Assets::balance(base, &caller), init_caller_balance + amount_out);
+	}
+
+	#[benchmark]
+	fn get_pool_query(l: Linear<1, { T::MaxTicks::get() }>) {
+		let caller: T::AccountId = whitelisted_caller();
+		let (base, quote) = create_orderbook_pool::<T>(&caller);
+		place_asks::<T>(&base, &quote, l, l);
+		let query;
+
+		#[block]
+		{
+			query = HybridOrderbook::<T>::get_pool_query(&base, &quote);
+		}
+
+		assert!(query.is_ok());
+	}
+
+	impl_benchmark_test_suite!(HybridOrderbook, crate::mock::new_test_ext(), crate::mock::Test);
 }
@@ -0,0 +1,640 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Concentrated liquidity of the pool, which is provided within the price ranges of the positions
+//! rather than along the whole price curve.
+//!
+//! Prices are tracked as the square root of the raw price, which is the amount of quote asset per
+//! base asset without the decimals. Between two boundaries of the ranges, the pool behaves as the
+//! constant product pool of the virtual reserves, `liquidity / sqrt_price` of base asset and
+//! `liquidity * sqrt_price` of quote asset. Amounts paid by the pool are rounded down and amounts
+//! paid to the pool are rounded up, so the pool always holds what it owes to the positions.
+
+use super::*;
+use codec::{Decode, Encode, MaxEncodedLen};
+use core::ops::Bound::{Excluded, Unbounded};
+use scale_info::TypeInfo;
+use sp_arithmetic::{helpers_128bit::multiply_by_rational_with_rounding, Rounding};
+use sp_core::{RuntimeDebug, U256};
+use sp_runtime::BoundedBTreeMap;
+
+/// Id of the liquidity position. Increments across all pools.
+pub type PositionId = u64;
+
+pub type PositionOf<T> = Position<<T as frame_system::Config>::AccountId, <T as Config>::Unit>;
+
+/// Liquidity provided within the price range of `lower` and `upper`
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq, MaxEncodedLen, TypeInfo)]
+pub struct Position<AccountId, Unit> {
+    /// Owner of the position
+    pub owner: AccountId,
+    /// Lower bound of the price range, scaled by `pool_decimals`
+    pub lower: Unit,
+    /// Upper bound of the price range, scaled by `pool_decimals`
+    pub upper: Unit,
+    /// Liquidity of the position
+    pub liquidity: Unit,
+    /// Fee growth of base asset per liquidity within the range, as of the last collection
+    pub fee_growth_inside_base: FixedU128,
+    /// Fee growth of quote asset per liquidity within the range, as of the last collection
+    pub fee_growth_inside_quote: FixedU128,
+}
+
+pub type LiquidityBoundaryOf<T> = LiquidityBoundary<<T as Config>::Unit>;
+
+/// Price where the range of any position starts or ends
+#[derive(Encode, Decode, Debug, Default, Clone, PartialEq, Eq, MaxEncodedLen, TypeInfo)]
+pub struct LiquidityBoundary<Unit> {
+    /// Square root of the raw price of the boundary
+    pub sqrt_price: FixedU128,
+    /// Liquidity of the positions whose range starts from the boundary
+    pub liquidity_starting: Unit,
+    /// Liquidity of the positions whose range ends at the boundary
+    pub liquidity_ending: Unit,
+    /// Fee growth of base asset per liquidity on the other side of the boundary from the current
+    /// price
+    pub fee_growth_outside_base: FixedU128,
+    /// Fee growth of quote asset per liquidity on the other side of the boundary from the current
+    /// price
+    pub fee_growth_outside_quote: FixedU128,
+}
+
+/// Result of the swap on the concentrated liquidity
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct ConcentratedSwap<Unit> {
+    /// Amount paid to the pool including the fee
+    pub amount_in: Unit,
+    /// Amount paid by the pool
+    pub amount_out: Unit,
+}
+
+/// Liquidity of the pool created with [`LiquidityMode::Concentrated`]. Boundaries are bounded by
+/// [`Config::MaxLiquidityBoundaries`].
+#[derive(Encode, Decode, Debug, Clone, PartialEq, MaxEncodedLen, TypeInfo)]
+#[scale_info(skip_type_params(T))]
+#[codec(mel_bound())]
+pub struct ConcentratedLiquidity<T: Config> {
+    /// Square root of the current raw price
+    pub sqrt_price: FixedU128,
+    /// Liquidity of the positions whose range contains the current price
+    pub liquidity: T::Unit,
+    /// The greatest boundary crossed by the price, whose liquidity is counted in `liquidity`.
+    /// Boundaries below the price are always crossed and the ones above are never crossed, while
+    /// the one at the price could be either.
+    crossed: Option<T::Unit>,
+    /// Fee growth of base asset per liquidity since the pool is created
+    pub fee_growth_base: FixedU128,
+    /// Fee growth of quote asset per liquidity since the pool is created
+    pub fee_growth_quote: FixedU128,
+    /// Boundaries of the ranges of the positions by price
+    boundaries: BoundedBTreeMap<T::Unit, LiquidityBoundaryOf<T>, T::MaxLiquidityBoundaries>,
+}
+
+impl<T: Config> ConcentratedLiquidity<T> {
+    /// Create the liquidity without any position at `sqrt_price`
+    pub fn new(sqrt_price: FixedU128) -> Self {
+        Self {
+            sqrt_price,
+            liquidity: Zero::zero(),
+            crossed: None,
+            fee_growth_base: Zero::zero(),
+            fee_growth_quote: Zero::zero(),
+            boundaries: BoundedBTreeMap::new(),
+        }
+    }
+
+    /// The number of boundaries of the ranges of the positions
+    pub fn boundaries_len(&self) -> u32 {
+        self.boundaries.len() as u32
+    }
+
+    /// Whether the price has crossed the boundary at `price` upward
+    fn is_crossed(&self, price: T::Unit) -> bool {
+        self.crossed.map_or(false, |crossed| price <= crossed)
+    }
+
+    /// The boundary which the price meets first when it goes up or down, with its square root
+    /// price
+    fn next_boundary(&self, price_up: bool) -> Option<(T::Unit, u128)> {
+        let next = match (self.crossed, price_up) {
+            (Some(crossed), true) => self.boundaries.range((Excluded(crossed), Unbounded)).next(),
+            (Some(crossed), false) => self.boundaries.get_key_value(&crossed),
+            (None, true) => self.boundaries.iter().next(),
+            (None, false) => None,
+        };
+        next.map(|(price, boundary)| (*price, boundary.sqrt_price.into_inner()))
+    }
+
+    /// Cross the boundary at `price`, applying the liquidity of the positions starting or ending
+    /// there and flipping its fee growth outside
+    fn cross(&mut self, price: T::Unit, price_up: bool) -> Result<(), Error<T>> {
+        let (fee_growth_base, fee_growth_quote) = (self.fee_growth_base, self.fee_growth_quote);
+        let below = self.boundaries.range(..price).next_back().map(|(price, _)| *price);
+        let boundary = self.boundaries.get_mut(&price).ok_or(Error::<T>::Overflow)?;
+        boundary.fee_growth_outside_base =
+            wrapping_sub(fee_growth_base, boundary.fee_growth_outside_base);
+        boundary.fee_growth_outside_quote =
+            wrapping_sub(fee_growth_quote, boundary.fee_growth_outside_quote);
+        let (added, removed) = if price_up {
+            (boundary.liquidity_starting, boundary.liquidity_ending)
+        } else {
+            (boundary.liquidity_ending, boundary.liquidity_starting)
+        };
+        self.liquidity = self
+            .liquidity
+            .checked_add(&added)
+            .and_then(|liquidity| liquidity.checked_sub(&removed))
+            .ok_or(Error::<T>::Overflow)?;
+        self.crossed = if price_up { Some(price) } else { below };
+        Ok(())
+    }
+
+    /// Swap `amount` on the liquidity, crossing the boundaries on the way. `amount` is paid to
+    /// the pool including the fee if `exact_in`, otherwise it is paid by the pool. Base asset is
+    /// paid to the pool if `base_in`, otherwise quote asset is.
+    ///
+    /// If `limit` of the square root price is given, the swap stops once the price reaches it or
+    /// the liquidity runs out. Otherwise, it reverts if the liquidity runs out.
+    pub fn swap(
+        &mut self,
+        base_in: bool,
+        exact_in: bool,
+        amount: T::Unit,
+        limit: Option<FixedU128>,
+    ) -> Result<ConcentratedSwap<T::Unit>, Error<T>> {
+        let fee_rate = 1000u128
+            .checked_sub(T::LPFee::get().into())
+            .filter(|rate| !rate.is_zero())
+            .ok_or(Error::<T>::Overflow)?;
+        let gross =
+            |net: u128| mul_div(net, 1000, fee_rate, Rounding::Up).ok_or(Error::<T>::Overflow);
+        let price_up = !base_in;
+        let limit = limit.map(|limit| limit.into_inner());
+        let mut remaining = amount.saturated_into::<u128>();
+        let (mut amount_in, mut amount_out) = (0u128, 0u128);
+        while !remaining.is_zero() {
+            let sqrt_price = self.sqrt_price.into_inner();
+            let is_beyond = |price: u128| {
+                if price_up { sqrt_price >= price } else { sqrt_price <= price }
+            };
+            if limit.map_or(false, is_beyond) {
+                break;
+            }
+            let boundary = self.next_boundary(price_up);
+            if let Some((price, boundary_sqrt_price)) = boundary {
+                if is_beyond(boundary_sqrt_price) {
+                    self.cross(price, price_up)?;
+                    continue;
+                }
+            }
+            // Price where this step ends at the latest
+            let target = match (boundary.map(|(_, sqrt_price)| sqrt_price), limit) {
+                (Some(boundary), Some(limit)) => {
+                    Some(if price_up { boundary.min(limit) } else { boundary.max(limit) })
+                },
+                (boundary, limit) => boundary.or(limit),
+            };
+            let liquidity = self.liquidity.saturated_into::<u128>();
+            if liquidity.is_zero() {
+                // Nothing to swap until the next boundary
+                match target {
+                    Some(target) => {
+                        self.sqrt_price = FixedU128::from_inner(target);
+                        continue;
+                    },
+                    None if limit.is_some() => break,
+                    None if exact_in => return Err(Error::<T>::ZeroLiquidity),
+                    None => return Err(Error::<T>::AmountOutTooHigh),
+                }
+            }
+            // Net amount paid to and amount paid by the pool while the price moves to `to`
+            let net_in_to = |to: u128| {
+                if price_up {
+                    quote_amount(liquidity, sqrt_price, to, Rounding::Up)
+                } else {
+                    base_amount(liquidity, to, sqrt_price, Rounding::Up)
+                }
+                .ok_or(Error::<T>::Overflow)
+            };
+            let out_to = |to: u128| {
+                if price_up {
+                    base_amount(liquidity, sqrt_price, to, Rounding::Down)
+                } else {
+                    quote_amount(liquidity, to, sqrt_price, Rounding::Down)
+                }
+                .ok_or(Error::<T>::Overflow)
+            };
+            // Rounded price never passes the target
+            let clamp = |next: u128| match target {
+                Some(target) if price_up => next.min(target),
+                Some(target) => next.max(target),
+                None => next,
+            };
+            let (next, net_in, gross_in, out) = if exact_in {
+                let available = mul_div(remaining, fee_rate, 1000, Rounding::Down)
+                    .ok_or(Error::<T>::Overflow)?;
+                let to_target = match target {
+                    Some(target) => Some((target, net_in_to(target)?)),
+                    None => None,
+                };
+                let (next, net_in, gross_in) = match to_target {
+                    Some((target, need)) if available >= need => (target, need, gross(need)?),
+                    _ => {
+                        let next = if price_up {
+                            sqrt_price_after_quote(liquidity, sqrt_price, available, true)
+                        } else {
+                            sqrt_price_after_base(liquidity, sqrt_price, available, true)
+                        }
+                        .ok_or(Error::<T>::Overflow)?;
+                        (clamp(next), available, remaining)
+                    },
+                };
+                (next, net_in, gross_in, out_to(next)?)
+            } else {
+                let to_target = match target {
+                    Some(target) => Some((target, out_to(target)?)),
+                    None => None,
+                };
+                let (next, out) = match to_target {
+                    Some((target, available)) if remaining >= available => (target, available),
+                    _ => {
+                        let next = if price_up {
+                            sqrt_price_after_base(liquidity, sqrt_price, remaining, false)
+                        } else {
+                            sqrt_price_after_quote(liquidity, sqrt_price, remaining, false)
+                        }
+                        .ok_or(Error::<T>::AmountOutTooHigh)?;
+                        (clamp(next), remaining)
+                    },
+                };
+                let net_in = net_in_to(next)?;
+                (next, net_in, gross(net_in)?, out)
+            };
+            // Fee is shared by the liquidity of this step
+            let fee_growth = FixedU128::from_inner(
+                mul_div(gross_in.saturating_sub(net_in), FixedU128::DIV, liquidity, Rounding::Down)
+                    .ok_or(Error::<T>::Overflow)?,
+            );
+            if base_in {
+                self.fee_growth_base = wrapping_add(self.fee_growth_base, fee_growth);
+            } else {
+                self.fee_growth_quote = wrapping_add(self.fee_growth_quote, fee_growth);
+            }
+            self.sqrt_price = FixedU128::from_inner(next);
+            remaining = remaining.saturating_sub(if exact_in { gross_in } else { out });
+            amount_in = amount_in.checked_add(gross_in).ok_or(Error::<T>::Overflow)?;
+            amount_out = amount_out.checked_add(out).ok_or(Error::<T>::Overflow)?;
+        }
+        Ok(ConcentratedSwap {
+            amount_in: to_unit::<T>(amount_in)?,
+            amount_out: to_unit::<T>(amount_out)?,
+        })
+    }
+
+    /// Amount of quote asset worth `amount` of base asset if `is_base`, otherwise the amount of
+    /// base asset worth `amount` of quote asset, at the current price without the fee and the
+    /// price impact
+    pub fn spot_quote(&self, is_base: bool, amount: T::Unit) -> Result<T::Unit, Error<T>> {
+        let sqrt_price = self.sqrt_price.into_inner();
+        let amount = amount.saturated_into::<u128>();
+        let quoted = if is_base {
+            mul_div(amount, sqrt_price, FixedU128::DIV, Rounding::Down)
+                .and_then(|scaled| mul_div(scaled, sqrt_price, FixedU128::DIV, Rounding::Down))
+        } else {
+            mul_div(amount, FixedU128::DIV, sqrt_price, Rounding::Down)
+                .and_then(|scaled| mul_div(scaled, FixedU128::DIV, sqrt_price, Rounding::Down))
+        };
+        to_unit::<T>(quoted.ok_or(Error::<T>::Overflow)?)
+    }
+
+    /// Liquidity which could be provided within the range of `lower` and `upper` with up to
+    /// `base_amount` of base asset and `quote_amount` of quote asset at the current price
+    pub fn liquidity_for_amounts(
+        &self,
+        lower: FixedU128,
+        upper: FixedU128,
+        base_amount: T::Unit,
+        quote_amount: T::Unit,
+    ) -> Result<T::Unit, Error<T>> {
+        let (lower, upper) = (lower.into_inner(), upper.into_inner());
+        let current = self.sqrt_price.into_inner().clamp(lower, upper);
+        let base_amount = base_amount.saturated_into::<u128>();
+        let quote_amount = quote_amount.saturated_into::<u128>();
+        let liquidity = if current == lower {
+            liquidity_for_base(base_amount, lower, upper)
+        } else if current == upper {
+            liquidity_for_quote(quote_amount, lower, upper)
+        } else {
+            liquidity_for_base(base_amount, current, upper)
+                .zip(liquidity_for_quote(quote_amount, lower, current))
+                .map(|(base, quote)| base.min(quote))
+        };
+        to_unit::<T>(liquidity.ok_or(Error::<T>::Overflow)?)
+    }
+
+    /// Amounts of base and quote asset of `liquidity` within the range of `lower` and `upper` at
+    /// the current price
+    pub fn amounts_for_liquidity(
+        &self,
+        lower: FixedU128,
+        upper: FixedU128,
+        liquidity: T::Unit,
+        rounding: Rounding,
+    ) -> Result<(T::Unit, T::Unit), Error<T>> {
+        let (lower, upper) = (lower.into_inner(), upper.into_inner());
+        let current = self.sqrt_price.into_inner().clamp(lower, upper);
+        let liquidity = liquidity.saturated_into::<u128>();
+        let base = base_amount(liquidity, current, upper, rounding).ok_or(Error::<T>::Overflow)?;
+        let quote = quote_amount(liquidity, lower, current, rounding).ok_or(Error::<T>::Overflow)?;
+        Ok((to_unit::<T>(base)?, to_unit::<T>(quote)?))
+    }
+
+    /// Add `liquidity` of the position within the range of `lower` and `upper`, whose square root
+    /// prices are `lower_sqrt_price` and `upper_sqrt_price`. Boundaries are created if not exist.
+    pub fn add_position(
+        &mut self,
+        (lower, lower_sqrt_price): (T::Unit, FixedU128),
+        (upper, upper_sqrt_price): (T::Unit, FixedU128),
+        liquidity: T::Unit,
+    ) -> Result<(), Error<T>> {
+        self.ensure_boundary(lower, lower_sqrt_price)?;
+        self.ensure_boundary(upper, upper_sqrt_price)?;
+        let boundary = self.boundaries.get_mut(&lower).ok_or(Error::<T>::Overflow)?;
+        boundary.liquidity_starting =
+            boundary.liquidity_starting.checked_add(&liquidity).ok_or(Error::<T>::Overflow)?;
+        let boundary = self.boundaries.get_mut(&upper).ok_or(Error::<T>::Overflow)?;
+        boundary.liquidity_ending =
+            boundary.liquidity_ending.checked_add(&liquidity).ok_or(Error::<T>::Overflow)?;
+        if self.is_crossed(lower) && !self.is_crossed(upper) {
+            self.liquidity = self.liquidity.checked_add(&liquidity).ok_or(Error::<T>::Overflow)?;
+        }
+        Ok(())
+    }
+
+    /// Remove `liquidity` of the position within the range of `lower` and `upper`. Boundaries
+    /// which no position refers to are removed.
+    pub fn remove_position(
+        &mut self,
+        lower: T::Unit,
+        upper: T::Unit,
+        liquidity: T::Unit,
+    ) -> Result<(), Error<T>> {
+        if self.is_crossed(lower) && !self.is_crossed(upper) {
+            self.liquidity = self.liquidity.checked_sub(&liquidity).ok_or(Error::<T>::Overflow)?;
+        }
+        let boundary = self.boundaries.get_mut(&lower).ok_or(Error::<T>::Overflow)?;
+        boundary.liquidity_starting =
+            boundary.liquidity_starting.checked_sub(&liquidity).ok_or(Error::<T>::Overflow)?;
+        let boundary = self.boundaries.get_mut(&upper).ok_or(Error::<T>::Overflow)?;
+        boundary.liquidity_ending =
+            boundary.liquidity_ending.checked_sub(&liquidity).ok_or(Error::<T>::Overflow)?;
+        self.remove_unused_boundary(lower);
+        self.remove_unused_boundary(upper);
+        Ok(())
+    }
+
+    /// Fee growth of base and quote asset per liquidity within the range of `lower` and `upper`,
+    /// whose boundaries should exist
+    pub fn fee_growth_inside(
+        &self,
+        lower: T::Unit,
+        upper: T::Unit,
+    ) -> Result<(FixedU128, FixedU128), Error<T>> {
+        let lower_boundary = self.boundaries.get(&lower).ok_or(Error::<T>::Overflow)?;
+        let upper_boundary = self.boundaries.get(&upper).ok_or(Error::<T>::Overflow)?;
+        let inside = |global: FixedU128, lower_outside: FixedU128, upper_outside: FixedU128| {
+            let below = if self.is_crossed(lower) {
+                lower_outside
+            } else {
+                wrapping_sub(global, lower_outside)
+            };
+            let above = if self.is_crossed(upper) {
+                wrapping_sub(global, upper_outside)
+            } else {
+                upper_outside
+            };
+            wrapping_sub(wrapping_sub(global, below), above)
+        };
+        Ok((
+            inside(
+                self.fee_growth_base,
+                lower_boundary.fee_growth_outside_base,
+                upper_boundary.fee_growth_outside_base,
+            ),
+            inside(
+                self.fee_growth_quote,
+                lower_boundary.fee_growth_outside_quote,
+                upper_boundary.fee_growth_outside_quote,
+            ),
+        ))
+    }
+
+    /// Create the boundary at `price` if not exists. All fee growth so far is assumed to have
+    /// happened below the price, so it is the fee growth outside only if the boundary is below.
+    fn ensure_boundary(&mut self, price: T::Unit, sqrt_price: FixedU128) -> Result<(), Error<T>> {
+        if self.boundaries.contains_key(&price) {
+            return Ok(());
+        }
+        let is_below = self.is_crossed(price) || sqrt_price < self.sqrt_price;
+        let (fee_growth_outside_base, fee_growth_outside_quote) = if is_below {
+            (self.fee_growth_base, self.fee_growth_quote)
+        } else {
+            (Zero::zero(), Zero::zero())
+        };
+        self.boundaries
+            .try_insert(
+                price,
+                LiquidityBoundary {
+                    sqrt_price,
+                    liquidity_starting: Zero::zero(),
+                    liquidity_ending: Zero::zero(),
+                    fee_growth_outside_base,
+                    fee_growth_outside_quote,
+                },
+            )
+            .map_err(|_| Error::<T>::TooManyLiquidityBoundaries)?;
+        if is_below && !self.is_crossed(price) {
+            self.crossed = Some(price);
+        }
+        Ok(())
+    }
+
+    /// Remove the boundary at `price` if no position starts or ends there
+    fn remove_unused_boundary(&mut self, price: T::Unit) {
+        let is_unused = self.boundaries.get(&price).map_or(false, |boundary| {
+            boundary.liquidity_starting.is_zero() && boundary.liquidity_ending.is_zero()
+        });
+        if !is_unused {
+            return;
+        }
+        if self.crossed == Some(price) {
+            self.crossed = self.boundaries.range(..price).next_back().map(|(price, _)| *price);
+        }
+        self.boundaries.remove(&price);
+    }
+}
+
+impl<T: Config> Pool<T> {
+    /// Square root of the raw price of `price` scaled by `pool_decimals`
+    pub fn sqrt_price(&self, price: T::Unit) -> Result<FixedU128, Error<T>> {
+        let (price_scale, base_scale) = self.raw_price_scales()?;
+        let numerator = price
+            .saturated_into::<u128>()
+            .checked_mul(base_scale)
+            .ok_or(Error::<T>::Overflow)?;
+        FixedU128::checked_from_rational(numerator, price_scale)
+            .and_then(|raw_price| raw_price.try_sqrt())
+            .ok_or(Error::<T>::Overflow)
+    }
+
+    /// Price scaled by `pool_decimals` of the square root of the raw price, rounded to the
+    /// nearest. Inverse of [`Self::sqrt_price`].
+    pub fn price_of_sqrt(&self, sqrt_price: FixedU128) -> Result<T::Unit, Error<T>> {
+        let (price_scale, base_scale) = self.raw_price_scales()?;
+        let sqrt_price = sqrt_price.into_inner();
+        let denominator = FixedU128::DIV.checked_mul(base_scale).ok_or(Error::<T>::Overflow)?;
+        let price = mul_div(sqrt_price, sqrt_price, FixedU128::DIV, Rounding::NearestPrefDown)
+            .and_then(|raw_price| {
+                mul_div(raw_price, price_scale, denominator, Rounding::NearestPrefDown)
+            })
+            .ok_or(Error::<T>::Overflow)?;
+        to_unit::<T>(price)
+    }
+
+    /// Scales of the price and the base asset between the price and the raw price, where
+    /// `raw_price = price * base_scale / price_scale`
+    fn raw_price_scales(&self) -> Result<(u128, u128), Error<T>> {
+        let (p_adj, b_adj, q_adj) = self.decimal_adjustment();
+        let price_decimals = u32::from(p_adj.unwrap_or(0)) + u32::from(q_adj.unwrap_or(0));
+        Ok((pow10::<T>(price_decimals)?, pow10::<T>(b_adj.unwrap_or(0).into())?))
+    }
+}
+
+/// Square root price after `amount` of base asset is paid to(`base_in`) or by the pool.
+/// `liquidity * sqrt_price / (liquidity ± amount * sqrt_price)`, rounded up.
+fn sqrt_price_after_base(
+    liquidity: u128,
+    sqrt_price: u128,
+    amount: u128,
+    base_in: bool,
+) -> Option<u128> {
+    let scaled = U256::from(liquidity).checked_mul(U256::from(FixedU128::DIV))?;
+    let product = U256::from(amount).checked_mul(U256::from(sqrt_price))?;
+    let denominator = if base_in {
+        scaled.checked_add(product)?
+    } else {
+        scaled.checked_sub(product).filter(|denominator| !denominator.is_zero())?
+    };
+    div(scaled.checked_mul(U256::from(sqrt_price))?, denominator, Rounding::Up)
+}
+
+/// Square root price after `amount` of quote asset is paid to(`quote_in`) or by the pool.
+/// `sqrt_price ± amount / liquidity`, rounded down.
+fn sqrt_price_after_quote(
+    liquidity: u128,
+    sqrt_price: u128,
+    amount: u128,
+    quote_in: bool,
+) -> Option<u128> {
+    if quote_in {
+        sqrt_price.checked_add(mul_div(amount, FixedU128::DIV, liquidity, Rounding::Down)?)
+    } else {
+        sqrt_price
+            .checked_sub(mul_div(amount, FixedU128::DIV, liquidity, Rounding::Up)?)
+            .filter(|sqrt_price| !sqrt_price.is_zero())
+    }
+}
+
+/// Amount of base asset of `liquidity` between the square root prices `lower` and `upper`.
+/// `liquidity * (upper - lower) / (lower * upper)`
+fn base_amount(liquidity: u128, lower: u128, upper: u128, rounding: Rounding) -> Option<u128> {
+    let numerator = U256::from(liquidity)
+        .checked_mul(U256::from(upper.checked_sub(lower)?))?
+        .checked_mul(U256::from(FixedU128::DIV))?;
+    div(numerator, U256::from(lower).checked_mul(U256::from(upper))?, rounding)
+}
+
+/// Amount of quote asset of `liquidity` between the square root prices `lower` and `upper`.
+/// `liquidity * (upper - lower)`
+fn quote_amount(liquidity: u128, lower: u128, upper: u128, rounding: Rounding) -> Option<u128> {
+    mul_div(liquidity, upper.checked_sub(lower)?, FixedU128::DIV, rounding)
+}
+
+/// Liquidity of `amount` of base asset between the square root prices `lower` and `upper`,
+/// rounded down. Inverse of [`base_amount`].
+fn liquidity_for_base(amount: u128, lower: u128, upper: u128) -> Option<u128> {
+    let numerator =
+        U256::from(amount).checked_mul(U256::from(lower))?.checked_mul(U256::from(upper))?;
+    let denominator = U256::from(upper.checked_sub(lower)?)
+        .checked_mul(U256::from(FixedU128::DIV))?;
+    div(numerator, denominator, Rounding::Down)
+}
+
+/// Liquidity of `amount` of quote asset between the square root prices `lower` and `upper`,
+/// rounded down. Inverse of [`quote_amount`].
+fn liquidity_for_quote(amount: u128, lower: u128, upper: u128) -> Option<u128> {
+    mul_div(amount, FixedU128::DIV, upper.checked_sub(lower)?, Rounding::Down)
+}
+
+/// `a * b / c` rounded by `rounding`
+fn mul_div(a: u128, b: u128, c: u128, rounding: Rounding) -> Option<u128> {
+    multiply_by_rational_with_rounding(a, b, c, rounding)
+}
+
+/// `numerator / denominator` rounded up or down by `rounding`. `None` if it doesn't fit in `u128`.
+fn div(numerator: U256, denominator: U256, rounding: Rounding) -> Option<u128> {
+    if denominator.is_zero() {
+        return None;
+    }
+    let (quotient, remainder) = numerator.div_mod(denominator);
+    let quotient = if rounding == Rounding::Up && !remainder.is_zero() {
+        quotient.checked_add(U256::one())?
+    } else {
+        quotient
+    };
+    (quotient.bits() <= 128).then(|| quotient.low_u128())
+}
+
+fn pow10<T: Config>(exponent: u32) -> Result<u128, Error<T>> {
+    10u128.checked_pow(exponent).ok_or(Error::<T>::Overflow)
+}
+
+fn to_unit<T: Config>(value: u128) -> Result<T::Unit, Error<T>> {
+    T::Unit::try_from(value).map_err(|_| Error::<T>::Overflow)
+}
+
+/// Fee growth wraps around, while only the difference of two of them is used
+fn wrapping_add(a: FixedU128, b: FixedU128) -> FixedU128 {
+    FixedU128::from_inner(a.into_inner().wrapping_add(b.into_inner()))
+}
+
+fn wrapping_sub(a: FixedU128, b: FixedU128) -> FixedU128 {
+    FixedU128::from_inner(a.into_inner().wrapping_sub(b.into_inner()))
+}
+
+/// Fee of `liquidity` accrued while the fee growth per liquidity went from `last` to `now`,
+/// rounded down
+pub(crate) fn fee_owed<T: Config>(
+    liquidity: T::Unit,
+    now: FixedU128,
+    last: FixedU128,
+) -> Result<T::Unit, Error<T>> {
+    let growth = wrapping_sub(now, last).into_inner();
+    mul_div(liquidity.saturated_into::<u128>(), growth, FixedU128::DIV, Rounding::Down)
+        .ok_or(Error::<T>::Overflow)
+        .and_then(to_unit::<T>)
+}
@@ -1,17 +1,113 @@
 use self::traits::{OrderBookIndex, OrderInterface};
 
 use super::{Order as OrderUnit, *};
-use codec::DecodeWithMemTracking;
+use codec::{DecodeWithMemTracking, MaxEncodedLen};
+use core::marker::PhantomData;
 
+/// Storage of the nodes of [`CritbitTree`]. Tree only keeps its root and indices, while the nodes
+/// are read and written through this trait one by one.
+pub trait NodeStore<K, V> {
+    /// Create new instance of the storage
+    fn new() -> Self;
+
+    /// Get the internal node of given `index`
+    fn internal_node(&self, index: &K) -> Option<InternalNode<K>>;
+
+    /// Insert the internal node of given `index`. Return the previous node if any.
+    fn insert_internal_node(&mut self, index: K, node: InternalNode<K>) -> Option<InternalNode<K>>;
+
+    /// Remove the internal node of given `index`
+    fn remove_internal_node(&mut self, index: &K) -> Option<InternalNode<K>>;
+
+    /// Get the leaf node of given `index`
+    fn leaf(&self, index: &K) -> Option<LeafNode<K, V>>;
+
+    /// Insert the leaf node of given `index`. Return the previous node if any.
+    fn insert_leaf(&mut self, index: K, leaf: LeafNode<K, V>) -> Option<LeafNode<K, V>>;
+
+    /// Remove the leaf node of given `index`
+    fn remove_leaf(&mut self, index: &K) -> Option<LeafNode<K, V>>;
+
+    /// All internal nodes of the tree. Reads the whole tree, so must only be used off-chain.
+    fn internal_nodes(&self) -> Vec<(K, InternalNode<K>)>;
+
+    /// All leaf nodes of the tree. Reads the whole tree, so must only be used off-chain.
+    fn leaves(&self) -> Vec<(K, LeafNode<K, V>)>;
+}
+
+/// Nodes of the tree stored in memory
 #[derive(Encode, Decode, DecodeWithMemTracking, Debug, Default, Clone, PartialEq, Eq, TypeInfo)]
-pub struct CritbitTree<K, V> {
-    /// Index of the root node which is part of the internal nodes.
-    root: K,
+pub struct InMemoryNodes<K, V> {
     /// The internal nodes of the tree
     /// Here, `key` refers to `index` of `InternalNode.
     internal_nodes: BTreeMap<K, InternalNode<K>>,
     /// The leaf nodes of the tree.
     leaves: BTreeMap<K, LeafNode<K, V>>,
+}
+
+impl<K: Ord + Clone, V: Clone> NodeStore<K, V> for InMemoryNodes<K, V> {
+    fn new() -> Self {
+        Self {
+            internal_nodes: BTreeMap::new(),
+            leaves: BTreeMap::new(),
+        }
+    }
+
+    fn internal_node(&self, index: &K) -> Option<InternalNode<K>> {
+        self.internal_nodes.get(index).cloned()
+    }
+
+    fn insert_internal_node(&mut self, index: K, node: InternalNode<K>) -> Option<InternalNode<K>> {
+        self.internal_nodes.insert(index, node)
+    }
+
+    fn remove_internal_node(&mut self, index: &K) -> Option<InternalNode<K>> {
+        self.internal_nodes.remove(index)
+    }
+
+    fn leaf(&self, index: &K) -> Option<LeafNode<K, V>> {
+        self.leaves.get(index).cloned()
+    }
+
+    fn insert_leaf(&mut self, index: K, leaf: LeafNode<K, V>) -> Option<LeafNode<K, V>> {
+        self.leaves.insert(index, leaf)
+    }
+
+    fn remove_leaf(&mut self, index: &K) -> Option<LeafNode<K, V>> {
+        self.leaves.remove(index)
+    }
+
+    fn internal_nodes(&self) -> Vec<(K, InternalNode<K>)> {
+        self.internal_nodes
+            .iter()
+            .map(|(index, node)| (index.clone(), node.clone()))
+            .collect()
+    }
+
+    fn leaves(&self) -> Vec<(K, LeafNode<K, V>)> {
+        self.leaves
+            .iter()
+            .map(|(index, leaf)| (index.clone(), leaf.clone()))
+            .collect()
+    }
+}
+
+#[derive(
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Debug,
+    Default,
+    Clone,
+    PartialEq,
+    Eq,
+    MaxEncodedLen,
+    TypeInfo,
+)]
+#[codec(mel_bound(K: MaxEncodedLen, S: MaxEncodedLen))]
+pub struct CritbitTree<K, V, S = InMemoryNodes<K, V>> {
+    /// Index of the root node which is part of the internal nodes.
+    root: K,
     /// Index of the largest value of the leaf nodes. Could be updated for every insertion.
     max_leaf_index: K,
     /// Index of the smallest value of the leaf nodes. Could be updated for every insertion.
@@ -20,6 +116,11 @@ pub struct CritbitTree<K, V> {
     next_internal_node_index: K,
     /// Index of the next leaf node which should be incremented for every insertion.
     next_leaf_node_index: K,
+    /// The number of the leaf nodes
+    size: u64,
+    /// The internal and leaf nodes of the tree
+    nodes: S,
+    _phantom: PhantomData<V>,
 }
 
 #[derive(Encode, Decode, Default, Clone, PartialEq, TypeInfo)]
@@ -31,42 +132,62 @@ pub enum NodeKind {
     Leaf,
 }
 
-impl<K, V> CritbitTree<K, V>
+impl<K, V, S> CritbitTree<K, V, S>
 where
     K: OrderBookIndex,
-    V: Clone + PartialOrd,
+    V: Clone,
+    S: NodeStore<K, V>,
 {
     /// Create new instance of the tree.
     pub fn new() -> Self {
         Self {
             root: K::PARTITION_INDEX,
-            internal_nodes: BTreeMap::new(),
-            leaves: BTreeMap::new(),
             max_leaf_index: K::PARTITION_INDEX,
             min_leaf_index: K::PARTITION_INDEX,
             next_internal_node_index: Default::default(),
             next_leaf_node_index: Default::default(),
+            size: 0,
+            nodes: S::new(),
+            _phantom: PhantomData,
         }
     }
 
     /// Check whether the leaf exists
     pub fn is_empty(&self) -> bool {
-        self.leaves.is_empty()
+        self.size == 0
     }
 
     /// Get the number of leaf nodes in the tree.
     pub fn size(&self) -> usize {
-        self.leaves.len()
+        self.size as usize
+    }
+
+    /// Copy of the tree which holds all of its nodes in memory. Every node is read from `S`, so
+    /// it must not be used on-chain if the nodes are in the storage.
+    pub fn snapshot(&self) -> CritbitTree<K, V> {
+        CritbitTree {
+            root: self.root,
+            max_leaf_index: self.max_leaf_index,
+            min_leaf_index: self.min_leaf_index,
+            next_internal_node_index: self.next_internal_node_index,
+            next_leaf_node_index: self.next_leaf_node_index,
+            size: self.size,
+            nodes: InMemoryNodes {
+                internal_nodes: self.nodes.internal_nodes().into_iter().collect(),
+                leaves: self.nodes.leaves().into_iter().collect(),
+            },
+            _phantom: PhantomData,
+        }
     }
 
     /// Query the maximum leaf node. Return **(key, index)**.
     ///
     /// Index indicates the index of the leaf node which is encoded as `(K::MAX_INDEX - tree_index)`
     pub fn max_leaf(&self) -> Result<Option<(K, K)>, CritbitTreeError> {
-        if self.leaves.is_empty() {
+        if self.is_empty() {
             return Ok(None);
         }
-        if let Some(leaf) = self.leaves.get(&self.max_leaf_index) {
+        if let Some(leaf) = self.nodes.leaf(&self.max_leaf_index) {
             Ok(Some((leaf.key(), self.max_leaf_index)))
         } else {
             // For safety, should not reach here unless leaves are empty
@@ -78,10 +199,10 @@ where
     ///
     /// Index indicates the index of the leaf node which is encoded as `(K::MAX_INDEX - tree_index)`
     pub fn min_leaf(&self) -> Result<Option<(K, K)>, CritbitTreeError> {
-        if self.leaves.is_empty() {
+        if self.is_empty() {
             return Ok(None);
         }
-        if let Some(leaf) = self.leaves.get(&self.min_leaf_index) {
+        if let Some(leaf) = self.nodes.leaf(&self.min_leaf_index) {
             Ok(Some((leaf.key(), self.min_leaf_index)))
         } else {
             // For safety, should not reach here unless leaves are empty
@@ -89,37 +210,61 @@ where
         }
     }
 
+    /// Get the value of the leaf node of given `index`
+    pub fn leaf_value(&self, leaf_index: &K) -> Option<V> {
+        self.nodes.leaf(leaf_index).map(|leaf| leaf.value)
+    }
+
+    /// Update the value of the leaf node of given `index`
+    pub fn set_leaf_value(&mut self, leaf_index: &K, value: V) -> Result<(), CritbitTreeError> {
+        let mut leaf = self
+            .nodes
+            .leaf(leaf_index)
+            .ok_or(CritbitTreeError::LeafNodeShouldExist)?;
+        leaf.value = value;
+        self.nodes.insert_leaf(*leaf_index, leaf);
+        Ok(())
+    }
+
     /// Insert a new leaf node into the tree for given key `K` and value `V`
     pub fn insert(&mut self, key: K, value: V) -> Result<(), CritbitTreeError> {
+        let closest_leaf_index = self.get_closest_leaf_index(&key)?;
+        let closest_leaf_key = match closest_leaf_index {
+            Some(leaf_index) => {
+                let closest_leaf_key = self
+                    .nodes
+                    .leaf(&leaf_index)
+                    .ok_or(CritbitTreeError::LeafNodeShouldExist)?
+                    .key;
+                if closest_leaf_key == key {
+                    return Err(CritbitTreeError::AlreadyExist);
+                }
+                Some(closest_leaf_key)
+            }
+            None => None,
+        };
         let new_leaf = LeafNode::new(key, value);
         let new_leaf_index = self.next_index(NodeKind::Leaf)?;
-        if let Some(_) = self.leaves.insert(new_leaf_index, new_leaf) {
+        if let Some(_) = self.nodes.insert_leaf(new_leaf_index, new_leaf) {
             return Err(CritbitTreeError::UniqueIndex);
         }
-        let closest_leaf_index = self.get_closest_leaf_index(&key)?;
-        if closest_leaf_index == None {
-            // Handle first insertion
-            self.root = K::MAX_INDEX;
-            self.max_leaf_index = new_leaf_index;
-            self.min_leaf_index = new_leaf_index;
-            return Ok(());
-        }
-        let closest_leaf_key = self
-            .leaves
-            .get(
-                &closest_leaf_index.expect("Case for `None` is already handled"), // TODO: Safe way
-            )
-            .ok_or(CritbitTreeError::LeafNodeShouldExist)?
-            .key;
-        if closest_leaf_key == key {
-            return Err(CritbitTreeError::AlreadyExist);
-        }
+        self.size += 1;
+        let closest_leaf_key = match closest_leaf_key {
+            Some(closest_leaf_key) => closest_leaf_key,
+            None => {
+                // Handle first insertion
+                self.root = K::MAX_INDEX;
+                self.max_leaf_index = new_leaf_index;
+                self.min_leaf_index = new_leaf_index;
+                return Ok(());
+            }
+        };
         let new_mask = K::new_mask(&key, &closest_leaf_key);
         let new_internal_node = InternalNode::new(new_mask);
         let new_internal_index = self.next_index(NodeKind::Internal)?;
         if let Some(_) = self
-            .internal_nodes
-            .insert(new_internal_index, new_internal_node.clone())
+            .nodes
+            .insert_internal_node(new_internal_index, new_internal_node.clone())
         {
             return Err(CritbitTreeError::UniqueIndex);
         }
@@ -127,8 +272,8 @@ where
         let mut internal_node_parent_index = K::PARTITION_INDEX;
         while curr < K::PARTITION_INDEX {
             let internal_node = self
-                .internal_nodes
-                .get(&curr)
+                .nodes
+                .internal_node(&curr)
                 .ok_or(CritbitTreeError::InternalNodeShouldExist)?;
             if new_mask > internal_node.mask {
                 break;
@@ -171,8 +316,8 @@ where
     /// **Index here indicates the index of the leaves**
     pub fn remove_leaf_by_index(&mut self, leaf_index: &K) -> Result<V, CritbitTreeError> {
         let leaf_node = self
-            .leaves
-            .get(leaf_index)
+            .nodes
+            .leaf(leaf_index)
             .ok_or(CritbitTreeError::NotFound)?;
         // Update min/max leaf index
         let mut is_empty: bool = false;
@@ -195,15 +340,16 @@ where
             parent: parent_index,
             ..
         } = self
-            .leaves
-            .remove(leaf_index)
+            .nodes
+            .remove_leaf(leaf_index)
             .expect("We already check above");
+        self.size -= 1;
         if is_empty {
             self.reset()
         } else {
             let parent_node = self
-                .internal_nodes
-                .get(&parent_index)
+                .nodes
+                .internal_node(&parent_index)
                 .ok_or(CritbitTreeError::InternalNodeShouldExist)?;
             // Sibling node could be internal node or leaf
             let sibling_node_index =
@@ -217,21 +363,19 @@ where
                 // Removed parent is root node
                 if sibling_node_index < K::PARTITION_INDEX {
                     let mut sibling_node = self
-                        .internal_nodes
-                        .get_mut(&sibling_node_index)
-                        .ok_or(CritbitTreeError::InternalNodeShouldExist)?
-                        .clone();
+                        .nodes
+                        .internal_node(&sibling_node_index)
+                        .ok_or(CritbitTreeError::InternalNodeShouldExist)?;
                     sibling_node.parent = K::PARTITION_INDEX;
-                    self.internal_nodes.insert(sibling_node_index, sibling_node);
+                    self.nodes.insert_internal_node(sibling_node_index, sibling_node);
                 } else {
                     let mut sibling_node = self
-                        .leaves
-                        .get_mut(&(K::MAX_INDEX - sibling_node_index))
-                        .ok_or(CritbitTreeError::LeafNodeShouldExist)?
-                        .clone();
+                        .nodes
+                        .leaf(&(K::MAX_INDEX - sibling_node_index))
+                        .ok_or(CritbitTreeError::LeafNodeShouldExist)?;
                     sibling_node.parent = K::PARTITION_INDEX;
-                    self.leaves
-                        .insert(K::MAX_INDEX - sibling_node_index, sibling_node);
+                    self.nodes
+                        .insert_leaf(K::MAX_INDEX - sibling_node_index, sibling_node);
                 }
                 self.root = sibling_node_index;
             } else {
@@ -239,7 +383,7 @@ where
                 let is_left_child = self.is_left_child(&grand_parent_index, &parent_index);
                 self.update_ref(grand_parent_index, sibling_node_index, is_left_child)?;
             }
-            self.internal_nodes.remove(&parent_index);
+            self.nodes.remove_internal_node(&parent_index);
         }
 
         Ok(value)
@@ -262,8 +406,8 @@ where
     pub fn previous_leaf(&self, key: &K) -> Result<Option<(K, K)>, CritbitTreeError> {
         let leaf_index = self.find_leaf(key)?.ok_or(CritbitTreeError::NotFound)?;
         let mut parent_node_index = self
-            .leaves
-            .get(&leaf_index)
+            .nodes
+            .leaf(&leaf_index)
             .ok_or(CritbitTreeError::LeafNodeShouldExist)?
             .parent;
         let mut ptr = K::MAX_INDEX - leaf_index;
@@ -272,8 +416,8 @@ where
         {
             ptr = parent_node_index;
             parent_node_index = self
-                .internal_nodes
-                .get(&ptr)
+                .nodes
+                .internal_node(&ptr)
                 .ok_or(CritbitTreeError::InternalNodeShouldExist)?
                 .parent;
         }
@@ -282,17 +426,17 @@ where
             return Ok(None);
         }
         let start_index = self
-            .internal_nodes
-            .get(&parent_node_index)
+            .nodes
+            .internal_node(&parent_node_index)
             .ok_or(CritbitTreeError::InternalNodeShouldExist)?
             .left;
         let leaf_index = K::MAX_INDEX - self.right_most_leaf(start_index)?;
         let leaf_node = self
-            .leaves
-            .get(&leaf_index)
+            .nodes
+            .leaf(&leaf_index)
             .ok_or(CritbitTreeError::LeafNodeShouldExist)?;
 
-        Ok(Some((leaf_node.clone().key, leaf_index)))
+        Ok(Some((leaf_node.key, leaf_index)))
     }
 
     /// Find next leaf for given key `K`. Return **(key, index)** where `index` indicates leaf index
@@ -303,8 +447,8 @@ where
     pub fn next_leaf(&self, key: &K) -> Result<Option<(K, K)>, CritbitTreeError> {
         let leaf_index = self.find_leaf(key)?.ok_or(CritbitTreeError::NotFound)?;
         let mut parent_node_index = self
-            .leaves
-            .get(&leaf_index)
+            .nodes
+            .leaf(&leaf_index)
             .ok_or(CritbitTreeError::LeafNodeShouldExist)?
             .parent;
         let mut ptr = K::MAX_INDEX - leaf_index;
@@ -313,8 +457,8 @@ where
         {
             ptr = parent_node_index;
             parent_node_index = self
-                .internal_nodes
-                .get(&ptr)
+                .nodes
+                .internal_node(&ptr)
                 .ok_or(CritbitTreeError::InternalNodeShouldExist)?
                 .parent;
         }
@@ -323,17 +467,17 @@ where
             return Ok(None);
         }
         let start_index = self
-            .internal_nodes
-            .get(&parent_node_index)
+            .nodes
+            .internal_node(&parent_node_index)
             .ok_or(CritbitTreeError::InternalNodeShouldExist)?
             .right;
         let leaf_index = K::MAX_INDEX - self.left_most_leaf(&start_index)?;
         let leaf_node = self
-            .leaves
-            .get(&leaf_index)
+            .nodes
+            .leaf(&leaf_index)
             .ok_or(CritbitTreeError::LeafNodeShouldExist)?;
 
-        Ok(Some((leaf_node.clone().key, leaf_index)))
+        Ok(Some((leaf_node.key, leaf_index)))
     }
 
     /// Get the left most leaf index of the tree. Index indicates the index inside the tree.
@@ -343,8 +487,8 @@ where
         let mut curr = root.clone();
         while curr < K::PARTITION_INDEX {
             let internal_node = self
-                .internal_nodes
-                .get(&curr)
+                .nodes
+                .internal_node(&curr)
                 .ok_or(CritbitTreeError::InternalNodeShouldExist)?;
             curr = internal_node.left;
         }
@@ -361,8 +505,8 @@ where
         }
         while curr < K::PARTITION_INDEX {
             let internal_node = self
-                .internal_nodes
-                .get(&curr)
+                .nodes
+                .internal_node(&curr)
                 .ok_or(CritbitTreeError::InternalNodeShouldExist)?;
             curr = internal_node.right;
         }
@@ -379,8 +523,8 @@ where
     pub fn find_leaf(&self, key: &K) -> Result<Option<K>, CritbitTreeError> {
         if let Some(leaf_index) = self.get_closest_leaf_index(key)? {
             let leaf_node = self
-                .leaves
-                .get(&leaf_index)
+                .nodes
+                .leaf(&leaf_index)
                 .ok_or(CritbitTreeError::LeafNodeShouldExist)?;
             if &leaf_node.key != key {
                 Ok(None)
@@ -395,14 +539,14 @@ where
 
     /// Update the minimum and maximum leaf nodes.
     fn update_min_max_leaf(&mut self, new_leaf_index: K, new_key: K) {
-        if let Some(min_leaf_value) = self.leaves.get(&self.min_leaf_index) {
+        if let Some(min_leaf_value) = self.nodes.leaf(&self.min_leaf_index) {
             if min_leaf_value.key > new_key {
                 self.min_leaf_index = new_leaf_index;
             }
         } else {
             self.min_leaf_index = new_leaf_index;
         }
-        if let Some(max_leaf_value) = self.leaves.get(&self.max_leaf_index) {
+        if let Some(max_leaf_value) = self.nodes.leaf(&self.max_leaf_index) {
             if max_leaf_value.key < new_key {
                 self.max_leaf_index = new_leaf_index;
             }
@@ -413,7 +557,7 @@ where
 
     /// Check if the index is the left child of the parent. Index indicates index of the tree
     fn is_left_child(&self, parent: &K, child: &K) -> bool {
-        if let Some(internal_node) = self.internal_nodes.get(parent) {
+        if let Some(internal_node) = self.nodes.internal_node(parent) {
             return &internal_node.left == child;
         }
         // Should not reach here
@@ -453,35 +597,32 @@ where
         is_left_child: bool,
     ) -> Result<(), CritbitTreeError> {
         let mut internal_node = self
-            .internal_nodes
-            .get(&parent)
-            .ok_or(CritbitTreeError::InternalNodeShouldExist)?
-            .clone();
+            .nodes
+            .internal_node(&parent)
+            .ok_or(CritbitTreeError::InternalNodeShouldExist)?;
         if is_left_child {
             internal_node.left = child;
         } else {
             internal_node.right = child;
         }
-        self.internal_nodes.insert(parent, internal_node);
+        self.nodes.insert_internal_node(parent, internal_node);
         if child > K::PARTITION_INDEX {
             // child is `leaf`
             let leaf_node_index = K::MAX_INDEX - child;
             let mut leaf_node = self
-                .leaves
-                .get(&leaf_node_index)
-                .ok_or(CritbitTreeError::LeafNodeShouldExist)?
-                .clone();
+                .nodes
+                .leaf(&leaf_node_index)
+                .ok_or(CritbitTreeError::LeafNodeShouldExist)?;
             leaf_node.parent = parent;
-            self.leaves.insert(leaf_node_index, leaf_node);
+            self.nodes.insert_leaf(leaf_node_index, leaf_node);
         } else {
             // child is `internal_node`
             let mut internal_node = self
-                .internal_nodes
-                .get(&child)
-                .ok_or(CritbitTreeError::InternalNodeShouldExist)?
-                .clone();
+                .nodes
+                .internal_node(&child)
+                .ok_or(CritbitTreeError::InternalNodeShouldExist)?;
             internal_node.parent = parent;
-            self.internal_nodes.insert(child, internal_node);
+            self.nodes.insert_internal_node(child, internal_node);
         }
         Ok(())
     }
@@ -495,8 +636,8 @@ where
         }
         while curr < K::PARTITION_INDEX {
             let internal_node = self
-                .internal_nodes
-                .get(&curr)
+                .nodes
+                .internal_node(&curr)
                 .ok_or(CritbitTreeError::InternalNodeShouldExist)?;
             if internal_node.mask & *key == Zero::zero() {
                 curr = internal_node.left;
@@ -536,7 +677,18 @@ pub enum CritbitTreeError {
 /// `InternalNode` for `critbit-tree` with `K` index. Here, `K` refer to two meaning.
 /// -  mask
 /// - path of the tree
-#[derive(Encode, Decode, DecodeWithMemTracking, Debug, Default, Clone, PartialEq, Eq, TypeInfo)]
+#[derive(
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Debug,
+    Default,
+    Clone,
+    PartialEq,
+    Eq,
+    MaxEncodedLen,
+    TypeInfo,
+)]
 pub struct InternalNode<K> {
     /// Mask for branching the tree based on the critbit.
     mask: K,
@@ -592,16 +744,18 @@ impl<K: OrderBookIndex, V> LeafNode<K, V> {
     }
 }
 
-impl<Account, Unit, Order, BlockNumber> OrderBook<Account, Unit, BlockNumber>
-    for CritbitTree<Unit, Order>
+impl<Account, Unit, Order, BlockNumber, S> OrderBook<Account, Unit, BlockNumber>
+    for CritbitTree<Unit, Order, S>
 where
     Account: Clone,
     Unit: OrderBookIndex,
-    Order: OrderInterface<Account, Unit, BlockNumber> + Clone + PartialOrd,
+    Order: OrderInterface<Account, Unit, BlockNumber> + Clone,
+    S: NodeStore<Unit, Order>,
 {
     type Order = Order;
     type OrderId = <Order as OrderInterface<Account, Unit, BlockNumber>>::OrderId;
     type Error = CritbitTreeError;
+    type Snapshot = CritbitTree<Unit, Order>;
 
     fn new() -> Self {
         CritbitTree::new()
@@ -615,24 +769,13 @@ where
         self.is_empty()
     }
 
-    fn get_orders(&self, owner: &Account) -> Vec<OrderUnit<Unit, Account, BlockNumber>> {
-        let mut res = Vec::new();
-        self.leaves
-            .values()
-            .for_each(|l| match l.value.find_order_of(owner) {
-                Some(mut orders) => res.append(&mut orders),
-                None => {}
-            });
-        res
+    fn snapshot(&self) -> Self::Snapshot {
+        self.snapshot()
     }
 
     fn open_orders_at(&self, key: Unit) -> Result<Option<Self::Order>, Self::Error> {
         if let Some(leaf_index) = self.find_leaf(&key)? {
-            if let Some(leaf) = self.leaves.get(&leaf_index) {
-                Ok(Some(leaf.value.orders()))
-            } else {
-                Ok(None)
-            }
+            Ok(self.leaf_value(&leaf_index))
         } else {
             Ok(None)
         }
@@ -669,20 +812,18 @@ where
                 // Should not reach here
                 return Err(CritbitTreeError::LeafNodeShouldExist);
             }
-            let leaf_node = self
-                .leaves
-                .get_mut(&leaf_index)
+            let mut orders = self
+                .leaf_value(&leaf_index)
                 .ok_or(CritbitTreeError::LeafNodeShouldExist)?;
-            leaf_node
-                .value
-                .placed(order_id, owner, quantity, expired_at);
-            Ok(())
+            orders
+                .placed(order_id, owner, quantity, expired_at)
+                .map_err(|_| CritbitTreeError::ValueOps)?;
+            self.set_leaf_value(&leaf_index, orders)
         } else {
             // Insert new leaf node with new order
-            self.insert(
-                key,
-                Order::new(order_id, owner.clone(), quantity, expired_at),
-            )?;
+            let orders = Order::new(order_id, owner.clone(), quantity, expired_at)
+                .map_err(|_| CritbitTreeError::ValueOps)?;
+            self.insert(key, orders)?;
             Ok(())
         }
     }
@@ -691,12 +832,14 @@ where
         &mut self,
         key: Unit,
         quantity: Unit,
-    ) -> Result<Option<Vec<(Account, Unit)>>, Self::Error> {
+    ) -> Result<Option<Vec<(Self::OrderId, Account, Unit, Unit)>>, Self::Error> {
         let maybe_filled = if let Some(leaf_index) = self.find_leaf(&key)? {
-            if let Some(leaf) = self.leaves.get_mut(&leaf_index) {
-                let filled = leaf.value.filled(quantity);
-                if leaf.value.is_empty() {
+            if let Some(mut orders) = self.leaf_value(&leaf_index) {
+                let filled = orders.filled(quantity);
+                if orders.is_empty() {
                     self.remove_leaf_by_index(&leaf_index)?;
+                } else {
+                    self.set_leaf_value(&leaf_index, orders)?;
                 }
                 filled
             } else {
@@ -722,11 +865,17 @@ where
                 // Since tree is empty, we don't do anything
                 return Ok(());
             }
-            if let Some(leaf) = self.leaves.get_mut(&leaf_index) {
-                leaf.value
+            if let Some(mut orders) = self.leaf_value(&leaf_index) {
+                orders
                     .canceled(maybe_owner, order_id, quantity)
                     .map_err(|_| CritbitTreeError::ValueOps)?;
-                Ok(())
+                // Remove the leaf if there are no open orders left for the given price(key)
+                if orders.is_empty() {
+                    self.remove_leaf_by_index(&leaf_index)?;
+                    Ok(())
+                } else {
+                    self.set_leaf_value(&leaf_index, orders)
+                }
             } else {
                 // no leaf?
                 return Err(CritbitTreeError::NotFound);
@@ -735,11 +884,86 @@ where
             Err(CritbitTreeError::NotFound)
         }
     }
+
+    fn get_order(
+        &self,
+        key: Unit,
+        order_id: Self::OrderId,
+    ) -> Option<OrderUnit<Unit, Account, BlockNumber>> {
+        let leaf_index = self.find_leaf(&key).ok()??;
+        self.leaf_value(&leaf_index)?.get_order(&order_id)
+    }
+
+    fn remove_order(
+        &mut self,
+        key: Unit,
+        order_id: Self::OrderId,
+    ) -> Result<OrderUnit<Unit, Account, BlockNumber>, Self::Error> {
+        let leaf_index = self.find_leaf(&key)?.ok_or(CritbitTreeError::NotFound)?;
+        let mut orders = self
+            .leaf_value(&leaf_index)
+            .ok_or(CritbitTreeError::LeafNodeShouldExist)?;
+        let removed = orders.removed(&order_id).ok_or(CritbitTreeError::NotFound)?;
+        // Remove the leaf if there are no open orders left for the given price(key)
+        if orders.is_empty() {
+            self.remove_leaf_by_index(&leaf_index)?;
+        } else {
+            self.set_leaf_value(&leaf_index, orders)?;
+        }
+        Ok(removed)
+    }
+
+    fn expired_orders_at(
+        &self,
+        key: Unit,
+        now: &BlockNumber,
+    ) -> Vec<(Self::OrderId, OrderUnit<Unit, Account, BlockNumber>)> {
+        match self.find_leaf(&key) {
+            Ok(Some(leaf_index)) => self
+                .leaf_value(&leaf_index)
+                .map_or(Vec::new(), |orders| orders.expired_orders(now)),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl<Unit, Account, BlockNumber, MaxOrders, S> OrderBookDepth<Unit>
+    for CritbitTree<Unit, Tick<Unit, Account, BlockNumber, MaxOrders>, S>
+where
+    Unit: OrderBookIndex,
+    MaxOrders: Get<u32>,
+    Tick<Unit, Account, BlockNumber, MaxOrders>: Clone,
+    S: NodeStore<Unit, Tick<Unit, Account, BlockNumber, MaxOrders>>,
+{
+    fn depth(&self, descending: bool, max_levels: u32) -> Vec<(Unit, Unit, u32)> {
+        let mut levels = Vec::new();
+        let mut maybe_leaf = if descending {
+            self.max_leaf()
+        } else {
+            self.min_leaf()
+        };
+        while let Ok(Some((price, leaf_index))) = maybe_leaf {
+            if levels.len() as u32 >= max_levels {
+                break;
+            }
+            if let Some(tick) = self.leaf_value(&leaf_index) {
+                let (quantity, orders) = tick.depth();
+                levels.push((price, quantity, orders));
+            }
+            maybe_leaf = if descending {
+                self.previous_leaf(&price)
+            } else {
+                self.next_leaf(&price)
+            };
+        }
+        levels
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use frame_support::traits::ConstU32;
 
     #[test]
     fn test_critbit_tree() {
@@ -771,7 +995,6 @@ mod tests {
         let next_leaf = tree.next_leaf(&k1).unwrap();
         assert_eq!(previous_leaf, None);
         assert_eq!(next_leaf, None);
-        println!("First Insertion => {:?}", tree);
 
         // Second insertion
         let k2 = 0x100u64;
@@ -794,7 +1017,6 @@ mod tests {
         assert_eq!(tree.previous_leaf(&k1).unwrap(), Some((k2, 1)));
         assert_eq!(tree.next_leaf(&k2).unwrap(), Some((k1, 0)));
 
-        println!("{:?}", tree);
     }
 
     #[test]
@@ -806,18 +1028,49 @@ mod tests {
         assert_eq!(tree.remove_leaf_by_index(&0), Ok(0));
         assert_eq!(tree.size(), 0);
         assert_eq!(tree.is_empty(), true);
-        println!("Empty tree => {:?}", tree);
-        println!("--------------------------");
         let k_v: Vec<(u64, u64)> = vec![(0x2u64, 1), (0x3u64, 2), (0x4u64, 3), (0x5u64, 4)];
         for (k, v) in k_v.clone() {
             tree.insert(k, v).unwrap();
         }
         assert_eq!(tree.size(), k_v.len());
-        println!("After insert 4 items => {:?}", tree);
-        println!("--------------------------");
         tree.remove_leaf_by_index(&0).unwrap();
         assert_eq!(tree.min_leaf().unwrap(), Some((0x3u64, 1)));
         assert_eq!(tree.size(), k_v.len() - 1);
-        println!("Delete left most leaf => {:?}", tree);
+    }
+
+    #[test]
+    fn insert_existing_key_fails() {
+        let mut tree = CritbitTree::<u64, u64>::new();
+        tree.insert(0x1u64, 0).unwrap();
+        tree.insert(0x2u64, 1).unwrap();
+        assert_eq!(tree.insert(0x2u64, 2), Err(CritbitTreeError::AlreadyExist));
+        assert_eq!(tree.size(), 2);
+        assert_eq!(tree.max_leaf().unwrap(), Some((0x2u64, 1)));
+    }
+
+    #[test]
+    fn snapshot_works() {
+        let mut tree = CritbitTree::<u64, u64>::new();
+        for (k, v) in vec![(0x2u64, 1), (0x3u64, 2), (0x4u64, 3)] {
+            tree.insert(k, v).unwrap();
+        }
+        tree.remove_leaf_by_index(&1).unwrap();
+        assert_eq!(tree.snapshot(), tree);
+    }
+
+    #[test]
+    fn depth_works() {
+        type TestTick = Tick<u64, u64, u64, ConstU32<10>>;
+        let mut tree = CritbitTree::<u64, TestTick>::new();
+        assert!(tree.depth(false, 10).is_empty());
+        let mut order_id = OrderId(0);
+        for (price, quantity) in vec![(10u64, 5u64), (30, 1), (20, 2), (10, 3), (30, 4)] {
+            OrderBook::<u64, u64, u64>::place_order(&mut tree, order_id, &1, price, quantity, 100)
+                .unwrap();
+            order_id += 1;
+        }
+        assert_eq!(tree.depth(false, 10), vec![(10, 8, 2), (20, 2, 1), (30, 5, 2)]);
+        assert_eq!(tree.depth(true, 2), vec![(30, 5, 2), (20, 2, 1)]);
+        assert!(tree.depth(true, 0).is_empty());
     }
 }
@@ -56,7 +56,9 @@
 
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
+mod concentrated;
 mod critbit;
+pub mod migration;
 #[cfg(test)]
 mod mock;
 mod swap;
@@ -66,6 +68,7 @@ mod types;
 pub mod weights;
 #[cfg(feature = "runtime-benchmarks")]
 pub use benchmarking::{BenchmarkHelper, NativeOrWithIdFactory};
+pub use concentrated::*;
 pub use critbit::*;
 pub use pallet::*;
 pub use swap::*;
@@ -77,6 +80,7 @@ use frame_support::{
     ensure,
     storage::{with_storage_layer, with_transaction},
     traits::{
+        fungible::MutateHold,
         fungibles::{Balanced, Create, Credit, Inspect, Mutate, MutateFreeze},
         tokens::{
             AssetId, Balance,
@@ -86,22 +90,23 @@ use frame_support::{
         },
         AccountTouch, Incrementable, OnUnbalanced,
     },
-    PalletId,
+    BoundedVec, PalletId,
 };
 pub use pallet_assets::FrozenBalance;
 use scale_info::TypeInfo;
 use sp_core::Get;
 use sp_runtime::{
     traits::{
-        AccountIdConversion, CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, Ensure,
-        IntegerSquareRoot, MaybeDisplay, One, TrailingZeroInput, Zero,
+        AccountIdConversion, Bounded, CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, Ensure,
+        IntegerSquareRoot, MaybeDisplay, One, SaturatedConversion, TrailingZeroInput, Zero,
     },
-    DispatchError, Permill, Saturating, TokenError, TransactionOutcome,
+    DispatchError, FixedPointNumber, FixedU128, Permill, Saturating, TokenError,
+    TransactionOutcome,
 };
 use sp_std::{
     boxed::Box,
     collections::{btree_map::BTreeMap, btree_set::BTreeSet},
-    if_std, vec,
+    vec,
     vec::Vec,
 };
 
@@ -113,9 +118,13 @@ pub mod pallet {
     use super::*;
     use frame_support::pallet_prelude::{DispatchResult, *};
     use frame_system::pallet_prelude::*;
-    use sp_arithmetic::{traits::Unsigned, Permill};
+    use sp_arithmetic::{traits::Unsigned, PerThing, Permill, Rounding};
+
+    /// The in-code storage version.
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
 
     #[pallet::pallet]
+    #[pallet::storage_version(STORAGE_VERSION)]
     pub struct Pallet<T>(_);
 
     #[pallet::composite_enum]
@@ -124,6 +133,13 @@ pub mod pallet {
         LimitOrder,
     }
 
+    #[pallet::composite_enum]
+    pub enum HoldReason {
+        /// Deposit held for each resting limit order
+        #[codec(index = 0)]
+        OrderDeposit,
+    }
+
     #[pallet::config]
     pub trait Config: frame_system::Config {
         /// Overarching event type.
@@ -161,7 +177,9 @@ pub mod pallet {
 
         /// Type of data structure of orderbook
         type OrderBook: OrderBook<Self::AccountId, Self::Unit, BlockNumberFor<Self>, OrderId = OrderId>
-            + Parameter;
+            + OrderBookDepth<Self::Unit>
+            + Parameter
+            + MaxEncodedLen;
 
         /// Liquidity pool identifier.
         type PoolId: Parameter + MaxEncodedLen + Ord;
@@ -184,6 +202,21 @@ pub mod pallet {
 
         type RuntimeFreezeReason: From<FreezeReason>;
 
+        type RuntimeHoldReason: From<HoldReason>;
+
+        /// Native currency on which [`Config::OrderDeposit`] is held.
+        type Currency: MutateHold<
+            Self::AccountId,
+            Reason = Self::RuntimeHoldReason,
+            Balance = Self::Unit,
+        >;
+
+        /// Deposit held on [`Config::Currency`] for each resting limit order and stop limit order,
+        /// which deters spamming the orderbook. Released once the order is filled, cancelled,
+        /// expired or triggered.
+        #[pallet::constant]
+        type OrderDeposit: Get<Self::Unit>;
+
         /// A % the liquidity providers will take of every swap. Represents 10ths of a percent.
         #[pallet::constant]
         type LPFee: Get<u32>;
@@ -199,9 +232,80 @@ pub mod pallet {
         /// Handler for the [`Config::PoolSetupFee`].
         type PoolSetupFeeTarget: OnUnbalanced<CreditOf<Self>>;
 
+        /// Handler for the taker fee charged on the orders filled from the orderbook and the maker
+        /// fee if any. Rebates for the makers are paid out of the taker fee before handled.
+        type TakerFeeTarget: OnUnbalanced<CreditOf<Self>>;
+
+        /// Number of blocks after which a resting limit order is expired.
         #[pallet::constant]
         type OrderExpiration: Get<BlockNumberFor<Self>>;
 
+        /// The max number of expired orders swept on `on_idle` per block.
+        #[pallet::constant]
+        type MaxExpiredOrdersPerBlock: Get<u32>;
+
+        /// The max number of stop limit orders triggered after an order or a swap.
+        #[pallet::constant]
+        type MaxTriggeredStopOrders: Get<u32>;
+
+        /// The max number of stop limit orders of each pool waiting to be triggered.
+        #[pallet::constant]
+        type MaxStopOrders: Get<u32>;
+
+        /// The max number of price levels on each side of the orderbook.
+        #[pallet::constant]
+        type MaxTicks: Get<u32>;
+
+        /// The max number of open orders on each price level.
+        #[pallet::constant]
+        type MaxOrdersPerTick: Get<u32>;
+
+        /// The max number of open limit orders and stop limit orders of an account across all
+        /// pools.
+        #[pallet::constant]
+        type MaxOpenOrders: Get<u32>;
+
+        /// The max number of resting orders filled or expired by a single order, which bounds
+        /// the weight of matching.
+        #[pallet::constant]
+        type MaxOrdersFilled: Get<u32>;
+
+        /// The max number of the recent trades stored for each pool.
+        #[pallet::constant]
+        type MaxRecentTrades: Get<u32>;
+
+        /// The max number of the price observations stored for each pool, which bounds the window
+        /// of the time weighted average price.
+        #[pallet::constant]
+        type MaxPriceObservations: Get<u32>;
+
+        /// Source of the price provided by [`PriceFeed`].
+        #[pallet::constant]
+        type OraclePriceSource: Get<PriceSource<BlockNumberFor<Self>>>;
+
+        /// The max number of price levels of the expired orders skipped for the best bid and ask
+        /// of [`PriceFeed`], which bounds its weight.
+        #[pallet::constant]
+        type MaxExpiredPriceLevels: Get<u32>;
+
+        /// The max number of [`Config::CandleResolutions`], which bounds the candles updated by
+        /// each trade.
+        #[pallet::constant]
+        type MaxCandleResolutions: Get<u32>;
+
+        /// Resolutions of the candles in blocks, e.g. `[10, 600]`.
+        #[pallet::constant]
+        type CandleResolutions: Get<BoundedVec<u32, Self::MaxCandleResolutions>>;
+
+        /// The max number of the candles stored for each resolution of the pool.
+        #[pallet::constant]
+        type MaxCandles: Get<u32>;
+
+        /// The max number of the boundaries of the price ranges of the positions of each pool with
+        /// [`LiquidityMode::Concentrated`], which bounds the boundaries crossed by a swap.
+        #[pallet::constant]
+        type MaxLiquidityBoundaries: Get<u32>;
+
         /// A fee to withdraw the liquidity.
         #[pallet::constant]
         type LiquidityWithdrawalFee: Get<Permill>;
@@ -232,7 +336,6 @@ pub mod pallet {
     /// Map from `PoolAssetId` to `PoolInfo`. This establishes whether a pool has been officially
     /// created rather than people sending tokens directly to a pool's public account.
     #[pallet::storage]
-    #[pallet::unbounded]
     pub type Pools<T: Config> = StorageMap<_, Blake2_128Concat, T::PoolId, Pool<T>, OptionQuery>;
 
     /// Stores the `PoolAssetId` that is going to be used for the next lp token.
@@ -240,6 +343,161 @@ pub mod pallet {
     #[pallet::storage]
     pub type NextPoolAssetId<T: Config> = StorageValue<_, T::PoolAssetId, OptionQuery>;
 
+    /// Id of the next orderbook. Each pool has two orderbooks, one for the bid and one for the ask.
+    #[pallet::storage]
+    pub type NextOrderBookId<T: Config> = StorageValue<_, OrderBookId, ValueQuery>;
+
+    /// Internal nodes of the critbit tree of each orderbook. Keyed by the index of the node.
+    #[pallet::storage]
+    pub type InternalNodes<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        OrderBookId,
+        Twox64Concat,
+        T::Unit,
+        InternalNode<T::Unit>,
+        OptionQuery,
+    >;
+
+    /// Leaf nodes of the critbit tree of each orderbook, which hold all open orders of the price
+    /// level. Keyed by the index of the node.
+    #[pallet::storage]
+    pub type LeafNodes<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        OrderBookId,
+        Twox64Concat,
+        T::Unit,
+        LeafNode<T::Unit, TickOf<T>>,
+        OptionQuery,
+    >;
+
+    /// Index of the resting limit orders by the block number they are expired at. Value is the
+    /// price of the order. Entries of orders which are already filled or cancelled are removed
+    /// lazily by the sweeper.
+    #[pallet::storage]
+    pub type OrderExpiries<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        Blake2_128Concat,
+        (T::PoolId, OrderId),
+        T::Unit,
+        OptionQuery,
+    >;
+
+    /// Index of the open limit orders of each account by pool. Value is the price of the order
+    /// and the [`Config::OrderDeposit`] held for it when placed.
+    #[pallet::storage]
+    pub type OpenOrders<T: Config> = StorageNMap<
+        _,
+        (
+            NMapKey<Blake2_128Concat, T::AccountId>,
+            NMapKey<Blake2_128Concat, T::PoolId>,
+            NMapKey<Twox64Concat, OrderId>,
+        ),
+        (T::Unit, T::Unit),
+        OptionQuery,
+    >;
+
+    /// The number of the open limit orders of each account across all pools. Bounded by
+    /// [`Config::MaxOpenOrders`].
+    #[pallet::storage]
+    pub type OpenOrdersCount<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+    /// Ring buffer of the recent trades of each pool. Key is the sequence number of the trade
+    /// modulo [`Config::MaxRecentTrades`], so the oldest trade is overwritten once it is full.
+    #[pallet::storage]
+    pub type RecentTrades<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::PoolId,
+        Twox64Concat,
+        u32,
+        TradeRecordOf<T>,
+        OptionQuery,
+    >;
+
+    /// The number of the trades executed on each pool.
+    #[pallet::storage]
+    pub type TradeCount<T: Config> = StorageMap<_, Blake2_128Concat, T::PoolId, u64, ValueQuery>;
+
+    /// Ring buffer of the cumulative price observations of each pool. Key is the sequence number
+    /// of the observation modulo [`Config::MaxPriceObservations`].
+    #[pallet::storage]
+    pub type PriceObservations<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::PoolId,
+        Twox64Concat,
+        u32,
+        PriceObservationOf<T>,
+        OptionQuery,
+    >;
+
+    /// The number of the price observations made on each pool.
+    #[pallet::storage]
+    pub type PriceObservationCount<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::PoolId, u64, ValueQuery>;
+
+    /// Ring buffer of the candles of each pool and resolution. Key is the index of the bucket
+    /// modulo [`Config::MaxCandles`], so the oldest candle is overwritten by the new bucket.
+    #[pallet::storage]
+    pub type Candles<T: Config> = StorageNMap<
+        _,
+        (
+            NMapKey<Blake2_128Concat, T::PoolId>,
+            NMapKey<Twox64Concat, u32>,
+            NMapKey<Twox64Concat, u32>,
+        ),
+        CandleOf<T>,
+        OptionQuery,
+    >;
+
+    /// The block number from which the expired orders have not been swept yet.
+    #[pallet::storage]
+    pub type ExpirySweepCursor<T: Config> = StorageValue<_, BlockNumberFor<T>, OptionQuery>;
+
+    /// Stop prices of the stop limit orders of each pool waiting to be triggered. Bounded by
+    /// [`Config::MaxStopOrders`].
+    #[pallet::storage]
+    pub type TriggerBooks<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::PoolId, TriggerBookOf<T>, ValueQuery>;
+
+    /// Stop limit orders waiting to be triggered by pool and id.
+    #[pallet::storage]
+    pub type StopOrders<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::PoolId,
+        Twox64Concat,
+        StopOrderId,
+        StopLimitOrderOf<T>,
+        OptionQuery,
+    >;
+
+    /// Liquidity of each pool with [`LiquidityMode::Concentrated`].
+    #[pallet::storage]
+    pub type ConcentratedLiquidities<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::PoolId, ConcentratedLiquidity<T>, OptionQuery>;
+
+    /// Id of the next liquidity position.
+    #[pallet::storage]
+    pub type NextPositionId<T: Config> = StorageValue<_, PositionId, ValueQuery>;
+
+    /// Liquidity positions of the pools with [`LiquidityMode::Concentrated`] by pool and id.
+    #[pallet::storage]
+    pub type Positions<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::PoolId,
+        Twox64Concat,
+        PositionId,
+        PositionOf<T>,
+        OptionQuery,
+    >;
+
     // Pallet's events.
     #[pallet::event]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
@@ -258,10 +516,14 @@ pub mod pallet {
             lp_token: T::PoolAssetId,
             /// The fee rate of the taker.
             taker_fee_rate: Permill,
+            /// The fee rate of the maker.
+            maker_fee_rate: MakerFeeRate,
             /// The tick size of the orderbook.
             tick_size: T::Unit,
             /// The lot size of the orderbook.
             lot_size: T::Unit,
+            /// How the liquidity of the pool is provided.
+            liquidity_mode: LiquidityMode<T::Unit>,
         },
 
         /// A successful call of the `AddLiquidity` extrinsic will create this event.
@@ -333,10 +595,38 @@ pub mod pallet {
             /// The account initiating the touch.
             who: T::AccountId,
         },
+        /// Orders have been matched from the pool and the orderbook.
         OrderMatched {
+            /// The account that the orders were matched for.
             orderer: T::AccountId,
+            /// The quantity of the orders filled.
             filled: T::Unit,
+            /// Whether the orderer is `bid` or not.
+            is_bid: bool,
+            /// The taker fee charged in quote asset for the orders filled from the orderbook.
+            fee: T::Unit,
+        },
+        /// An order has been filled against the resting order of the orderbook or the pool.
+        Trade {
+            /// The pool id of the pool where the order was filled.
+            pool_id: T::PoolId,
+            /// The owner of the resting order, or the account of the pool if it was filled from
+            /// the pool.
+            maker: T::AccountId,
+            /// The account whose order was matched.
+            taker: T::AccountId,
+            /// The id of the resting order. `None` if it was filled from the pool.
+            order_id: Option<OrderId>,
+            /// Whether the taker is `bid` or not.
             is_bid: bool,
+            /// The execution price scaled by `pool_decimals`.
+            price: T::Unit,
+            /// The quantity of base asset filled.
+            quantity: T::Unit,
+            /// Where the order was filled.
+            venue: Venue,
+            /// The taker fee charged in quote asset.
+            fee: T::Unit,
         },
         /// A limit order has been placed.
         LimitOrder {
@@ -379,6 +669,118 @@ pub mod pallet {
             owner: T::AccountId,
             /// The id of the order.
             order_id: OrderId,
+            /// The amount of asset released from frozen. Quote asset for `bid` order and base
+            /// asset for `ask` order.
+            released: T::Unit,
+        },
+        /// A stop limit order has been placed.
+        StopLimitOrderPlaced {
+            /// The pool id of the pool that the order was placed from.
+            pool_id: T::PoolId,
+            /// The account that the order was placed from.
+            owner: T::AccountId,
+            /// The id of the stop limit order.
+            stop_order_id: StopOrderId,
+            /// The pool price at which the order is triggered.
+            stop_price: T::Unit,
+            /// The price of the limit order placed once triggered.
+            limit_price: T::Unit,
+            /// The quantity of the order.
+            quantity: T::Unit,
+            /// Whether the order is `bid` or not.
+            is_bid: bool,
+        },
+        /// A stop limit order has been triggered and converted to the limit order.
+        StopLimitOrderTriggered {
+            /// The pool id of the pool that the order was triggered from.
+            pool_id: T::PoolId,
+            /// The owner of the order.
+            owner: T::AccountId,
+            /// The id of the stop limit order.
+            stop_order_id: StopOrderId,
+            /// The pool price at which the order is triggered.
+            stop_price: T::Unit,
+            /// Whether the order is `bid` or not.
+            is_bid: bool,
+            /// The result of the limit order.
+            result: DispatchResult,
+        },
+        /// A stop limit order has been cancelled.
+        StopLimitOrderCancelled {
+            /// The pool id of the pool that the order was cancelled from.
+            pool_id: T::PoolId,
+            /// The owner of the order.
+            owner: T::AccountId,
+            /// The id of the stop limit order.
+            stop_order_id: StopOrderId,
+            /// The amount of asset released from frozen.
+            released: T::Unit,
+        },
+        /// An expired order has been removed from the orderbook.
+        OrderExpired {
+            /// The pool id of the pool that the order was removed from.
+            pool_id: T::PoolId,
+            /// The owner of the order.
+            owner: T::AccountId,
+            /// The id of the order.
+            order_id: OrderId,
+            /// The amount of asset released from frozen. Quote asset for `bid` order and base
+            /// asset for `ask` order.
+            released: T::Unit,
+        },
+        /// A resting order has been amended.
+        OrderAmended {
+            /// The pool id of the pool that the order is placed on.
+            pool_id: T::PoolId,
+            /// The owner of the order.
+            owner: T::AccountId,
+            /// The id of the order before amended.
+            order_id: OrderId,
+            /// The id of the order after amended. Same as `order_id` if the order keeps its
+            /// time priority.
+            new_order_id: OrderId,
+            /// The price of the amended order.
+            price: T::Unit,
+            /// The quantity of the amended order.
+            quantity: T::Unit,
+            /// Whether the order is bid or ask.
+            is_bid: bool,
+        },
+        /// A liquidity position has been opened on the pool with the concentrated liquidity.
+        PositionOpened {
+            /// The owner of the position.
+            who: T::AccountId,
+            /// The pool id of the pool that the liquidity was provided to.
+            pool_id: T::PoolId,
+            /// The id of the position.
+            position_id: PositionId,
+            /// The lower bound of the price range.
+            lower: T::Unit,
+            /// The upper bound of the price range.
+            upper: T::Unit,
+            /// The liquidity of the position.
+            liquidity: T::Unit,
+            /// The amount of the base asset that was provided.
+            base_asset_provided: T::Unit,
+            /// The amount of the quote asset that was provided.
+            quote_asset_provided: T::Unit,
+        },
+        /// A liquidity position has been closed with the fee earned by it.
+        PositionClosed {
+            /// The owner of the position.
+            who: T::AccountId,
+            /// The pool id of the pool that the liquidity was removed from.
+            pool_id: T::PoolId,
+            /// The id of the position.
+            position_id: PositionId,
+            /// The amount of the base asset withdrawn, including the fee.
+            base_asset_amount: T::Unit,
+            /// The amount of the quote asset withdrawn, including the fee.
+            quote_asset_amount: T::Unit,
+            /// The fee of the base asset earned by the position.
+            base_fee: T::Unit,
+            /// The fee of the quote asset earned by the position.
+            quote_fee: T::Unit,
         },
     }
 
@@ -457,15 +859,101 @@ pub mod pallet {
         InvalidTickSize,
         /// Invalid lot size
         InvalidLotSize,
+        /// Rebate rate of the maker is greater than the taker fee rate
+        InvalidMakerFeeRate,
+        /// Rebates paid to the makers exceed the taker fee collected
+        RebateExceedsTakerFee,
+        /// Stop price should be above(bid) or below(ask) the current pool price
+        InvalidStopPrice,
+        /// Stop limit order not found
+        StopOrderNotFound,
+        /// The number of stop limit orders of the pool exceeds [`Config::MaxStopOrders`]
+        TooManyStopOrders,
+        /// `FillOrKill` order could not be filled for the whole quantity
+        OrderNotFilled,
+        /// `PostOnly` order would cross the pool price
+        OrderWouldCross,
+        /// Expiry of the order should be in the future and within `OrderExpiration`
+        InvalidExpiry,
+        /// Deadline of the order has passed
+        DeadlinePassed,
+        /// The number of price levels of the orderbook exceeds [`Config::MaxTicks`]
+        TooManyTicks,
+        /// The number of orders of the price level exceeds [`Config::MaxOrdersPerTick`]
+        TooManyOrdersPerTick,
+        /// The number of open orders of the account exceeds [`Config::MaxOpenOrders`]
+        TooManyOpenOrders,
+        /// The number of resting orders filled by the order exceeds [`Config::MaxOrdersFilled`]
+        TooManyOrdersFilled,
+        /// Resolution of the candles is not one of [`Config::CandleResolutions`]
+        InvalidCandleResolution,
+        /// Price observations of the pool don't cover the requested window
+        InsufficientPriceHistory,
+        /// Price of the configured source is not available
+        PriceNotAvailable,
+        /// Window of the time weighted average price should be greater than zero
+        ZeroTwapWindow,
+        /// Operation is not supported by the liquidity mode of the pool
+        WrongLiquidityMode,
+        /// Initial price of the concentrated liquidity should be a multiple of the tick size
+        InvalidInitialPrice,
+        /// Lower bound of the price range should be less than the upper bound and both should be
+        /// multiples of the tick size
+        InvalidPositionRange,
+        /// The number of boundaries of the price ranges exceeds [`Config::MaxLiquidityBoundaries`]
+        TooManyLiquidityBoundaries,
+        /// Liquidity position not found
+        PositionNotFound,
     }
 
     #[pallet::hooks]
     impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_idle(now: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+            Self::do_sweep_expired_orders(now, remaining_weight)
+        }
+
         fn integrity_test() {
             assert!(
                 T::MaxSwapPathLength::get() > 1,
                 "the `MaxSwapPathLength` should be greater than 1",
             );
+            assert!(T::MaxTicks::get() > 0, "the `MaxTicks` should be greater than 0");
+            assert!(
+                T::MaxOrdersPerTick::get() > 0,
+                "the `MaxOrdersPerTick` should be greater than 0",
+            );
+            assert!(
+                T::MaxOrdersFilled::get() > 0,
+                "the `MaxOrdersFilled` should be greater than 0",
+            );
+            assert!(
+                T::MaxRecentTrades::get() > 0,
+                "the `MaxRecentTrades` should be greater than 0",
+            );
+            assert!(
+                T::MaxPriceObservations::get() > 0,
+                "the `MaxPriceObservations` should be greater than 0",
+            );
+            assert!(
+                !matches!(
+                    T::OraclePriceSource::get(),
+                    PriceSource::Twap(window) if window.is_zero()
+                ),
+                "the window of the `OraclePriceSource` should be greater than 0",
+            );
+            assert!(T::MaxCandles::get() > 0, "the `MaxCandles` should be greater than 0");
+            assert!(
+                T::CandleResolutions::get().iter().all(|resolution| *resolution > 0),
+                "the `CandleResolutions` should be greater than 0",
+            );
+            assert!(
+                T::MaxCandleResolutions::get() > 0,
+                "the `MaxCandleResolutions` should be greater than 0",
+            );
+            assert!(
+                T::MaxLiquidityBoundaries::get() > 0,
+                "the `MaxLiquidityBoundaries` should be greater than 0",
+            );
         }
     }
 
@@ -475,7 +963,9 @@ pub mod pallet {
         /// Creates an empty liquidity pool and an associated new `lp_token` asset
         /// (the id of which is returned in the `Event::PoolCreated` event).
         ///
-        /// Once a pool is created, someone may [`Pallet::add_liquidity`] to it.
+        /// Once a pool is created, someone may [`Pallet::add_liquidity`] to it. If the pool is
+        /// created with [`LiquidityMode::Concentrated`], the liquidity is provided within the price
+        /// ranges by [`Pallet::open_position`] instead, and the pool starts from `initial_price`.
         #[pallet::call_index(0)]
         #[pallet::weight(T::WeightInfo::create_pool())]
         pub fn create_pool(
@@ -485,12 +975,18 @@ pub mod pallet {
             quote_asset: Box<T::AssetKind>,
             quote_decimals: u8,
             taker_fee_rate: Permill,
+            maker_fee_rate: MakerFeeRate,
             tick_size: T::Unit,
             lot_size: T::Unit,
             pool_decimals: u8,
+            liquidity_mode: LiquidityMode<T::Unit>,
         ) -> DispatchResult {
             let sender = ensure_signed(origin)?;
             ensure!(base_asset != quote_asset, Error::<T>::InvalidAssetPair);
+            ensure!(
+                maker_fee_rate.is_valid(taker_fee_rate),
+                Error::<T>::InvalidMakerFeeRate
+            );
             ensure!(tick_size > Zero::zero(), Error::<T>::InvalidTickSize);
             ensure!(lot_size > Zero::zero(), Error::<T>::InvalidLotSize);
             // prepare pool_id
@@ -531,26 +1027,42 @@ pub mod pallet {
                 T::PoolAssets::touch(lp_token.clone(), &pool_account, &sender)?
             };
 
-            Pools::<T>::insert(
-                pool_id.clone(),
-                Pool::<T>::new(
-                    lp_token.clone(),
-                    taker_fee_rate,
-                    tick_size,
-                    lot_size,
-                    pool_decimals,
-                    base_decimals,
-                    quote_decimals,
-                ),
+            let pool = Pool::<T>::new(
+                lp_token.clone(),
+                *base_asset.clone(),
+                *quote_asset.clone(),
+                taker_fee_rate,
+                maker_fee_rate,
+                tick_size,
+                lot_size,
+                pool_decimals,
+                base_decimals,
+                quote_decimals,
+                liquidity_mode,
             );
+            if let LiquidityMode::Concentrated { initial_price } = liquidity_mode {
+                ensure!(
+                    pool.is_valid_order_price(initial_price),
+                    Error::<T>::InvalidInitialPrice
+                );
+                let sqrt_price = pool.sqrt_price(initial_price)?;
+                ensure!(!sqrt_price.is_zero(), Error::<T>::InvalidInitialPrice);
+                ConcentratedLiquidities::<T>::insert(
+                    &pool_id,
+                    ConcentratedLiquidity::<T>::new(sqrt_price),
+                );
+            }
+            Pools::<T>::insert(pool_id.clone(), pool);
             Self::deposit_event(Event::PoolCreated {
                 creator: sender,
                 pool_id,
                 pool_account,
                 lp_token,
                 taker_fee_rate,
+                maker_fee_rate,
                 tick_size,
                 lot_size,
+                liquidity_mode,
             });
 
             Ok(())
@@ -593,8 +1105,10 @@ pub mod pallet {
             );
 
             let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+            ensure!(!pool.is_concentrated(), Error::<T>::WrongLiquidityMode);
             let pool_account =
                 T::PoolLocator::address(&pool_id).map_err(|_| Error::<T>::InvalidAssetPair)?;
+            Self::observe_price(&base_asset, &quote_asset)?;
 
             let base_asset_reserve = Self::get_balance(&pool_account, &base_asset);
             let quote_asset_reserve = Self::get_balance(&pool_account, &quote_asset);
@@ -723,9 +1237,11 @@ pub mod pallet {
             ensure!(lp_token_burn > Zero::zero(), Error::<T>::ZeroLiquidity);
 
             let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+            ensure!(!pool.is_concentrated(), Error::<T>::WrongLiquidityMode);
 
             let pool_account =
                 T::PoolLocator::address(&pool_id).map_err(|_| Error::<T>::InvalidAssetPair)?;
+            Self::observe_price(&base_asset, &quote_asset)?;
             let base_asset_reserve = Self::get_balance(&pool_account, &base_asset);
             let quote_asset_reserve = Self::get_balance(&pool_account, &quote_asset);
 
@@ -796,6 +1312,92 @@ pub mod pallet {
             Ok(())
         }
 
+        /// Swap the exact amount of `asset1` into `asset2`.
+        /// `amount_out_min` param allows you to specify the min amount of the `asset2`
+        /// you're happy to receive.
+        ///
+        /// Swaps are executed only on the liquidity pools along the `path`. Pool of each hop could
+        /// be either `(base, quote)` or `(quote, base)`.
+        ///
+        /// [`Pallet::quote_price_exact_tokens_for_tokens`] can be used for a quote.
+        #[pallet::call_index(3)]
+        #[pallet::weight(
+            T::WeightInfo::swap_exact_tokens_for_tokens(path.len() as u32)
+                .saturating_add(
+                    Pallet::<T>::max_trigger_stop_orders_weight()
+                        .saturating_mul(path.len().saturating_sub(1) as u64)
+                )
+                .saturating_add(
+                    Pallet::<T>::max_concentrated_swap_weight(path.len().saturating_sub(1))
+                )
+        )]
+        pub fn swap_exact_tokens_for_tokens(
+            origin: OriginFor<T>,
+            path: Vec<Box<T::AssetKind>>,
+            amount_in: T::Unit,
+            amount_out_min: T::Unit,
+            send_to: T::AccountId,
+            keep_alive: bool,
+        ) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+            let path: Vec<T::AssetKind> = path.into_iter().map(|a| *a).collect();
+            Self::do_swap_exact_tokens_for_tokens(
+                &sender,
+                path.clone(),
+                amount_in,
+                Some(amount_out_min),
+                &send_to,
+                keep_alive,
+            )?;
+            let triggered = Self::do_trigger_stop_orders_along(&path)?;
+            let weight = T::WeightInfo::swap_exact_tokens_for_tokens(path.len() as u32)
+                .saturating_add(Self::concentrated_swap_weight(&path));
+            Ok(Some(weight.saturating_add(triggered)).into())
+        }
+
+        /// Swap any amount of `asset1` to get the exact amount of `asset2`.
+        /// `amount_in_max` param allows to specify the max amount of the `asset1`
+        /// you're happy to provide.
+        ///
+        /// Swaps are executed only on the liquidity pools along the `path`. Pool of each hop could
+        /// be either `(base, quote)` or `(quote, base)`.
+        ///
+        /// [`Pallet::quote_price_tokens_for_exact_tokens`] can be used for a quote.
+        #[pallet::call_index(4)]
+        #[pallet::weight(
+            T::WeightInfo::swap_tokens_for_exact_tokens(path.len() as u32)
+                .saturating_add(
+                    Pallet::<T>::max_trigger_stop_orders_weight()
+                        .saturating_mul(path.len().saturating_sub(1) as u64)
+                )
+                .saturating_add(
+                    Pallet::<T>::max_concentrated_swap_weight(path.len().saturating_sub(1))
+                )
+        )]
+        pub fn swap_tokens_for_exact_tokens(
+            origin: OriginFor<T>,
+            path: Vec<Box<T::AssetKind>>,
+            amount_out: T::Unit,
+            amount_in_max: T::Unit,
+            send_to: T::AccountId,
+            keep_alive: bool,
+        ) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+            let path: Vec<T::AssetKind> = path.into_iter().map(|a| *a).collect();
+            Self::do_swap_tokens_for_exact_tokens(
+                &sender,
+                path.clone(),
+                amount_out,
+                Some(amount_in_max),
+                &send_to,
+                keep_alive,
+            )?;
+            let triggered = Self::do_trigger_stop_orders_along(&path)?;
+            let weight = T::WeightInfo::swap_tokens_for_exact_tokens(path.len() as u32)
+                .saturating_add(Self::concentrated_swap_weight(&path));
+            Ok(Some(weight.saturating_add(triggered)).into())
+        }
+
         /// Touch an existing pool to fulfill prerequisites before providing liquidity, such as
         /// ensuring that the pool's accounts are in place. It is typically useful when a pool
         /// creator removes the pool's accounts and does not provide a liquidity. This action may
@@ -839,24 +1441,55 @@ pub mod pallet {
             Ok(Some(T::WeightInfo::touch(refunds_number)).into())
         }
 
-        // TODO: Benchmark
+        /// Fill `quantity` of base asset from both the pool and the orderbook.
+        ///
+        /// - `quote_limit`: maximum amount of quote asset to pay(bid) or minimum amount of quote
+        ///   asset to receive(ask) including the taker fee. Revert if it is exceeded.
+        /// - `deadline`: block number after which the order is no longer valid
+        ///
+        /// Weight is refunded down to the price levels crossed, the orders filled and the stop
+        /// limit orders triggered.
         #[pallet::call_index(6)]
-        #[pallet::weight(T::WeightInfo::touch(3))]
+        #[pallet::weight({
+            let max = Pallet::<T>::max_match_count();
+            Pallet::<T>::matched_weight(T::WeightInfo::market_order, &max)
+                .saturating_add(Pallet::<T>::max_trigger_stop_orders_weight())
+        })]
         pub fn market_order(
             origin: OriginFor<T>,
             base_asset: Box<T::AssetKind>,
             quote_asset: Box<T::AssetKind>,
             quantity: T::Unit,
             is_bid: bool,
-        ) -> DispatchResult {
+            quote_limit: Option<T::Unit>,
+            deadline: Option<BlockNumberFor<T>>,
+        ) -> DispatchResultWithPostInfo {
             let taker = ensure_signed(origin)?;
-            Self::do_market_order(is_bid, &taker, &base_asset, &quote_asset, quantity)?;
-            Ok(())
+            Self::ensure_deadline(deadline)?;
+            let matched = Self::do_market_order(
+                is_bid,
+                &taker,
+                &base_asset,
+                &quote_asset,
+                quantity,
+                quote_limit,
+            )?;
+            let triggered = Self::do_trigger_stop_orders(&base_asset, &quote_asset)?;
+            let weight = Self::matched_weight(T::WeightInfo::market_order, &matched);
+            Ok(Some(weight.saturating_add(triggered)).into())
         }
 
-        // TODO: Benchmark
+        /// Place `quantity` of limit order at `price`. Orders crossing the pool price are matched
+        /// first and the rest is placed on the orderbook according to `time_in_force`.
+        ///
+        /// Weight is refunded down to the price levels crossed, the orders filled and the stop
+        /// limit orders triggered.
         #[pallet::call_index(7)]
-        #[pallet::weight(T::WeightInfo::touch(3))]
+        #[pallet::weight({
+            let max = Pallet::<T>::max_match_count();
+            Pallet::<T>::matched_weight(T::WeightInfo::limit_order, &max)
+                .saturating_add(Pallet::<T>::max_trigger_stop_orders_weight())
+        })]
         pub fn limit_order(
             origin: OriginFor<T>,
             base_asset: Box<T::AssetKind>,
@@ -864,15 +1497,27 @@ pub mod pallet {
             is_bid: bool,
             price: T::Unit,
             quantity: T::Unit,
-        ) -> DispatchResult {
+            time_in_force: TimeInForce<BlockNumberFor<T>>,
+        ) -> DispatchResultWithPostInfo {
             let maker = ensure_signed(origin)?;
-            Self::do_limit_order(maker, price, quantity, is_bid, &*base_asset, &*quote_asset)?;
-            Ok(())
+            let matched = Self::do_limit_order(
+                maker,
+                price,
+                quantity,
+                is_bid,
+                &*base_asset,
+                &*quote_asset,
+                time_in_force,
+            )?;
+            let triggered = Self::do_trigger_stop_orders(&base_asset, &quote_asset)?;
+            let weight = Self::matched_weight(T::WeightInfo::limit_order, &matched);
+            Ok(Some(weight.saturating_add(triggered)).into())
         }
 
-        // TODO: Benchmark
+        /// Cancel `quantity` of the limit order of `order_id` placed at `price` and release its
+        /// frozen asset.
         #[pallet::call_index(8)]
-        #[pallet::weight(T::WeightInfo::touch(3))]
+        #[pallet::weight(T::WeightInfo::cancel_order())]
         pub fn cancel_order(
             origin: OriginFor<T>,
             base_asset: Box<T::AssetKind>,
@@ -893,103 +1538,1151 @@ pub mod pallet {
             Ok(())
         }
 
-        // impl me!
-        // #[pallet::call_index(9)]
-        // #[pallet::weight(T::WeightInfo::touch(3))]
-        // pub fn stop_limit_order(origin: OriginFor<T>) -> DispatchResult {
-        // 	let owner = ensure_signed(origin)?;
-        // 	let pool = Self::get_pool(asset1, asset2)?;
-        // 	Self::do_stop_limit_order(owner, pool)?;
-        // 	Ok(())
-        // }
-    }
-
-    impl<T: Config> Pallet<T> {
-        fn _account_id() -> T::AccountId {
-            T::PalletId::get().into_account_truncating()
+        /// Place `quantity` of stop limit order which is converted to the limit order of
+        /// `limit_price` once the pool price reaches `stop_price`. `bid` order is triggered when
+        /// the pool price goes up to `stop_price` and `ask` order when it goes down.
+        ///
+        /// Collateral of the limit order is frozen and [`Config::OrderDeposit`] is held on
+        /// placement. Counts towards [`Config::MaxOpenOrders`] until triggered or cancelled.
+        #[pallet::call_index(9)]
+        #[pallet::weight(T::WeightInfo::stop_limit_order(T::MaxStopOrders::get()))]
+        pub fn stop_limit_order(
+            origin: OriginFor<T>,
+            base_asset: Box<T::AssetKind>,
+            quote_asset: Box<T::AssetKind>,
+            is_bid: bool,
+            stop_price: T::Unit,
+            limit_price: T::Unit,
+            quantity: T::Unit,
+        ) -> DispatchResultWithPostInfo {
+            let owner = ensure_signed(origin)?;
+            let stop_orders = Self::do_stop_limit_order(
+                &owner,
+                &base_asset,
+                &quote_asset,
+                is_bid,
+                stop_price,
+                limit_price,
+                quantity,
+            )?;
+            Ok(Some(T::WeightInfo::stop_limit_order(stop_orders)).into())
         }
 
-        // TODO: Benchmark
-        pub fn get_pool_query(
-            base_asset: &T::AssetKind,
-            quote_asset: &T::AssetKind,
-        ) -> Result<PoolQuery<T::OrderBook, T::Unit>, DispatchError> {
-            let pool_id = T::PoolLocator::pool_id(base_asset, quote_asset).map_err(|_| Error::<T>::PoolNotFound)?;
-            let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
-            let (b_r, q_r) = Self::get_reserves(base_asset, quote_asset)?;
-            let pool_price = Self::pool_price(&pool, &b_r, &q_r)?;
-            return Ok(pool.to_pool_query(b_r, q_r, pool_price));
+        /// Cancel the stop limit order of `stop_order_id` which has not been triggered yet.
+        #[pallet::call_index(10)]
+        #[pallet::weight(T::WeightInfo::cancel_stop_limit_order(T::MaxStopOrders::get()))]
+        pub fn cancel_stop_limit_order(
+            origin: OriginFor<T>,
+            base_asset: Box<T::AssetKind>,
+            quote_asset: Box<T::AssetKind>,
+            stop_order_id: StopOrderId,
+        ) -> DispatchResultWithPostInfo {
+            let owner = ensure_signed(origin)?;
+            let stop_orders = Self::do_cancel_stop_limit_order(
+                &owner,
+                &base_asset,
+                &quote_asset,
+                stop_order_id,
+            )?;
+            Ok(Some(T::WeightInfo::cancel_stop_limit_order(stop_orders)).into())
         }
 
-        pub fn get_pool_metadata(
-            base_asset: &T::AssetKind,
-            quote_asset: &T::AssetKind,
-        ) -> Result<PoolMetadata<T::Unit>, DispatchError> {
-            let pool_id = T::PoolLocator::pool_id(base_asset, quote_asset).map_err(|_| Error::<T>::PoolNotFound)?;
-            let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
-            Ok(pool.to_pool_metadata())
+        /// Cancel the whole remaining quantity of the limit order of `order_id`. Price and
+        /// quantity of the order are looked up from the open orders of the origin.
+        #[pallet::call_index(11)]
+        #[pallet::weight(T::WeightInfo::cancel_order_by_id())]
+        pub fn cancel_order_by_id(
+            origin: OriginFor<T>,
+            base_asset: Box<T::AssetKind>,
+            quote_asset: Box<T::AssetKind>,
+            order_id: OrderId,
+        ) -> DispatchResult {
+            let owner = ensure_signed(origin)?;
+            Self::do_cancel_order_by_id(&owner, &base_asset, &quote_asset, order_id)?;
+            Ok(())
         }
 
-        fn freeze_asset(
-            who: &T::AccountId,
-            asset: &T::AssetKind,
-            amount: T::Unit,
-        ) -> DispatchResult {
-            T::AssetsFreezer::increase_frozen(
-                asset.clone(),
-                &FreezeReason::LimitOrder.into(),
-                who,
-                amount,
-            )
+        /// Cancel all open limit orders of the origin on the pool.
+        ///
+        /// Weight is refunded down to the orders cancelled.
+        #[pallet::call_index(12)]
+        #[pallet::weight(T::WeightInfo::cancel_all_orders(T::MaxOpenOrders::get()))]
+        pub fn cancel_all_orders(
+            origin: OriginFor<T>,
+            base_asset: Box<T::AssetKind>,
+            quote_asset: Box<T::AssetKind>,
+        ) -> DispatchResultWithPostInfo {
+            let owner = ensure_signed(origin)?;
+            let cancelled = Self::do_cancel_all_orders(&owner, &base_asset, &quote_asset)?;
+            Ok(Some(T::WeightInfo::cancel_all_orders(cancelled)).into())
         }
 
-        fn handle_filled_orders(
-            is_bid: bool,
-            pool: &Pool<T>,
-            base_asset: T::AssetKind,
-            quote_asset: T::AssetKind,
-            orderer: &T::AccountId,
-            filled_orders: Vec<(T::AccountId, T::Unit, T::Unit)>,
+        /// Amend the resting limit order of `order_id` to `new_quantity` at `new_price`.
+        ///
+        /// - Decreasing the quantity at the same price keeps the time priority of the order.
+        /// - Increasing the quantity or moving the price re-places the order with a new id.
+        ///
+        /// Only the difference of the frozen collateral is frozen or released. Amended order
+        /// should not cross the pool price.
+        #[pallet::call_index(13)]
+        #[pallet::weight(T::WeightInfo::amend_order())]
+        pub fn amend_order(
+            origin: OriginFor<T>,
+            base_asset: Box<T::AssetKind>,
+            quote_asset: Box<T::AssetKind>,
+            order_id: OrderId,
+            new_price: T::Unit,
+            new_quantity: T::Unit,
         ) -> DispatchResult {
-            // If it is matched with bid order, `base_asset` should be released.
-            // While it is ask order, `quote_asset` should be released.
-            // Here, `asset1` means asset that is released from frozen
-            let (asset1, asset2) = if is_bid {
-                (base_asset, quote_asset)
+            let owner = ensure_signed(origin)?;
+            Self::do_amend_order(
+                &owner,
+                &base_asset,
+                &quote_asset,
+                order_id,
+                new_price,
+                new_quantity,
+            )?;
+            Ok(())
+        }
+
+        /// Buy base asset by spending up to `quote_amount` of quote asset from both the pool and
+        /// the orderbook. Quote asset which could not be spent stays with the origin.
+        ///
+        /// - `min_quantity`: minimum amount of base asset to receive. Revert if not reached.
+        /// - `deadline`: block number after which the order is no longer valid
+        ///
+        /// Weighted as [`Pallet::market_order`] and refunded down to the price levels crossed,
+        /// the orders filled and the stop limit orders triggered.
+        #[pallet::call_index(14)]
+        #[pallet::weight({
+            let max = Pallet::<T>::max_match_count();
+            Pallet::<T>::matched_weight(T::WeightInfo::market_order, &max)
+                .saturating_add(Pallet::<T>::max_trigger_stop_orders_weight())
+        })]
+        pub fn market_order_with_quote(
+            origin: OriginFor<T>,
+            base_asset: Box<T::AssetKind>,
+            quote_asset: Box<T::AssetKind>,
+            quote_amount: T::Unit,
+            min_quantity: Option<T::Unit>,
+            deadline: Option<BlockNumberFor<T>>,
+        ) -> DispatchResultWithPostInfo {
+            let taker = ensure_signed(origin)?;
+            Self::ensure_deadline(deadline)?;
+            let matched = Self::do_market_order_with_quote(
+                &taker,
+                &base_asset,
+                &quote_asset,
+                quote_amount,
+                min_quantity,
+            )?;
+            let triggered = Self::do_trigger_stop_orders(&base_asset, &quote_asset)?;
+            let weight = Self::matched_weight(T::WeightInfo::market_order, &matched);
+            Ok(Some(weight.saturating_add(triggered)).into())
+        }
+
+        /// Provide liquidity to the pool with [`LiquidityMode::Concentrated`] within the price
+        /// range of `lower` and `upper`, which are multiples of the tick size. The liquidity is
+        /// only used for the swaps while the pool price is within the range.
+        ///
+        /// As much liquidity as `base_asset_desired` and `quote_asset_desired` allow at the
+        /// current price is provided. Only base asset is provided if the range is above the price
+        /// and only quote asset if it is below. `base_asset_min`/`quote_asset_min` represent the
+        /// min amounts you're happy to provide.
+        ///
+        /// The position is owned by the origin until [`Pallet::close_position`].
+        #[pallet::call_index(15)]
+        #[pallet::weight(T::WeightInfo::open_position(T::MaxLiquidityBoundaries::get()))]
+        pub fn open_position(
+            origin: OriginFor<T>,
+            base_asset: Box<T::AssetKind>,
+            quote_asset: Box<T::AssetKind>,
+            lower: T::Unit,
+            upper: T::Unit,
+            base_asset_desired: T::Unit,
+            quote_asset_desired: T::Unit,
+            base_asset_min: T::Unit,
+            quote_asset_min: T::Unit,
+        ) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+
+            let pool_id = T::PoolLocator::pool_id(&base_asset, &quote_asset)
+                .map_err(|_| Error::<T>::InvalidAssetPair)?;
+
+            ensure!(
+                base_asset_desired > Zero::zero() || quote_asset_desired > Zero::zero(),
+                Error::<T>::WrongDesiredAmount
+            );
+
+            let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+            ensure!(pool.is_concentrated(), Error::<T>::WrongLiquidityMode);
+            ensure!(
+                lower < upper
+                    && pool.is_valid_order_price(lower)
+                    && pool.is_valid_order_price(upper),
+                Error::<T>::InvalidPositionRange
+            );
+            let (lower_sqrt_price, upper_sqrt_price) =
+                (pool.sqrt_price(lower)?, pool.sqrt_price(upper)?);
+            ensure!(
+                !lower_sqrt_price.is_zero() && lower_sqrt_price < upper_sqrt_price,
+                Error::<T>::InvalidPositionRange
+            );
+            let pool_account =
+                T::PoolLocator::address(&pool_id).map_err(|_| Error::<T>::InvalidAssetPair)?;
+            Self::observe_price(&base_asset, &quote_asset)?;
+
+            let position_id = NextPositionId::<T>::get();
+            NextPositionId::<T>::put(position_id.checked_add(1).ok_or(Error::<T>::Overflow)?);
+            let (liquidity, base_asset_amount, quote_asset_amount, boundaries) =
+                ConcentratedLiquidities::<T>::try_mutate(&pool_id, |state| {
+                    let state = state.as_mut().ok_or(Error::<T>::PoolNotFound)?;
+                    let liquidity = state.liquidity_for_amounts(
+                        lower_sqrt_price,
+                        upper_sqrt_price,
+                        base_asset_desired,
+                        quote_asset_desired,
+                    )?;
+                    ensure!(!liquidity.is_zero(), Error::<T>::InsufficientLiquidityMinted);
+                    let (base_asset_amount, quote_asset_amount) = state.amounts_for_liquidity(
+                        lower_sqrt_price,
+                        upper_sqrt_price,
+                        liquidity,
+                        Rounding::Up,
+                    )?;
+                    ensure!(
+                        base_asset_amount >= base_asset_min,
+                        Error::<T>::AssetOneDepositDidNotMeetMinimum
+                    );
+                    ensure!(
+                        quote_asset_amount >= quote_asset_min,
+                        Error::<T>::AssetTwoDepositDidNotMeetMinimum
+                    );
+                    state.add_position(
+                        (lower, lower_sqrt_price),
+                        (upper, upper_sqrt_price),
+                        liquidity,
+                    )?;
+                    let (fee_growth_inside_base, fee_growth_inside_quote) =
+                        state.fee_growth_inside(lower, upper)?;
+                    Positions::<T>::insert(
+                        &pool_id,
+                        position_id,
+                        Position {
+                            owner: sender.clone(),
+                            lower,
+                            upper,
+                            liquidity,
+                            fee_growth_inside_base,
+                            fee_growth_inside_quote,
+                        },
+                    );
+                    Ok::<_, Error<T>>((
+                        liquidity,
+                        base_asset_amount,
+                        quote_asset_amount,
+                        state.boundaries_len(),
+                    ))
+                })?;
+
+            if !base_asset_amount.is_zero() {
+                T::Assets::transfer(
+                    *base_asset,
+                    &sender,
+                    &pool_account,
+                    base_asset_amount,
+                    Preserve,
+                )?;
+            }
+            if !quote_asset_amount.is_zero() {
+                T::Assets::transfer(
+                    *quote_asset,
+                    &sender,
+                    &pool_account,
+                    quote_asset_amount,
+                    Preserve,
+                )?;
+            }
+
+            Self::deposit_event(Event::PositionOpened {
+                who: sender,
+                pool_id,
+                position_id,
+                lower,
+                upper,
+                liquidity,
+                base_asset_provided: base_asset_amount,
+                quote_asset_provided: quote_asset_amount,
+            });
+
+            Ok(Some(T::WeightInfo::open_position(boundaries)).into())
+        }
+
+        /// Close the liquidity position of `position_id` owned by the origin. The liquidity and
+        /// the fee earned by the position while the pool price was within its range are
+        /// transferred to the origin. With the usage of `base_asset_min_receive` and
+        /// `quote_asset_min_receive` it's possible to control the min amounts of returned tokens
+        /// including the fee.
+        #[pallet::call_index(16)]
+        #[pallet::weight(T::WeightInfo::close_position(T::MaxLiquidityBoundaries::get()))]
+        pub fn close_position(
+            origin: OriginFor<T>,
+            base_asset: Box<T::AssetKind>,
+            quote_asset: Box<T::AssetKind>,
+            position_id: PositionId,
+            base_asset_min_receive: T::Unit,
+            quote_asset_min_receive: T::Unit,
+        ) -> DispatchResultWithPostInfo {
+            let sender = ensure_signed(origin)?;
+
+            let pool_id = T::PoolLocator::pool_id(&base_asset, &quote_asset)
+                .map_err(|_| Error::<T>::InvalidAssetPair)?;
+
+            let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+            ensure!(pool.is_concentrated(), Error::<T>::WrongLiquidityMode);
+            let position =
+                Positions::<T>::take(&pool_id, position_id).ok_or(Error::<T>::PositionNotFound)?;
+            ensure!(position.owner == sender, Error::<T>::NoPermission);
+            let (lower_sqrt_price, upper_sqrt_price) =
+                (pool.sqrt_price(position.lower)?, pool.sqrt_price(position.upper)?);
+            let pool_account =
+                T::PoolLocator::address(&pool_id).map_err(|_| Error::<T>::InvalidAssetPair)?;
+            Self::observe_price(&base_asset, &quote_asset)?;
+
+            let (base_asset_amount, quote_asset_amount, base_fee, quote_fee, boundaries) =
+                ConcentratedLiquidities::<T>::try_mutate(&pool_id, |state| {
+                    let state = state.as_mut().ok_or(Error::<T>::PoolNotFound)?;
+                    let (fee_growth_inside_base, fee_growth_inside_quote) =
+                        state.fee_growth_inside(position.lower, position.upper)?;
+                    let base_fee = fee_owed::<T>(
+                        position.liquidity,
+                        fee_growth_inside_base,
+                        position.fee_growth_inside_base,
+                    )?;
+                    let quote_fee = fee_owed::<T>(
+                        position.liquidity,
+                        fee_growth_inside_quote,
+                        position.fee_growth_inside_quote,
+                    )?;
+                    let (base_asset_amount, quote_asset_amount) = state.amounts_for_liquidity(
+                        lower_sqrt_price,
+                        upper_sqrt_price,
+                        position.liquidity,
+                        Rounding::Down,
+                    )?;
+                    state.remove_position(position.lower, position.upper, position.liquidity)?;
+                    Ok::<_, Error<T>>((
+                        base_asset_amount.checked_add(&base_fee).ok_or(Error::<T>::Overflow)?,
+                        quote_asset_amount.checked_add(&quote_fee).ok_or(Error::<T>::Overflow)?,
+                        base_fee,
+                        quote_fee,
+                        state.boundaries_len(),
+                    ))
+                })?;
+
+            ensure!(
+                base_asset_amount >= base_asset_min_receive,
+                Error::<T>::AssetOneWithdrawalDidNotMeetMinimum
+            );
+            ensure!(
+                quote_asset_amount >= quote_asset_min_receive,
+                Error::<T>::AssetTwoWithdrawalDidNotMeetMinimum
+            );
+
+            if !base_asset_amount.is_zero() {
+                T::Assets::transfer(
+                    *base_asset,
+                    &pool_account,
+                    &sender,
+                    base_asset_amount,
+                    Expendable,
+                )?;
+            }
+            if !quote_asset_amount.is_zero() {
+                T::Assets::transfer(
+                    *quote_asset,
+                    &pool_account,
+                    &sender,
+                    quote_asset_amount,
+                    Expendable,
+                )?;
+            }
+
+            Self::deposit_event(Event::PositionClosed {
+                who: sender,
+                pool_id,
+                position_id,
+                base_asset_amount,
+                quote_asset_amount,
+                base_fee,
+                quote_fee,
+            });
+
+            // Boundaries of the position may have been removed
+            let boundaries = boundaries.saturating_add(2).min(T::MaxLiquidityBoundaries::get());
+            Ok(Some(T::WeightInfo::close_position(boundaries)).into())
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        fn _account_id() -> T::AccountId {
+            T::PalletId::get().into_account_truncating()
+        }
+
+        /// Reserves and price of the pool with the copy of its orderbook. Every node of the
+        /// orderbook is read from the storage, so it must only be called by the runtime API, never
+        /// by the dispatchables or the hooks. Use [`Self::get_pool_reserves`] or
+        /// [`Self::get_depth`] instead.
+        pub fn get_pool_query(
+            base_asset: &T::AssetKind,
+            quote_asset: &T::AssetKind,
+        ) -> Result<PoolQuery<OrderBookSnapshot<T>, T::Unit>, DispatchError> {
+            let pool_id = T::PoolLocator::pool_id(base_asset, quote_asset).map_err(|_| Error::<T>::PoolNotFound)?;
+            let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+            let (b_r, q_r) = Self::pool_balances(&pool_id, &pool)?;
+            let pool_price = Self::current_price(&pool_id, &pool)?;
+            return Ok(pool.to_pool_query(b_r, q_r, pool_price));
+        }
+
+        pub fn get_pool_metadata(
+            base_asset: &T::AssetKind,
+            quote_asset: &T::AssetKind,
+        ) -> Result<PoolMetadata<T::Unit>, DispatchError> {
+            let pool_id = T::PoolLocator::pool_id(base_asset, quote_asset).map_err(|_| Error::<T>::PoolNotFound)?;
+            let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+            Ok(pool.to_pool_metadata())
+        }
+
+        /// Reserves and price of the pool. Unlike [`Self::get_pool_query`], the orderbook is not
+        /// copied.
+        pub fn get_pool_reserves(
+            base_asset: &T::AssetKind,
+            quote_asset: &T::AssetKind,
+        ) -> Result<PoolReserves<T::Unit>, DispatchError> {
+            let pool_id = T::PoolLocator::pool_id(base_asset, quote_asset)
+                .map_err(|_| Error::<T>::PoolNotFound)?;
+            let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+            let (base_reserve, quote_reserve) = Self::pool_balances(&pool_id, &pool)?;
+            let pool_price = Self::current_price(&pool_id, &pool)?;
+            Ok(PoolReserves { base_reserve, quote_reserve, pool_price })
+        }
+
+        /// Up to `levels` price levels of each side of the orderbook from the best bid and the best
+        /// ask. Read directly from the stored tree, so only the nodes of the returned levels are
+        /// accessed.
+        pub fn get_depth(
+            base_asset: &T::AssetKind,
+            quote_asset: &T::AssetKind,
+            levels: u32,
+        ) -> Result<PoolDepth<T::Unit>, DispatchError> {
+            let pool_id = T::PoolLocator::pool_id(base_asset, quote_asset)
+                .map_err(|_| Error::<T>::PoolNotFound)?;
+            let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+            Ok(PoolDepth {
+                bids: pool.get_orderbook(true).depth(true, levels),
+                asks: pool.get_orderbook(false).depth(false, levels),
+            })
+        }
+
+        /// Up to `limit` of the recent trades of the pool, the latest first. Only up to
+        /// [`Config::MaxRecentTrades`] trades are kept.
+        pub fn recent_trades(
+            base_asset: &T::AssetKind,
+            quote_asset: &T::AssetKind,
+            limit: u32,
+        ) -> Result<Vec<TradeRecordOf<T>>, DispatchError> {
+            let pool_id = T::PoolLocator::pool_id(base_asset, quote_asset)
+                .map_err(|_| Error::<T>::PoolNotFound)?;
+            ensure!(Pools::<T>::contains_key(&pool_id), Error::<T>::PoolNotFound);
+            let count = TradeCount::<T>::get(&pool_id);
+            let max_trades = T::MaxRecentTrades::get().max(1) as u64;
+            let len = count.min(max_trades).min(limit as u64);
+            Ok((1..=len)
+                .filter_map(|i| {
+                    RecentTrades::<T>::get(&pool_id, ((count - i) % max_trades) as u32)
+                })
+                .collect())
+        }
+
+        /// Time weighted average pool price of `base_asset` in `quote_asset` over the last `window`
+        /// blocks, scaled by `pool_decimals`. The price moved within the current block is not
+        /// counted, which makes it costly to manipulate. Observations are binary searched, so it
+        /// reads at most [`Self::twap_weight`] from the storage.
+        pub fn twap(
+            base_asset: &T::AssetKind,
+            quote_asset: &T::AssetKind,
+            window: BlockNumberFor<T>,
+        ) -> Result<T::Unit, DispatchError> {
+            ensure!(!window.is_zero(), Error::<T>::ZeroTwapWindow);
+            let pool_id = T::PoolLocator::pool_id(base_asset, quote_asset)
+                .map_err(|_| Error::<T>::PoolNotFound)?;
+            let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+            let pool_price = Self::current_price(&pool_id, &pool)?;
+            let now = frame_system::Pallet::<T>::block_number();
+            let target = now.checked_sub(&window).ok_or(Error::<T>::InsufficientPriceHistory)?;
+            let count = PriceObservationCount::<T>::get(&pool_id);
+            let max_observations = T::MaxPriceObservations::get().max(1) as u64;
+            let len = count.min(max_observations);
+            // `i`th oldest observation kept in the ring buffer
+            let observation_at = |i: u64| {
+                PriceObservations::<T>::get(&pool_id, ((count - len + i) % max_observations) as u32)
+                    .ok_or(Error::<T>::InsufficientPriceHistory)
+            };
+            let latest =
+                observation_at(len.checked_sub(1).ok_or(Error::<T>::InsufficientPriceHistory)?)?;
+            let cumulative_now = Self::accumulate_price(&latest, pool_price, now);
+            // Number of the observations made no later than `target`
+            let (mut low, mut high) = (0, len);
+            while low < high {
+                let mid = (low + high) / 2;
+                if observation_at(mid)?.block <= target {
+                    low = mid + 1;
+                } else {
+                    high = mid;
+                }
+            }
+            let observation =
+                observation_at(low.checked_sub(1).ok_or(Error::<T>::InsufficientPriceHistory)?)?;
+            let (later_block, later_cumulative) = if low < len {
+                let later = observation_at(low)?;
+                (later.block, later.price_cumulative)
+            } else {
+                (now, cumulative_now)
+            };
+            // Pool price is constant between the observations
+            let cumulative_target = Self::wrapping_add(
+                observation.price_cumulative,
+                Self::mul_div(
+                    &Self::wrapping_sub(later_cumulative, observation.price_cumulative),
+                    &Self::blocks(target - observation.block),
+                    &Self::blocks(later_block - observation.block),
+                )?,
+            );
+            let accumulated = Self::wrapping_sub(cumulative_now, cumulative_target);
+            Ok(accumulated / Self::blocks(window))
+        }
+
+        /// Upper bound of the weight of [`Self::twap`]. It reads the pool, its reserves, the
+        /// observation count, the latest observation and the two observations around the target
+        /// block, which are binary searched.
+        pub fn twap_weight() -> Weight {
+            let searches = T::MaxPriceObservations::get().max(1).ilog2() + 1;
+            T::DbWeight::get().reads(9u64.saturating_add(searches.into()))
+        }
+
+        /// Prices of `base_asset` in `quote_asset` normalised by `pool_decimals`, where `price` is
+        /// from [`Config::OraclePriceSource`]. Price levels of which all orders are expired but not
+        /// swept yet are skipped for the best bid and ask, up to [`Config::MaxExpiredPriceLevels`].
+        pub fn price_info(
+            base_asset: &T::AssetKind,
+            quote_asset: &T::AssetKind,
+        ) -> Result<PriceInfo<BlockNumberFor<T>>, DispatchError> {
+            let pool_id = T::PoolLocator::pool_id(base_asset, quote_asset)
+                .map_err(|_| Error::<T>::PoolNotFound)?;
+            let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+            let pool_price = Self::current_price(&pool_id, &pool)?;
+            let now = frame_system::Pallet::<T>::block_number();
+            let max_expired = T::MaxExpiredPriceLevels::get();
+            let best_bid = pool.best_price(true, &now, max_expired);
+            let best_ask = pool.best_price(false, &now, max_expired);
+            let mid = best_bid
+                .zip(best_ask)
+                .map(|(bid, ask)| bid.saturating_add(ask) / 2u32.into());
+            let price = match T::OraclePriceSource::get() {
+                PriceSource::Spot => pool_price,
+                PriceSource::Mid => mid.ok_or(Error::<T>::PriceNotAvailable)?,
+                PriceSource::Twap(window) => Self::twap(base_asset, quote_asset, window)?,
+            };
+            let normalize = |price| Self::normalize_price(&pool, price);
+            Ok(PriceInfo {
+                price: normalize(price)?,
+                pool_price: normalize(pool_price)?,
+                best_bid: best_bid.map(normalize).transpose()?,
+                best_ask: best_ask.map(normalize).transpose()?,
+                mid: mid.map(normalize).transpose()?,
+                last_trade: Self::last_trade_block(&pool_id),
+            })
+        }
+
+        /// Upper bound of the weight of [`Self::price_info`]. Price levels of each side are read
+        /// twice, once for the depth and once for the expired orders.
+        pub fn price_weight() -> Weight {
+            let levels = T::MaxExpiredPriceLevels::get().saturating_add(1).saturating_mul(4);
+            // `TradeCount` and `RecentTrades` for the last trade
+            let weight = T::WeightInfo::get_pool_query(levels)
+                .saturating_add(T::DbWeight::get().reads(2));
+            match T::OraclePriceSource::get() {
+                PriceSource::Twap(_) => weight.saturating_add(Self::twap_weight()),
+                PriceSource::Spot | PriceSource::Mid => weight,
+            }
+        }
+
+        /// Candles of `resolution` on the pool of `base_asset` and `quote_asset` whose buckets
+        /// are within `from` and `to`, oldest first. Buckets without any trade are skipped, and
+        /// only the latest [`Config::MaxCandles`] buckets are kept.
+        pub fn candles(
+            base_asset: &T::AssetKind,
+            quote_asset: &T::AssetKind,
+            resolution: u32,
+            from: BlockNumberFor<T>,
+            to: BlockNumberFor<T>,
+        ) -> Result<Vec<CandleOf<T>>, DispatchError> {
+            let pool_id = T::PoolLocator::pool_id(base_asset, quote_asset)
+                .map_err(|_| Error::<T>::PoolNotFound)?;
+            ensure!(Pools::<T>::contains_key(&pool_id), Error::<T>::PoolNotFound);
+            ensure!(
+                T::CandleResolutions::get().contains(&resolution),
+                Error::<T>::InvalidCandleResolution
+            );
+            let max_candles = T::MaxCandles::get().max(1);
+            let last = to.saturated_into::<u32>() / resolution;
+            let first = (from.saturated_into::<u32>() / resolution)
+                .max(last.saturating_sub(max_candles - 1));
+            Ok((first..=last)
+                .filter_map(|bucket| {
+                    Candles::<T>::get((&pool_id, resolution, bucket % max_candles)).filter(
+                        |candle| candle.start == BlockNumberFor::<T>::from(bucket * resolution),
+                    )
+                })
+                .collect())
+        }
+
+        /// Open limit orders of `who` on the pool of `base_asset` and `quote_asset` if given,
+        /// otherwise on all pools. Looked up from [`OpenOrders`], so only the orders of `who` are
+        /// read from the orderbook.
+        pub fn open_orders(
+            who: &T::AccountId,
+            maybe_pool: Option<(&T::AssetKind, &T::AssetKind)>,
+        ) -> Result<Vec<OpenOrderOf<T>>, DispatchError> {
+            let orders: Vec<(T::PoolId, OrderId, T::Unit)> = match maybe_pool {
+                Some((base_asset, quote_asset)) => {
+                    let pool_id = T::PoolLocator::pool_id(base_asset, quote_asset)
+                        .map_err(|_| Error::<T>::PoolNotFound)?;
+                    ensure!(Pools::<T>::contains_key(&pool_id), Error::<T>::PoolNotFound);
+                    OpenOrders::<T>::iter_prefix((who.clone(), pool_id.clone()))
+                        .map(|(order_id, (price, _))| (pool_id.clone(), order_id, price))
+                        .collect()
+                }
+                None => OpenOrders::<T>::iter_prefix((who.clone(),))
+                    .map(|((pool_id, order_id), (price, _))| (pool_id, order_id, price))
+                    .collect(),
+            };
+            let mut pools: BTreeMap<T::PoolId, Option<Pool<T>>> = BTreeMap::new();
+            let mut open_orders = Vec::new();
+            for (pool_id, order_id, price) in orders {
+                let maybe_pool = pools
+                    .entry(pool_id.clone())
+                    .or_insert_with(|| Pools::<T>::get(&pool_id));
+                let Some(pool) = maybe_pool else { continue };
+                let is_bid = order_id.is_bid();
+                if let Some(order) = pool.get_order(is_bid, price, order_id) {
+                    open_orders.push(OpenOrder {
+                        pool_id,
+                        order_id,
+                        is_bid,
+                        price,
+                        quantity: order.quantity(),
+                        expired_at: order.expired_at(),
+                    });
+                }
+            }
+            Ok(open_orders)
+        }
+
+        /// Amount of quote asset to pay(bid) or receive(ask) including the taker fee for the market
+        /// order of `quantity`, matched from both the pool and the orderbook as `market_order`
+        /// does. Only up to [`Config::MaxOrdersFilled`] price levels are looked up.
+        ///
+        /// Note that it is an estimate. Taker fee is rounded per price level rather than per order
+        /// and the expired orders which are not removed yet are counted.
+        pub fn quote_market_order(
+            base_asset: &T::AssetKind,
+            quote_asset: &T::AssetKind,
+            quantity: T::Unit,
+            is_bid: bool,
+        ) -> Result<T::Unit, DispatchError> {
+            let pool_id = T::PoolLocator::pool_id(base_asset, quote_asset)
+                .map_err(|_| Error::<T>::PoolNotFound)?;
+            let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+            ensure!(pool.is_valid_order_quantity(quantity), Error::<T>::InvalidOrderQuantity);
+            let mut concentrated = ConcentratedLiquidities::<T>::get(&pool_id);
+            let (mut b_r, mut q_r) = match concentrated {
+                Some(_) => (Zero::zero(), Zero::zero()),
+                None => Self::get_reserves(base_asset, quote_asset)?,
+            };
+            let levels = if is_bid {
+                pool.get_orderbook(false).depth(false, T::MaxOrdersFilled::get())
             } else {
-                (quote_asset, base_asset)
+                pool.get_orderbook(true).depth(true, T::MaxOrdersFilled::get())
             };
+            let mut remain_orders = quantity;
+            let mut quote_amount: T::Unit = Zero::zero();
+            let mut fee: T::Unit = Zero::zero();
+            for (price, level_quantity, _) in levels {
+                if remain_orders.is_zero() {
+                    break;
+                }
+                let max_swap_quantity = match concentrated.clone() {
+                    Some(liquidity) => Self::find_max_swap_quantity_concentrated(
+                        is_bid,
+                        price,
+                        &pool,
+                        liquidity,
+                        remain_orders,
+                    )?,
+                    None => Self::find_max_swap_quantity_with_reserves(
+                        is_bid,
+                        price,
+                        &pool,
+                        b_r,
+                        q_r,
+                        remain_orders,
+                    )?,
+                };
+                if remain_orders <= max_swap_quantity {
+                    break;
+                }
+                if !max_swap_quantity.is_zero() {
+                    quote_amount += Self::swap_on_reserves(
+                        is_bid,
+                        max_swap_quantity,
+                        concentrated.as_mut(),
+                        &mut b_r,
+                        &mut q_r,
+                    )?;
+                    remain_orders -= max_swap_quantity;
+                }
+                let filled = remain_orders.min(level_quantity);
+                let book_amount = Self::frozen_amount(&pool, true, price, filled);
+                quote_amount += book_amount;
+                fee = fee.saturating_add(pool.taker_fee(book_amount));
+                remain_orders -= filled;
+            }
+            if !remain_orders.is_zero() {
+                quote_amount += Self::swap_on_reserves(
+                    is_bid,
+                    remain_orders,
+                    concentrated.as_mut(),
+                    &mut b_r,
+                    &mut q_r,
+                )?;
+            }
+            Ok(if is_bid {
+                quote_amount.saturating_add(fee)
+            } else {
+                quote_amount.saturating_sub(fee)
+            })
+        }
+
+        /// Swap `quantity` of base asset on the given reserves, or on the concentrated liquidity if
+        /// given, without transferring any asset. Return the amount of quote asset paid(bid) or
+        /// received(ask).
+        fn swap_on_reserves(
+            is_bid: bool,
+            quantity: T::Unit,
+            concentrated: Option<&mut ConcentratedLiquidity<T>>,
+            b_r: &mut T::Unit,
+            q_r: &mut T::Unit,
+        ) -> Result<T::Unit, Error<T>> {
+            if let Some(liquidity) = concentrated {
+                let swapped = liquidity.swap(!is_bid, !is_bid, quantity, None)?;
+                return Ok(if is_bid { swapped.amount_in } else { swapped.amount_out });
+            }
+            if is_bid {
+                let amount_in = Self::get_amount_in(&quantity, q_r, b_r)?;
+                *b_r = b_r.checked_sub(&quantity).ok_or(Error::<T>::Overflow)?;
+                *q_r = q_r.checked_add(&amount_in).ok_or(Error::<T>::Overflow)?;
+                Ok(amount_in)
+            } else {
+                let amount_out = Self::get_amount_out(&quantity, b_r, q_r)?;
+                *b_r = b_r.checked_add(&quantity).ok_or(Error::<T>::Overflow)?;
+                *q_r = q_r.checked_sub(&amount_out).ok_or(Error::<T>::Overflow)?;
+                Ok(amount_out)
+            }
+        }
+
+        fn freeze_asset(
+            who: &T::AccountId,
+            asset: &T::AssetKind,
+            amount: T::Unit,
+        ) -> DispatchResult {
+            T::AssetsFreezer::increase_frozen(
+                asset.clone(),
+                &FreezeReason::LimitOrder.into(),
+                who,
+                amount,
+            )
+        }
+
+        pub(crate) fn unfreeze_asset(
+            who: &T::AccountId,
+            asset: &T::AssetKind,
+            amount: T::Unit,
+        ) -> DispatchResult {
+            T::AssetsFreezer::decrease_frozen(
+                asset.clone(),
+                &FreezeReason::LimitOrder.into(),
+                who,
+                amount,
+            )
+        }
+
+        /// Amount of asset which should be frozen for the order of `quantity` at `price`.
+        ///
+        /// - `bid`: `price * quantity` of quote asset, adjusted by the pool and quote decimals
+        /// - `ask`: `quantity` of base asset
+        pub(crate) fn frozen_amount(
+            pool: &Pool<T>,
+            is_bid: bool,
+            price: T::Unit,
+            quantity: T::Unit,
+        ) -> T::Unit {
+            if is_bid {
+                let (p_adj, _, q_adj) = pool.decimal_adjustment();
+                (price * quantity).denom(p_adj.unwrap_or(0) + q_adj.unwrap_or(0))
+            } else {
+                quantity
+            }
+        }
+
+        /// Settle the orders filled from the orderbook between `orderer` and the owners of the
+        /// orders with the taker and maker fees. Return the taker fee charged to `orderer`.
+        fn handle_filled_orders(
+            is_bid: bool,
+            pool: &Pool<T>,
+            base_asset: T::AssetKind,
+            quote_asset: T::AssetKind,
+            orderer: &T::AccountId,
+            filled_orders: Vec<(T::AccountId, T::Unit, T::Unit)>,
+        ) -> Result<T::Unit, DispatchError> {
+            let mut taker_fee: T::Unit = Zero::zero();
+            // (Maker, Rebate) paid out of the taker fee
+            let mut rebates: Vec<(T::AccountId, T::Unit)> = Vec::new();
+            let mut total_rebate: T::Unit = Zero::zero();
             for (owner, p, q) in filled_orders {
-                // Release amount would be different based on `is_bid`. If it is bid order, `q`
-                // amount of base asset would be released. While if it is ask order, `p*q` amount of
-                // quote asset would be released.
-                let released = if is_bid { q } else { 
-                    let r: T::Unit = p * q;
-                    let (p_adj, _, q_adj) = pool.decimal_adjustment();
-                    r.denom(p_adj.unwrap_or(0) + q_adj.unwrap_or(0))
+                // `q` amount of base asset is exchanged with `p*q` amount of quote asset
+                let quote_amount = Self::frozen_amount(pool, true, p, q);
+                // If it is matched with ask order, `q` amount of base asset should be released.
+                // While it is matched with bid order, `p*q` amount of quote asset should be released.
+                let (frozen_asset, released) = if is_bid {
+                    (&base_asset, q)
+                } else {
+                    (&quote_asset, quote_amount)
                 };
                 // 1. Release the frozen asset
-                T::AssetsFreezer::decrease_frozen(
-                    asset1.clone(),
-                    &FreezeReason::LimitOrder.into(),
-                    &owner,
-                    released,
+                Self::unfreeze_asset(&owner, frozen_asset, released)?;
+                // 2. Transfer assets between orderer and owner of limit order
+                let (base_from, base_to) = if is_bid { (&owner, orderer) } else { (orderer, &owner) };
+                T::Assets::transfer(base_asset.clone(), base_from, base_to, q, Preserve)?;
+                T::Assets::transfer(quote_asset.clone(), base_to, base_from, quote_amount, Preserve)?;
+                log::debug!(target: LOG_TARGET, "HandleFilledOrder {:?} => {:?} base {:?}, {:?} => {:?} quote {:?}",
+                    base_from,
+                    base_to,
+                    q,
+                    base_to,
+                    base_from,
+                    quote_amount,
+                );
+                taker_fee = taker_fee.saturating_add(pool.taker_fee(quote_amount));
+                // 3. Settle the maker fee
+                match pool.maker_fee_rate() {
+                    MakerFeeRate::Fee(rate) => {
+                        // Charged from the asset the maker received
+                        let (asset, amount) = if is_bid {
+                            (&quote_asset, quote_amount)
+                        } else {
+                            (&base_asset, q)
+                        };
+                        let maker_fee = rate.mul_ceil(amount);
+                        if !maker_fee.is_zero() {
+                            let credit = Self::withdraw(asset.clone(), &owner, maker_fee, true)?;
+                            T::TakerFeeTarget::on_unbalanced(credit);
+                        }
+                    },
+                    MakerFeeRate::Rebate(rate) => {
+                        let rebate = rate.mul_floor(quote_amount);
+                        total_rebate = total_rebate.saturating_add(rebate);
+                        rebates.push((owner, rebate));
+                    },
+                }
+            }
+            // Never pay out more than collected from the taker
+            ensure!(total_rebate <= taker_fee, Error::<T>::RebateExceedsTakerFee);
+            // 4. Charge the taker fee and pay the rebates out of it
+            if !taker_fee.is_zero() {
+                let mut credit = Self::withdraw(quote_asset, orderer, taker_fee, true)?;
+                for (maker, rebate) in rebates {
+                    if rebate.is_zero() {
+                        continue;
+                    }
+                    let (paid, remain) = credit.split(rebate);
+                    credit = remain;
+                    T::Assets::resolve(&maker, paid).map_err(|_| Error::<T>::BelowMinimum)?;
+                }
+                T::TakerFeeTarget::on_unbalanced(credit);
+            }
+
+            Ok(taker_fee)
+        }
+
+        /// Revert if the current block is after `deadline`
+        fn ensure_deadline(deadline: Option<BlockNumberFor<T>>) -> DispatchResult {
+            if let Some(deadline) = deadline {
+                ensure!(
+                    frame_system::Pallet::<T>::block_number() <= deadline,
+                    Error::<T>::DeadlinePassed
+                );
+            }
+            Ok(())
+        }
+
+        /// Worst case of the work done on the orderbook by a single order. As
+        /// [`Config::MaxOrdersFilled`] is checked once a price level is crossed, all orders of one
+        /// more price level could be filled beyond it.
+        pub(crate) fn max_match_count() -> MatchCount {
+            let max_orders = T::MaxOrdersFilled::get();
+            MatchCount {
+                levels: max_orders.saturating_add(1),
+                orders: max_orders.saturating_add(T::MaxOrdersPerTick::get()),
+                concentrated: true,
+            }
+        }
+
+        /// Weight of the order which did the work of `matched`: `order_weight` of the price
+        /// levels crossed and the orders filled, on top of updating the candles. The pool with
+        /// the concentrated liquidity is swapped up to once per price level and once after the
+        /// last, each of which may cross up to [`Config::MaxLiquidityBoundaries`].
+        pub(crate) fn matched_weight(
+            order_weight: fn(u32, u32) -> Weight,
+            matched: &MatchCount,
+        ) -> Weight {
+            let concentrated = if matched.concentrated {
+                T::WeightInfo::swap_concentrated(T::MaxLiquidityBoundaries::get())
+                    .saturating_mul(matched.levels.saturating_add(1).into())
+            } else {
+                Weight::zero()
+            };
+            order_weight(matched.levels, matched.orders)
+                .saturating_add(Self::candles_weight(matched))
+                .saturating_add(concentrated)
+        }
+
+        /// Worst case weight of swapping on the concentrated liquidity of the pools of `hops`,
+        /// which is charged on top of the swap.
+        pub(crate) fn max_concentrated_swap_weight(hops: usize) -> Weight {
+            T::WeightInfo::swap_concentrated(T::MaxLiquidityBoundaries::get())
+                .saturating_mul(hops as u64)
+        }
+
+        /// Same as [`Self::max_concentrated_swap_weight`] but only for the pools along `path` with
+        /// [`LiquidityMode::Concentrated`].
+        pub(crate) fn concentrated_swap_weight(path: &[T::AssetKind]) -> Weight {
+            let hops = path
+                .windows(2)
+                .filter(|hop| {
+                    Self::get_pool_id(&hop[0], &hop[1]).map_or(false, |pool_id| {
+                        ConcentratedLiquidities::<T>::contains_key(pool_id)
+                    })
+                })
+                .count();
+            Self::max_concentrated_swap_weight(hops)
+        }
+
+        /// Weight of updating a candle of each of [`Config::MaxCandleResolutions`] for every trade
+        /// of `matched`, which is one per order filled and one on the pool before each price
+        /// level crossed and after the last.
+        pub(crate) fn candles_weight(matched: &MatchCount) -> Weight {
+            let trades = matched.orders.saturating_add(matched.levels).saturating_add(1);
+            let candles = trades.saturating_mul(T::MaxCandleResolutions::get());
+            T::DbWeight::get().reads_writes(candles.into(), candles.into())
+        }
+
+        /// Worst case weight of triggering [`Config::MaxTriggeredStopOrders`] of stop limit orders
+        /// on a pool, each of which is converted to the limit order matching up to
+        /// [`Pallet::max_match_count`].
+        pub(crate) fn max_trigger_stop_orders_weight() -> Weight {
+            let max = Self::max_match_count();
+            T::WeightInfo::cancel_stop_limit_order(T::MaxStopOrders::get())
+                .saturating_add(Self::matched_weight(T::WeightInfo::limit_order, &max))
+                .saturating_mul(T::MaxTriggeredStopOrders::get().into())
+        }
+
+        /// Account `orders` filled or expired on another price level to `matched`. Revert if it
+        /// exceeds [`Config::MaxOrdersFilled`].
+        fn accrue_match_count(matched: &mut MatchCount, orders: u32) -> DispatchResult {
+            ensure!(
+                matched.orders < T::MaxOrdersFilled::get(),
+                Error::<T>::TooManyOrdersFilled
+            );
+            matched.accrue(orders);
+            Ok(())
+        }
+
+        /// Spend up to `quote_amount` of quote asset to buy base asset. Matching takes turns
+        /// between the pool and the orderbook like `do_match_order`, while the orderbook is filled
+        /// only by multiple of `lot_size` which can be afforded including the taker fee.
+        pub(crate) fn do_market_order_with_quote(
+            taker: &T::AccountId,
+            base_asset: &T::AssetKind,
+            quote_asset: &T::AssetKind,
+            quote_amount: T::Unit,
+            min_quantity: Option<T::Unit>,
+        ) -> Result<MatchCount, DispatchError> {
+            ensure!(quote_amount > Zero::zero(), Error::<T>::WrongDesiredAmount);
+            let pool_id = T::PoolLocator::pool_id(base_asset, quote_asset)
+                .map_err(|_| Error::<T>::InvalidAssetPair)?;
+            let now = frame_system::Pallet::<T>::block_number();
+            Pools::<T>::try_mutate_exists(pool_id.clone(), |maybe_pool| -> Result<MatchCount, DispatchError> {
+                let mut pool = maybe_pool.take().ok_or(Error::<T>::PoolNotFound)?;
+                let mut matched =
+                    MatchCount { concentrated: pool.is_concentrated(), ..Default::default() };
+                let mut remain_quote = quote_amount;
+                let mut filled: T::Unit = Zero::zero();
+                // (Account, Price, Quantity)
+                let mut filled_orders: Vec<(T::AccountId, T::Unit, T::Unit)> = Default::default();
+                while remain_quote > Zero::zero() {
+                    let target_price = match pool.next_ask_order() {
+                        Some((target_price, _)) => target_price,
+                        None => {
+                            // No orders on Orderbook. Spend the rest on the pool.
+                            let bought = Self::do_swap_exact_tokens_for_tokens(
+                                taker,
+                                vec![quote_asset.clone(), base_asset.clone()],
+                                remain_quote,
+                                None,
+                                taker,
+                                false,
+                            )?;
+                            Self::record_pool_trade(
+                                &pool_id,
+                                &pool,
+                                taker,
+                                true,
+                                bought,
+                                remain_quote,
+                            )?;
+                            filled += bought;
+                            break;
+                        },
+                    };
+                    let expired =
+                        Self::do_expire_orders_at(&pool_id, &mut pool, false, target_price, now)?;
+                    if expired > 0 {
+                        Self::accrue_match_count(&mut matched, expired)?;
+                        continue;
+                    }
+                    // 1. Swap from pool until the pool price reaches the orderbook price
+                    let max_pool_quantity =
+                        Self::get_pool_amount_out(quote_asset, base_asset, remain_quote)?;
+                    let swap_quantity = Self::find_max_swap_quantity(
+                        true,
+                        target_price,
+                        &pool,
+                        base_asset,
+                        quote_asset,
+                        max_pool_quantity,
+                    )?;
+                    if swap_quantity == max_pool_quantity && !swap_quantity.is_zero() {
+                        // All remain quote spent on the pool
+                        let bought = Self::do_swap_exact_tokens_for_tokens(
+                            taker,
+                            vec![quote_asset.clone(), base_asset.clone()],
+                            remain_quote,
+                            None,
+                            taker,
+                            false,
+                        )?;
+                        Self::record_pool_trade(&pool_id, &pool, taker, true, bought, remain_quote)?;
+                        filled += bought;
+                        break;
+                    }
+                    if !swap_quantity.is_zero() {
+                        let paid = Self::do_fill_pool(
+                            true,
+                            taker,
+                            &pool_id,
+                            &pool,
+                            swap_quantity,
+                            base_asset,
+                            quote_asset,
+                        )?;
+                        remain_quote = remain_quote.saturating_sub(paid);
+                        filled += swap_quantity;
+                    }
+                    // 2. Fill the orderbook at `target_price` as much as affordable
+                    let book_quantity = Self::affordable_quantity(&pool, target_price, remain_quote);
+                    if book_quantity.is_zero() {
+                        break;
+                    }
+                    let mut remain_orders = book_quantity;
+                    let filled_before = filled_orders.len();
+                    let filled_count = Self::do_fill_book(
+                        true,
+                        taker,
+                        &pool_id,
+                        &mut pool,
+                        target_price,
+                        &mut remain_orders,
+                        &mut filled_orders,
+                    )?;
+                    Self::accrue_match_count(&mut matched, filled_count)?;
+                    for (_, price, quantity) in filled_orders[filled_before..].iter() {
+                        let cost = Self::frozen_amount(&pool, true, *price, *quantity);
+                        remain_quote =
+                            remain_quote.saturating_sub(cost.saturating_add(pool.taker_fee(cost)));
+                    }
+                    filled += book_quantity - remain_orders;
+                }
+                if let Some(min_quantity) = min_quantity {
+                    ensure!(
+                        filled >= min_quantity,
+                        Error::<T>::ProvidedMinimumNotSufficientForSwap
+                    );
+                }
+                let fee = Self::handle_filled_orders(
+                    true,
+                    &pool,
+                    base_asset.clone(),
+                    quote_asset.clone(),
+                    taker,
+                    filled_orders,
                 )?;
-                // 2. Transfer assets between orderer and owner of limit order
-                let (transfer1, transfer2) = if is_bid { (q, released) } else { (released, q) };
-                T::Assets::transfer(asset1.clone(), &owner, &orderer, transfer1, Preserve)?;
-                T::Assets::transfer(asset2.clone(), &orderer, &owner, transfer2, Preserve)?;
-                log::debug!(target: LOG_TARGET, "HandleFilledOrder {:?} {:?} {:?} {:?} {:?} {:?}",
-                    orderer,
-                    transfer1,
-                    transfer2,
-                    owner,
-                    transfer2,
-                    transfer1,
-                );
-            }
+                *maybe_pool = Some(pool);
+                Self::deposit_event(Event::<T>::OrderMatched {
+                    orderer: taker.clone(),
+                    filled,
+                    is_bid: true,
+                    fee,
+                });
+                Self::deposit_event(Event::<T>::MarketOrder {
+                    taker: taker.clone(),
+                });
+                Ok(matched)
+            })
+        }
 
-            Ok(())
+        /// Maximum multiple of `lot_size` of orders at `price` which could be bought with
+        /// `budget` of quote asset including the taker fee
+        fn affordable_quantity(pool: &Pool<T>, price: T::Unit, budget: T::Unit) -> T::Unit {
+            let lot_size = pool.lot_size();
+            let cost = |lots: T::Unit| {
+                let amount = Self::frozen_amount(pool, true, price, lots.saturating_mul(lot_size));
+                amount.saturating_add(pool.taker_fee(amount))
+            };
+            let lot_cost = Self::frozen_amount(pool, true, price, lot_size).max(One::one());
+            let mut min: T::Unit = Zero::zero();
+            let mut max: T::Unit = budget / lot_cost;
+            // Binary search for the maximum lots whose cost doesn't exceed the budget
+            while min < max {
+                let mid = (min + max + One::one()) / 2u32.into();
+                if cost(mid) <= budget {
+                    min = mid;
+                } else {
+                    max = mid - One::one();
+                }
+            }
+            min * lot_size
         }
 
         /// `Quantity` of `Market` orders filled from _Pool_
@@ -999,21 +2692,42 @@ pub mod pallet {
             base_asset: &T::AssetKind,
             quote_asset: &T::AssetKind,
             quantity: T::Unit,
-        ) -> DispatchResult {
+            quote_limit: Option<T::Unit>,
+        ) -> Result<MatchCount, DispatchError> {
             ensure!(quantity > Zero::zero(), Error::<T>::WrongDesiredAmount);
             let pool_id = T::PoolLocator::pool_id(base_asset, quote_asset)
                 .map_err(|_| Error::<T>::InvalidAssetPair)?;
-            Pools::<T>::try_mutate_exists(pool_id, |maybe_pool| -> DispatchResult {
+            Pools::<T>::try_mutate_exists(pool_id, |maybe_pool| -> Result<MatchCount, DispatchError> {
                 let mut pool = maybe_pool.take().ok_or(Error::<T>::PoolNotFound)?;
                 ensure!(pool.is_valid_order_quantity(quantity), Error::<T>::InvalidOrderQuantity);
-                Self::do_match_order(is_bid, taker, &mut pool, base_asset, quote_asset, quantity)?;
+                let (_, quote_amount, matched) = Self::do_match_order(
+                    is_bid,
+                    taker,
+                    &mut pool,
+                    base_asset,
+                    quote_asset,
+                    quantity,
+                    None,
+                )?;
+                if let Some(quote_limit) = quote_limit {
+                    if is_bid {
+                        ensure!(
+                            quote_amount <= quote_limit,
+                            Error::<T>::ProvidedMaximumNotSufficientForSwap
+                        );
+                    } else {
+                        ensure!(
+                            quote_amount >= quote_limit,
+                            Error::<T>::ProvidedMinimumNotSufficientForSwap
+                        );
+                    }
+                }
                 *maybe_pool = Some(pool);
                 Self::deposit_event(Event::<T>::MarketOrder {
                     taker: taker.clone(),
                 });
-                Ok(())
-            })?;
-            Ok(())
+                Ok(matched)
+            })
         }
 
         /// Do `quantity` of limit order for given `order_price`.
@@ -1025,6 +2739,8 @@ pub mod pallet {
         /// - `order_price` is not multiple of `tick_size`
         /// - `order_quantity` is not multiple of `lot_size`
         /// - `order_quantity` is zero
+        ///
+        /// Return the work done on the orderbook while matching.
         pub(crate) fn do_limit_order(
             maker: T::AccountId,
             order_price: T::Unit,
@@ -1032,18 +2748,26 @@ pub mod pallet {
             is_bid: bool,
             base_asset: &T::AssetKind,
             quote_asset: &T::AssetKind,
-        ) -> DispatchResult {
+            time_in_force: TimeInForce<BlockNumberFor<T>>,
+        ) -> Result<MatchCount, DispatchError> {
             let pool_id = T::PoolLocator::pool_id(base_asset, quote_asset)
                 .map_err(|_| Error::<T>::InvalidAssetPair)?;
-            Pools::<T>::try_mutate(pool_id.clone(), |maybe_pool| -> DispatchResult {
+            let now = frame_system::Pallet::<T>::block_number();
+            let expired_at = match time_in_force {
+                TimeInForce::GoodTilBlock(expired_at) => {
+                    ensure!(
+                        expired_at > now &&
+                            expired_at <= now.saturating_add(T::OrderExpiration::get()),
+                        Error::<T>::InvalidExpiry
+                    );
+                    expired_at
+                },
+                _ => now.saturating_add(T::OrderExpiration::get()),
+            };
+            Pools::<T>::try_mutate(pool_id.clone(), |maybe_pool| -> Result<MatchCount, DispatchError> {
                 let mut pool = maybe_pool.take().ok_or(Error::<T>::PoolNotFound)?;
-                let (b_r, q_r) = Self::get_reserves(base_asset, quote_asset)?;
-                let pool_price = Self::pool_price(
-                    &pool,
-                    &b_r,
-                    &q_r,
-                )
-                .map_err(|_| Error::<T>::ZeroLiquidity)?;
+                let pool_price = Self::current_price(&pool_id, &pool)
+                    .map_err(|_| Error::<T>::ZeroLiquidity)?;
                 ensure!(
                     pool.is_valid_order_price(order_price),
                     Error::<T>::InvalidOrderPrice
@@ -1059,7 +2783,11 @@ pub mod pallet {
                     order_price <= pool_price
                 };
 
-                if should_match_order {
+                let (remain_orders, _, matched) = if should_match_order {
+                    ensure!(
+                        time_in_force != TimeInForce::PostOnly,
+                        Error::<T>::OrderWouldCross
+                    );
                     Self::do_match_order(
                         is_bid,
                         &maker,
@@ -1067,18 +2795,30 @@ pub mod pallet {
                         &base_asset,
                         &quote_asset,
                         order_quantity,
-                    )?;
+                        Some(order_price),
+                    )?
                 } else {
-                    Self::do_place_order(
-                        is_bid,
-                        &pool_id,
-                        &mut pool,
-                        &maker,
-                        &base_asset,
-                        &quote_asset,
-                        order_price,
-                        order_quantity,
-                    )?;
+                    (order_quantity, Zero::zero(), MatchCount::default())
+                };
+                if remain_orders > Zero::zero() {
+                    match time_in_force {
+                        // Unfilled orders are dropped
+                        TimeInForce::ImmediateOrCancel => {},
+                        TimeInForce::FillOrKill => return Err(Error::<T>::OrderNotFilled.into()),
+                        _ => {
+                            Self::do_place_order(
+                                is_bid,
+                                &pool_id,
+                                &mut pool,
+                                &maker,
+                                &base_asset,
+                                &quote_asset,
+                                order_price,
+                                remain_orders,
+                                expired_at,
+                            )?;
+                        },
+                    }
                 }
                 *maybe_pool = Some(pool);
                 Self::deposit_event(Event::<T>::LimitOrder {
@@ -1088,76 +2828,578 @@ pub mod pallet {
                     order_quantity,
                     is_bid,
                 });
-                Ok(())
-            })
+                Ok(matched)
+            })
+        }
+
+        /// Rest the order on the orderbook, freezing the asset it may spend. Return the id of the
+        /// placed order.
+        pub(crate) fn do_place_order(
+            is_bid: bool,
+            pool_id: &T::PoolId,
+            pool: &mut Pool<T>,
+            maker: &T::AccountId,
+            base_asset: &T::AssetKind,
+            quote_asset: &T::AssetKind,
+            price: T::Unit,
+            quantity: T::Unit,
+            expired_at: BlockNumberFor<T>,
+        ) -> Result<OrderId, DispatchError> {
+            let freeze_amount = Self::frozen_amount(pool, is_bid, price, quantity);
+            if is_bid {
+                Self::freeze_asset(maker, quote_asset, freeze_amount)?;
+                log::debug!(target: LOG_TARGET, "Freeze {:?} {:?} for bid order", quote_asset, freeze_amount);
+            } else {
+                Self::freeze_asset(maker, base_asset, freeze_amount)?;
+                log::debug!(target: LOG_TARGET, "Freeze {:?} {:?} for ask order", base_asset, freeze_amount);
+            }
+            let (price, order_id) = pool
+                .place_order(is_bid, maker, price, quantity, expired_at)
+                .map_err(|_| Error::<T>::ErrorOnPlaceOrder)?;
+            OrderExpiries::<T>::insert(expired_at, (pool_id.clone(), order_id), price);
+            Self::insert_open_order(maker, pool_id, order_id, price)?;
+            Self::deposit_event(Event::<T>::LimitOrderPlaced {
+                maker: maker.clone(),
+                order_price: price,
+                pool_id: pool_id.clone(),
+                order_id,
+                order_quantity: quantity,
+                is_bid,
+            });
+            Ok(order_id)
+        }
+
+        pub(crate) fn do_cancel_order(
+            owner: &T::AccountId,
+            base_asset: &T::AssetKind,
+            quote_asset: &T::AssetKind,
+            price: T::Unit,
+            order_id: OrderId,
+            quantity: T::Unit,
+        ) -> DispatchResult {
+            let pool_id = T::PoolLocator::pool_id(base_asset, quote_asset)
+                .map_err(|_| Error::<T>::InvalidAssetPair)?;
+            Pools::<T>::try_mutate(pool_id.clone(), |pool| -> DispatchResult {
+                let mut updated = pool.take().ok_or(Error::<T>::PoolNotFound)?;
+                updated
+                    .cancel_order(owner, price, order_id, quantity)
+                    .map_err(|_| Error::<T>::ErrorOnCancelOrder)?;
+                // Release the frozen asset with the same amount frozen on `do_place_order`
+                let is_bid = order_id.is_bid();
+                let released = Self::frozen_amount(&updated, is_bid, price, quantity);
+                let asset = if is_bid { quote_asset } else { base_asset };
+                Self::unfreeze_asset(owner, asset, released)?;
+                log::debug!(target: LOG_TARGET, "Release {:?} {:?} for cancelled order", asset, released);
+                if updated.get_order(is_bid, price, order_id).is_none() {
+                    Self::take_open_order(owner, &pool_id, order_id)?;
+                }
+                *pool = Some(updated);
+                Self::deposit_event(Event::<T>::OrderCancelled {
+                    pool_id,
+                    owner: owner.clone(),
+                    order_id,
+                    released,
+                });
+                Ok(())
+            })
+        }
+
+        pub(crate) fn do_cancel_order_by_id(
+            owner: &T::AccountId,
+            base_asset: &T::AssetKind,
+            quote_asset: &T::AssetKind,
+            order_id: OrderId,
+        ) -> DispatchResult {
+            let pool_id = T::PoolLocator::pool_id(base_asset, quote_asset)
+                .map_err(|_| Error::<T>::InvalidAssetPair)?;
+            Pools::<T>::try_mutate(pool_id.clone(), |maybe_pool| -> DispatchResult {
+                let pool = maybe_pool.as_mut().ok_or(Error::<T>::PoolNotFound)?;
+                Self::do_remove_open_order(owner, &pool_id, pool, order_id)
+            })
+        }
+
+        /// Cancel all open orders of `owner` on the pool. Return the number of the orders
+        /// cancelled, which is bounded by [`Config::MaxOpenOrders`].
+        pub(crate) fn do_cancel_all_orders(
+            owner: &T::AccountId,
+            base_asset: &T::AssetKind,
+            quote_asset: &T::AssetKind,
+        ) -> Result<u32, DispatchError> {
+            let pool_id = T::PoolLocator::pool_id(base_asset, quote_asset)
+                .map_err(|_| Error::<T>::InvalidAssetPair)?;
+            let order_ids: Vec<OrderId> =
+                OpenOrders::<T>::iter_key_prefix((owner.clone(), pool_id.clone())).collect();
+            ensure!(!order_ids.is_empty(), Error::<T>::OrderNotFound);
+            let cancelled = order_ids.len() as u32;
+            Pools::<T>::try_mutate(pool_id.clone(), |maybe_pool| -> DispatchResult {
+                let pool = maybe_pool.as_mut().ok_or(Error::<T>::PoolNotFound)?;
+                for order_id in order_ids {
+                    Self::do_remove_open_order(owner, &pool_id, pool, order_id)?;
+                }
+                Ok(())
+            })?;
+            Ok(cancelled)
+        }
+
+        pub(crate) fn do_amend_order(
+            owner: &T::AccountId,
+            base_asset: &T::AssetKind,
+            quote_asset: &T::AssetKind,
+            order_id: OrderId,
+            new_price: T::Unit,
+            new_quantity: T::Unit,
+        ) -> DispatchResult {
+            let pool_id = T::PoolLocator::pool_id(base_asset, quote_asset)
+                .map_err(|_| Error::<T>::InvalidAssetPair)?;
+            Pools::<T>::try_mutate(pool_id.clone(), |maybe_pool| -> DispatchResult {
+                let pool = maybe_pool.as_mut().ok_or(Error::<T>::PoolNotFound)?;
+                let (price, _) = OpenOrders::<T>::get((owner.clone(), pool_id.clone(), order_id))
+                    .ok_or(Error::<T>::OrderNotFound)?;
+                let is_bid = order_id.is_bid();
+                let order = pool
+                    .get_order(is_bid, price, order_id)
+                    .ok_or(Error::<T>::OrderNotFound)?;
+                ensure!(order.owner() == *owner, Error::<T>::NoPermission);
+                ensure!(pool.is_valid_order_price(new_price), Error::<T>::InvalidOrderPrice);
+                ensure!(
+                    pool.is_valid_order_quantity(new_quantity),
+                    Error::<T>::InvalidOrderQuantity
+                );
+                let quantity = order.quantity();
+                ensure!(new_price != price || new_quantity != quantity, Error::<T>::NoOps);
+                if new_price != price {
+                    // Amended order should rest on the orderbook
+                    let pool_price = Self::current_price(&pool_id, pool)
+                        .map_err(|_| Error::<T>::ZeroLiquidity)?;
+                    let is_resting =
+                        if is_bid { new_price < pool_price } else { new_price > pool_price };
+                    ensure!(is_resting, Error::<T>::InvalidOrderPrice);
+                }
+                // Freeze or release only the difference of the collateral
+                let frozen = Self::frozen_amount(pool, is_bid, price, quantity);
+                let to_freeze = Self::frozen_amount(pool, is_bid, new_price, new_quantity);
+                let asset = if is_bid { quote_asset } else { base_asset };
+                if to_freeze > frozen {
+                    Self::freeze_asset(owner, asset, to_freeze - frozen)?;
+                } else if frozen > to_freeze {
+                    Self::unfreeze_asset(owner, asset, frozen - to_freeze)?;
+                }
+                let new_order_id = if new_price == price && new_quantity < quantity {
+                    pool.cancel_order(owner, price, order_id, quantity - new_quantity)
+                        .map_err(|_| Error::<T>::ErrorOnCancelOrder)?;
+                    order_id
+                } else {
+                    // Re-placed order keeps the expiry of the original order
+                    let expired_at = order.expired_at();
+                    pool.remove_order(is_bid, price, order_id)?;
+                    OrderExpiries::<T>::remove(expired_at, (pool_id.clone(), order_id));
+                    Self::take_open_order(owner, &pool_id, order_id)?;
+                    let (_, new_order_id) =
+                        pool.place_order(is_bid, owner, new_price, new_quantity, expired_at)?;
+                    OrderExpiries::<T>::insert(expired_at, (pool_id.clone(), new_order_id), new_price);
+                    Self::insert_open_order(owner, &pool_id, new_order_id, new_price)?;
+                    new_order_id
+                };
+                Self::deposit_event(Event::<T>::OrderAmended {
+                    pool_id: pool_id.clone(),
+                    owner: owner.clone(),
+                    order_id,
+                    new_order_id,
+                    price: new_price,
+                    quantity: new_quantity,
+                    is_bid,
+                });
+                Ok(())
+            })
+        }
+
+        /// Remove the whole remaining quantity of the open order of `order_id` owned by `owner`
+        /// and release its frozen asset.
+        fn do_remove_open_order(
+            owner: &T::AccountId,
+            pool_id: &T::PoolId,
+            pool: &mut Pool<T>,
+            order_id: OrderId,
+        ) -> DispatchResult {
+            let price = Self::take_open_order(owner, pool_id, order_id)?
+                .ok_or(Error::<T>::OrderNotFound)?;
+            let is_bid = order_id.is_bid();
+            let order = pool.remove_order(is_bid, price, order_id)?;
+            ensure!(order.owner() == *owner, Error::<T>::NoPermission);
+            let released = Self::frozen_amount(pool, is_bid, price, order.quantity());
+            let asset = if is_bid { &pool.quote_asset } else { &pool.base_asset };
+            Self::unfreeze_asset(owner, asset, released)?;
+            log::debug!(target: LOG_TARGET, "Release {:?} {:?} for cancelled order", asset, released);
+            OrderExpiries::<T>::remove(order.expired_at(), (pool_id.clone(), order_id));
+            Self::deposit_event(Event::<T>::OrderCancelled {
+                pool_id: pool_id.clone(),
+                owner: owner.clone(),
+                order_id,
+                released,
+            });
+            Ok(())
+        }
+
+        /// Add the open order of `owner` to [`OpenOrders`] and hold [`Config::OrderDeposit`].
+        /// Fails if `owner` already has [`Config::MaxOpenOrders`] open orders.
+        fn insert_open_order(
+            owner: &T::AccountId,
+            pool_id: &T::PoolId,
+            order_id: OrderId,
+            price: T::Unit,
+        ) -> DispatchResult {
+            let deposit = Self::hold_order_deposit(owner)?;
+            OpenOrders::<T>::insert((owner.clone(), pool_id.clone(), order_id), (price, deposit));
+            Ok(())
+        }
+
+        /// Count the order of `owner` towards [`Config::MaxOpenOrders`] and hold
+        /// [`Config::OrderDeposit`] for it. Return the amount of the deposit held.
+        fn hold_order_deposit(owner: &T::AccountId) -> Result<T::Unit, DispatchError> {
+            OpenOrdersCount::<T>::try_mutate(owner, |count| -> DispatchResult {
+                ensure!(*count < T::MaxOpenOrders::get(), Error::<T>::TooManyOpenOrders);
+                *count += 1;
+                Ok(())
+            })?;
+            let deposit = T::OrderDeposit::get();
+            if !deposit.is_zero() {
+                T::Currency::hold(&HoldReason::OrderDeposit.into(), owner, deposit)?;
+            }
+            Ok(deposit)
+        }
+
+        /// Release `deposit` held by `hold_order_deposit` and uncount the order of `owner`.
+        fn release_order_deposit(owner: &T::AccountId, deposit: T::Unit) -> DispatchResult {
+            OpenOrdersCount::<T>::mutate_exists(owner, |maybe_count| {
+                *maybe_count = maybe_count.and_then(|count| count.checked_sub(1)).filter(|c| *c > 0);
+            });
+            if !deposit.is_zero() {
+                T::Currency::release(&HoldReason::OrderDeposit.into(), owner, deposit, Exact)?;
+            }
+            Ok(())
+        }
+
+        /// Remove the open order of `owner` from [`OpenOrders`] and release the deposit held for
+        /// it on `insert_open_order`. Return the price of the order if exists.
+        fn take_open_order(
+            owner: &T::AccountId,
+            pool_id: &T::PoolId,
+            order_id: OrderId,
+        ) -> Result<Option<T::Unit>, DispatchError> {
+            let Some((price, deposit)) =
+                OpenOrders::<T>::take((owner.clone(), pool_id.clone(), order_id))
+            else {
+                return Ok(None)
+            };
+            Self::release_order_deposit(owner, deposit)?;
+            Ok(Some(price))
+        }
+
+        /// Place the stop limit order on the trigger book of the pool, freeze its collateral and
+        /// hold [`Config::OrderDeposit`]. Return the number of the orders on the trigger book
+        /// before placed.
+        ///
+        /// No-ops if
+        ///
+        /// - `stop_price` or `limit_price` is not multiple of `tick_size`
+        /// - `quantity` is not multiple of `lot_size`
+        /// - order would be triggered immediately with the current pool price
+        /// - trigger book of the pool already has [`Config::MaxStopOrders`] orders
+        pub(crate) fn do_stop_limit_order(
+            owner: &T::AccountId,
+            base_asset: &T::AssetKind,
+            quote_asset: &T::AssetKind,
+            is_bid: bool,
+            stop_price: T::Unit,
+            limit_price: T::Unit,
+            quantity: T::Unit,
+        ) -> Result<u32, DispatchError> {
+            let pool_id = T::PoolLocator::pool_id(base_asset, quote_asset)
+                .map_err(|_| Error::<T>::InvalidAssetPair)?;
+            let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+            ensure!(
+                pool.is_valid_order_price(stop_price) && pool.is_valid_order_price(limit_price),
+                Error::<T>::InvalidOrderPrice
+            );
+            ensure!(pool.is_valid_order_quantity(quantity), Error::<T>::InvalidOrderQuantity);
+            let pool_price =
+                Self::current_price(&pool_id, &pool).map_err(|_| Error::<T>::ZeroLiquidity)?;
+            let is_valid_stop_price = if is_bid {
+                stop_price > pool_price
+            } else {
+                stop_price < pool_price
+            };
+            ensure!(is_valid_stop_price, Error::<T>::InvalidStopPrice);
+            let freeze_amount = Self::frozen_amount(&pool, is_bid, limit_price, quantity);
+            let asset = if is_bid { quote_asset } else { base_asset };
+            Self::freeze_asset(owner, asset, freeze_amount)?;
+            let deposit = Self::hold_order_deposit(owner)?;
+            let (stop_order_id, stop_orders) =
+                TriggerBooks::<T>::try_mutate(&pool_id, |book| -> Result<_, DispatchError> {
+                    let stop_orders = book.len();
+                    let stop_order_id = book
+                        .place(is_bid, stop_price)
+                        .map_err(|_| Error::<T>::TooManyStopOrders)?;
+                    Ok((stop_order_id, stop_orders))
+                })?;
+            StopOrders::<T>::insert(
+                &pool_id,
+                stop_order_id,
+                StopLimitOrder {
+                    owner: owner.clone(),
+                    is_bid,
+                    stop_price,
+                    limit_price,
+                    quantity,
+                    deposit,
+                },
+            );
+            Self::deposit_event(Event::<T>::StopLimitOrderPlaced {
+                pool_id,
+                owner: owner.clone(),
+                stop_order_id,
+                stop_price,
+                limit_price,
+                quantity,
+                is_bid,
+            });
+            Ok(stop_orders)
+        }
+
+        /// Cancel the stop limit order of `stop_order_id` owned by `owner`. Return the number of
+        /// the orders on the trigger book before cancelled.
+        pub(crate) fn do_cancel_stop_limit_order(
+            owner: &T::AccountId,
+            base_asset: &T::AssetKind,
+            quote_asset: &T::AssetKind,
+            stop_order_id: StopOrderId,
+        ) -> Result<u32, DispatchError> {
+            let pool_id = T::PoolLocator::pool_id(base_asset, quote_asset)
+                .map_err(|_| Error::<T>::InvalidAssetPair)?;
+            let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+            let order = StopOrders::<T>::take(&pool_id, stop_order_id)
+                .ok_or(Error::<T>::StopOrderNotFound)?;
+            ensure!(order.owner == *owner, Error::<T>::NoPermission);
+            let stop_orders = TriggerBooks::<T>::mutate(&pool_id, |book| {
+                let stop_orders = book.len();
+                book.remove(order.is_bid, order.stop_price, stop_order_id);
+                stop_orders
+            });
+            let released = Self::release_stop_order(&pool, &order)?;
+            Self::deposit_event(Event::<T>::StopLimitOrderCancelled {
+                pool_id,
+                owner: owner.clone(),
+                stop_order_id,
+                released,
+            });
+            Ok(stop_orders)
+        }
+
+        /// Release the frozen collateral and the deposit of the stop limit `order` which has been
+        /// removed from the trigger book. Return the amount of the collateral released.
+        fn release_stop_order(
+            pool: &Pool<T>,
+            order: &StopLimitOrderOf<T>,
+        ) -> Result<T::Unit, DispatchError> {
+            let StopLimitOrder { owner, is_bid, limit_price, quantity, deposit, .. } = order;
+            let released = Self::frozen_amount(pool, *is_bid, *limit_price, *quantity);
+            let asset = if *is_bid { &pool.quote_asset } else { &pool.base_asset };
+            Self::unfreeze_asset(owner, asset, released)?;
+            Self::release_order_deposit(owner, *deposit)?;
+            Ok(released)
+        }
+
+        /// Trigger the stop limit orders of each pool along the swap `path`. Return the weight of
+        /// the orders triggered.
+        fn do_trigger_stop_orders_along(path: &Vec<T::AssetKind>) -> Result<Weight, DispatchError> {
+            let mut weight = Weight::zero();
+            for assets in path.windows(2) {
+                let pool_id = Self::get_pool_id(&assets[0], &assets[1])?;
+                let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+                weight.saturating_accrue(Self::do_trigger_stop_orders(
+                    &pool.base_asset,
+                    &pool.quote_asset,
+                )?);
+            }
+            Ok(weight)
+        }
+
+        /// Trigger the stop limit orders whose stop price has been reached by the current pool
+        /// price, up to [`Config::MaxTriggeredStopOrders`]. Triggered order is converted to the
+        /// limit order. Failure of releasing the order or of the limit order is reported on the
+        /// event and doesn't revert the caller.
+        ///
+        /// Should be called after the pool price is changed, outside of mutating [`Pools`]. Return
+        /// the weight of the orders triggered, which is bounded by
+        /// [`Pallet::max_trigger_stop_orders_weight`].
+        pub(crate) fn do_trigger_stop_orders(
+            base_asset: &T::AssetKind,
+            quote_asset: &T::AssetKind,
+        ) -> Result<Weight, DispatchError> {
+            let pool_id = T::PoolLocator::pool_id(base_asset, quote_asset)
+                .map_err(|_| Error::<T>::InvalidAssetPair)?;
+            let mut weight = Weight::zero();
+            let mut book = TriggerBooks::<T>::get(&pool_id);
+            if book.is_empty() {
+                return Ok(weight)
+            }
+            let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+            let mut triggered = 0u32;
+            while triggered < T::MaxTriggeredStopOrders::get() {
+                // Price moves by each triggered order
+                let Ok(pool_price) = Self::current_price(&pool_id, &pool) else { break };
+                let stop_orders = book.len();
+                let Some(stop_order_id) = book.pop_triggered(pool_price) else { break };
+                triggered += 1;
+                weight.saturating_accrue(T::WeightInfo::cancel_stop_limit_order(stop_orders));
+                let Some(order) = StopOrders::<T>::take(&pool_id, stop_order_id) else { continue };
+                // Release the collateral which will be frozen again by the limit order
+                let result = with_storage_layer(|| Self::release_stop_order(&pool, &order))
+                    .and_then(|_| {
+                        with_storage_layer(|| {
+                            Self::do_limit_order(
+                                order.owner.clone(),
+                                order.limit_price,
+                                order.quantity,
+                                order.is_bid,
+                                base_asset,
+                                quote_asset,
+                                TimeInForce::GoodTilCancel,
+                            )
+                        })
+                    });
+                // Work done by the failed limit order is not known, so charged as the worst case
+                let matched = match &result {
+                    Ok(matched) => *matched,
+                    Err(_) => Self::max_match_count(),
+                };
+                weight
+                    .saturating_accrue(Self::matched_weight(T::WeightInfo::limit_order, &matched));
+                let result = result.map(|_| ());
+                log::debug!(target: LOG_TARGET, "Stop limit order {:?} triggered at {:?}: {:?}", stop_order_id, pool_price, result);
+                Self::deposit_event(Event::<T>::StopLimitOrderTriggered {
+                    pool_id: pool_id.clone(),
+                    owner: order.owner,
+                    stop_order_id,
+                    stop_price: order.stop_price,
+                    is_bid: order.is_bid,
+                    result,
+                });
+            }
+            if triggered > 0 {
+                TriggerBooks::<T>::insert(&pool_id, book);
+            }
+            Ok(weight)
         }
 
-        pub(crate) fn do_place_order(
-            is_bid: bool,
+        /// Remove the expired order of `order_id` placed at `price` from the `pool` and release
+        /// its frozen asset.
+        pub(crate) fn do_expire_order(
             pool_id: &T::PoolId,
             pool: &mut Pool<T>,
-            maker: &T::AccountId,
-            base_asset: &T::AssetKind,
-            quote_asset: &T::AssetKind,
             price: T::Unit,
-            quantity: T::Unit,
+            order_id: OrderId,
         ) -> DispatchResult {
-            let (p_adj, _, q_adj) = pool.decimal_adjustment();
-            if is_bid {
-                let freeze_amount = (price*quantity).denom(
-                    p_adj.unwrap_or(0) + q_adj.unwrap_or(0)
-                );
-                Self::freeze_asset(maker, quote_asset, freeze_amount)?;
-                log::debug!(target: LOG_TARGET, "Freeze {:?} {:?} for bid order", quote_asset, freeze_amount);
-            } else {
-                Self::freeze_asset(maker, base_asset, quantity)?;
-                log::debug!(target: LOG_TARGET, "Freeze {:?} {:?} for ask order", base_asset, quantity);
-            }
-            let (price, order_id) = pool
-                .place_order(is_bid, maker, price, quantity)
-                .map_err(|_| Error::<T>::ErrorOnPlaceOrder)?;
-            Self::deposit_event(Event::<T>::LimitOrderPlaced {
-                maker: maker.clone(),
-                order_price: price,
+            let is_bid = order_id.is_bid();
+            let order = pool.remove_order(is_bid, price, order_id)?;
+            let owner = order.owner();
+            let released = Self::frozen_amount(pool, is_bid, price, order.quantity());
+            let asset = if is_bid { &pool.quote_asset } else { &pool.base_asset };
+            Self::unfreeze_asset(&owner, asset, released)?;
+            log::debug!(target: LOG_TARGET, "Release {:?} {:?} for expired order", asset, released);
+            OrderExpiries::<T>::remove(order.expired_at(), (pool_id.clone(), order_id));
+            Self::take_open_order(&owner, pool_id, order_id)?;
+            Self::deposit_event(Event::<T>::OrderExpired {
                 pool_id: pool_id.clone(),
+                owner,
                 order_id,
-                order_quantity: quantity,
-                is_bid,
+                released,
             });
             Ok(())
         }
 
-        pub(crate) fn do_cancel_order(
-            owner: &T::AccountId,
-            base_asset: &T::AssetKind,
-            quote_asset: &T::AssetKind,
+        /// Remove all orders placed at `price` which are expired at `now`. Return the number of
+        /// orders removed.
+        fn do_expire_orders_at(
+            pool_id: &T::PoolId,
+            pool: &mut Pool<T>,
+            is_bid: bool,
             price: T::Unit,
-            order_id: OrderId,
-            quantity: T::Unit,
-        ) -> DispatchResult {
-            let pool_id = T::PoolLocator::pool_id(base_asset, quote_asset)
-                .map_err(|_| Error::<T>::InvalidAssetPair)?;
-            Pools::<T>::try_mutate(pool_id.clone(), |pool| -> DispatchResult {
-                let mut updated = pool.take().ok_or(Error::<T>::PoolNotFound)?;
-                updated
-                    .cancel_order(owner, price, order_id, quantity)
-                    .map_err(|_| Error::<T>::ErrorOnCancelOrder)?;
-                *pool = Some(updated);
-                Self::deposit_event(Event::<T>::OrderCancelled {
-                    pool_id,
-                    owner: owner.clone(),
-                    order_id,
-                });
-                Ok(())
-            })
+            now: BlockNumberFor<T>,
+        ) -> Result<u32, DispatchError> {
+            let expired = pool.expired_orders_at(is_bid, price, &now);
+            let count = expired.len() as u32;
+            for (order_id, _) in expired {
+                Self::do_expire_order(pool_id, pool, price, order_id)?;
+            }
+            Ok(count)
         }
 
-        pub(crate) fn _do_stop_limit_order(_owner: T::AccountId, _pool: Pool<T>) -> DispatchResult {
-            Ok(())
+        /// Sweep the orders expired at or before `now`, bounded by `remaining_weight` and
+        /// [`Config::MaxExpiredOrdersPerBlock`]. Return the consumed weight.
+        pub(crate) fn do_sweep_expired_orders(
+            now: BlockNumberFor<T>,
+            remaining_weight: Weight,
+        ) -> Weight {
+            let db_weight = T::DbWeight::get();
+            // Read and write of `ExpirySweepCursor`
+            let mut consumed = db_weight.reads_writes(1, 1);
+            if consumed.any_gt(remaining_weight) {
+                return Weight::zero();
+            }
+            let weight_per_order = T::WeightInfo::expire_order();
+            let mut budget = T::MaxExpiredOrdersPerBlock::get();
+            let mut cursor = ExpirySweepCursor::<T>::get().unwrap_or(now);
+            while cursor <= now && budget > 0 {
+                // Read of the index for `cursor`
+                consumed.saturating_accrue(db_weight.reads(1));
+                let affordable = remaining_weight
+                    .saturating_sub(consumed)
+                    .checked_div_per_component(&weight_per_order)
+                    .unwrap_or(u64::MAX);
+                let limit = affordable.min(budget as u64) as u32;
+                if limit == 0 {
+                    break;
+                }
+                // Take one more to check whether all orders expired at `cursor` could be swept
+                let mut expired = OrderExpiries::<T>::iter_prefix(cursor)
+                    .take(limit as usize + 1)
+                    .collect::<Vec<_>>();
+                let is_exhausted = expired.len() <= limit as usize;
+                expired.truncate(limit as usize);
+                let swept = expired.len() as u32;
+                for ((pool_id, order_id), price) in expired {
+                    OrderExpiries::<T>::remove(cursor, (pool_id.clone(), order_id));
+                    // Nodes of the orderbook are written on their own, so revert them on failure
+                    let res = with_storage_layer(|| {
+                        Pools::<T>::try_mutate(&pool_id, |maybe_pool| -> DispatchResult {
+                            let pool = maybe_pool.as_mut().ok_or(Error::<T>::PoolNotFound)?;
+                            Self::do_expire_order(&pool_id, pool, price, order_id)
+                        })
+                    });
+                    if let Err(e) = res {
+                        // Order has already been filled or cancelled
+                        log::debug!(target: LOG_TARGET, "Skip expired order {:?}: {:?}", order_id, e);
+                    }
+                    consumed.saturating_accrue(weight_per_order);
+                }
+                budget -= swept;
+                if is_exhausted {
+                    // All orders expired at `cursor` have been swept
+                    cursor = cursor.saturating_add(One::one());
+                }
+            }
+            ExpirySweepCursor::<T>::put(cursor);
+            consumed
         }
 
         /// Match `order_quantity` of orders from pool. Matching will take turns between `Orderbook`
-        /// and `Liquidity Pool`
+        /// and `Liquidity Pool`. If `limit_price` is given, orders are matched only up to(bid) or
+        /// down to(ask) the `limit_price` on both of them.
+        ///
+        /// Return (Unit, Unit, MatchCount) which means the quantity of orders which could not be
+        /// filled, the amount of quote asset paid(bid) or received(ask) including the taker fee and
+        /// the work done on the orderbook.
         pub(crate) fn do_match_order(
             is_bid: bool,
             orderer: &T::AccountId,
@@ -1165,8 +3407,16 @@ pub mod pallet {
             base_asset: &T::AssetKind,
             quote_asset: &T::AssetKind,
             order_quantity: T::Unit,
-        ) -> Result<(), DispatchError> {
+            limit_price: Option<T::Unit>,
+        ) -> Result<(T::Unit, T::Unit, MatchCount), DispatchError> {
+            let pool_id = T::PoolLocator::pool_id(base_asset, quote_asset)
+                .map_err(|_| Error::<T>::InvalidAssetPair)?;
+            let now = frame_system::Pallet::<T>::block_number();
             let mut remain_orders = order_quantity;
+            let mut matched =
+                MatchCount { concentrated: pool.is_concentrated(), ..Default::default() };
+            // Amount of quote asset paid or received from the pool and orderbook
+            let mut quote_amount: T::Unit = Zero::zero();
             // (Account, Price, Quantity)
             let mut filled_orders: Vec<(T::AccountId, T::Unit, T::Unit)> = Default::default();
             // Match orders from pool and orderbook until all orders are filled.
@@ -1183,6 +3433,21 @@ pub mod pallet {
                 };
                 match maybe_next_order {
                     Some((target_price, _)) => {
+                        // Expired orders should not be matched. Look for the next order again
+                        // once they are removed.
+                        let expired =
+                            Self::do_expire_orders_at(&pool_id, pool, !is_bid, target_price, now)?;
+                        if expired > 0 {
+                            Self::accrue_match_count(&mut matched, expired)?;
+                            continue;
+                        }
+                        // End loop, if the next order is beyond the limit price
+                        let is_beyond_limit = limit_price.map_or(false, |limit| {
+                            if is_bid { target_price > limit } else { target_price < limit }
+                        });
+                        if is_beyond_limit {
+                            break;
+                        }
                         let max_swap_quantity = Self::find_max_swap_quantity(
                             is_bid,
                             target_price,
@@ -1202,9 +3467,11 @@ pub mod pallet {
                         );
                         if remain_orders <= max_swap_quantity {
                             // All orders filled from pool
-                            Self::do_fill_pool(
+                            quote_amount += Self::do_fill_pool(
                                 is_bid,
                                 orderer,
+                                &pool_id,
+                                pool,
                                 remain_orders,
                                 base_asset,
                                 quote_asset,
@@ -1216,9 +3483,11 @@ pub mod pallet {
                             if is_pool {
                                 log::info!("💦 Filled {:?} orders from Pool", max_swap_quantity);
                                 // Swap up to `max_swap_quantity` from pool
-                                Self::do_fill_pool(
+                                quote_amount += Self::do_fill_pool(
                                     is_bid,
                                     orderer,
+                                    &pool_id,
+                                    pool,
                                     max_swap_quantity,
                                     base_asset,
                                     quote_asset,
@@ -1227,24 +3496,53 @@ pub mod pallet {
                             // Remain orders subsume the closest will be filled from Orderbook
                             remain_orders -= max_swap_quantity;
                             log::info!("📖 Filled {:?} orders from Book", remain_orders);
-                            Self::do_fill_book(
+                            let filled_count = Self::do_fill_book(
                                 is_bid,
+                                orderer,
+                                &pool_id,
                                 pool,
                                 target_price,
                                 &mut remain_orders,
                                 &mut filled_orders,
                             )?;
+                            Self::accrue_match_count(&mut matched, filled_count)?;
                         }
                     }
                     // End loop, if there are no orders on Orderbook
                     None => break,
                 }
             }
-            // Fill remain orders from pool if any(e.g no orders on OrderBook)
+            // Fill remain orders from pool if any(e.g no orders on OrderBook). Pool is swapped only
+            // up to the limit price, if any.
             if remain_orders > Zero::zero() {
-                Self::do_fill_pool(is_bid, orderer, remain_orders, base_asset, quote_asset)?;
+                let pool_quantity = match limit_price {
+                    Some(limit) => Self::find_max_swap_quantity(
+                        is_bid,
+                        limit,
+                        &pool,
+                        base_asset,
+                        quote_asset,
+                        remain_orders,
+                    )?,
+                    None => remain_orders,
+                };
+                if pool_quantity > Zero::zero() {
+                    quote_amount += Self::do_fill_pool(
+                        is_bid,
+                        orderer,
+                        &pool_id,
+                        pool,
+                        pool_quantity,
+                        base_asset,
+                        quote_asset,
+                    )?;
+                    remain_orders -= pool_quantity;
+                }
             }
-            Self::handle_filled_orders(
+            for (_, price, quantity) in filled_orders.iter() {
+                quote_amount += Self::frozen_amount(pool, true, *price, *quantity);
+            }
+            let fee = Self::handle_filled_orders(
                 is_bid,
                 &pool,
                 base_asset.clone(),
@@ -1254,21 +3552,33 @@ pub mod pallet {
             )?;
             Self::deposit_event(Event::<T>::OrderMatched {
                 orderer: orderer.clone(),
-                filled: order_quantity,
+                filled: order_quantity - remain_orders,
                 is_bid,
+                fee,
             });
 
-            Ok(())
+            // Taker fee is paid on top of(bid) or deducted from(ask) the quote amount
+            let quote_amount = if is_bid {
+                quote_amount.saturating_add(fee)
+            } else {
+                quote_amount.saturating_sub(fee)
+            };
+
+            Ok((remain_orders, quote_amount, matched))
         }
 
+        /// Fill `quantity` of base asset from the pool. Return the amount of quote asset
+        /// paid(bid) or received(ask).
         fn do_fill_pool(
             is_bid: bool,
             orderer: &T::AccountId,
+            pool_id: &T::PoolId,
+            pool: &Pool<T>,
             quantity: T::Unit,
             base_asset: &T::AssetKind,
             quote_asset: &T::AssetKind,
-        ) -> Result<(), DispatchError> {
-            if is_bid {
+        ) -> Result<T::Unit, DispatchError> {
+            let quote_amount = if is_bid {
                 Self::do_swap_tokens_for_exact_tokens(
                     orderer,
                     vec![quote_asset.clone(), base_asset.clone()],
@@ -1276,7 +3586,7 @@ pub mod pallet {
                     None,
                     orderer,
                     false,
-                )?;
+                )?
             } else {
                 Self::do_swap_exact_tokens_for_tokens(
                     orderer,
@@ -1285,32 +3595,246 @@ pub mod pallet {
                     None,
                     orderer,
                     false,
-                )?;
+                )?
+            };
+            Self::record_pool_trade(pool_id, pool, orderer, is_bid, quantity, quote_amount)?;
+            Ok(quote_amount)
+        }
+
+        /// Record the trade of `quantity` of base asset exchanged with `quote_amount` of quote
+        /// asset on the pool
+        fn record_pool_trade(
+            pool_id: &T::PoolId,
+            pool: &Pool<T>,
+            taker: &T::AccountId,
+            is_bid: bool,
+            quantity: T::Unit,
+            quote_amount: T::Unit,
+        ) -> DispatchResult {
+            let pool_account =
+                T::PoolLocator::address(pool_id).map_err(|_| Error::<T>::InvalidAssetPair)?;
+            Self::record_trade(
+                pool_id,
+                TradeRecord {
+                    maker: pool_account,
+                    taker: taker.clone(),
+                    order_id: None,
+                    is_bid,
+                    price: Self::execution_price(pool, quantity, quote_amount),
+                    quantity,
+                    venue: Venue::Pool,
+                    fee: Zero::zero(),
+                    block: frame_system::Pallet::<T>::block_number(),
+                },
+            );
+            Ok(())
+        }
+
+        /// Emit [`Event::Trade`] and push the trade to [`RecentTrades`] of the pool, which
+        /// overwrites the oldest one once [`Config::MaxRecentTrades`] trades are stored.
+        fn record_trade(pool_id: &T::PoolId, trade: TradeRecordOf<T>) {
+            let count = TradeCount::<T>::get(pool_id);
+            let slot = count % T::MaxRecentTrades::get().max(1) as u64;
+            Self::deposit_event(Event::<T>::Trade {
+                pool_id: pool_id.clone(),
+                maker: trade.maker.clone(),
+                taker: trade.taker.clone(),
+                order_id: trade.order_id,
+                is_bid: trade.is_bid,
+                price: trade.price,
+                quantity: trade.quantity,
+                venue: trade.venue,
+                fee: trade.fee,
+            });
+            Self::update_candles(pool_id, &trade);
+            RecentTrades::<T>::insert(pool_id, slot as u32, trade);
+            TradeCount::<T>::insert(pool_id, count.saturating_add(1));
+        }
+
+        /// Observe the cumulative price of the pool of `asset1` and `asset2` before its reserves
+        /// change, once per block. Pool price before the first change of the block is the price
+        /// since the last observation, so the price moved within the block is accumulated only
+        /// from the next block. Nothing is observed until the liquidity is provided.
+        fn observe_price(asset1: &T::AssetKind, asset2: &T::AssetKind) -> DispatchResult {
+            let pool_id = Self::get_pool_id(asset1, asset2)?;
+            let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+            let now = frame_system::Pallet::<T>::block_number();
+            let latest = Self::latest_price_observation(&pool_id);
+            if latest.as_ref().map_or(false, |observation| observation.block == now) {
+                return Ok(());
             }
+            let Ok(pool_price) = Self::current_price(&pool_id, &pool) else {
+                return Ok(());
+            };
+            let price_cumulative = match latest {
+                Some(observation) => Self::accumulate_price(&observation, pool_price, now),
+                None => Zero::zero(),
+            };
+            let count = PriceObservationCount::<T>::get(&pool_id);
+            let slot = count % T::MaxPriceObservations::get().max(1) as u64;
+            PriceObservations::<T>::insert(
+                &pool_id,
+                slot as u32,
+                PriceObservation { block: now, price_cumulative },
+            );
+            PriceObservationCount::<T>::insert(&pool_id, count.saturating_add(1));
             Ok(())
         }
 
+        /// `price` scaled by `pool_decimals` as a fixed point number
+        fn normalize_price(pool: &Pool<T>, price: T::Unit) -> Result<FixedU128, Error<T>> {
+            let scale = 10u128
+                .checked_pow(pool.pool_decimals.into())
+                .ok_or(Error::<T>::Overflow)?;
+            FixedU128::checked_from_rational(price.saturated_into::<u128>(), scale)
+                .ok_or(Error::<T>::Overflow)
+        }
+
+        fn last_trade_block(pool_id: &T::PoolId) -> Option<BlockNumberFor<T>> {
+            let count = TradeCount::<T>::get(pool_id).checked_sub(1)?;
+            let slot = count % T::MaxRecentTrades::get().max(1) as u64;
+            RecentTrades::<T>::get(pool_id, slot as u32).map(|trade| trade.block)
+        }
+
+        fn latest_price_observation(pool_id: &T::PoolId) -> Option<PriceObservationOf<T>> {
+            let count = PriceObservationCount::<T>::get(pool_id).checked_sub(1)?;
+            let slot = count % T::MaxPriceObservations::get().max(1) as u64;
+            PriceObservations::<T>::get(pool_id, slot as u32)
+        }
+
+        /// Cumulative price of `now` given `pool_price` has lasted since `observation`
+        fn accumulate_price(
+            observation: &PriceObservationOf<T>,
+            pool_price: T::Unit,
+            now: BlockNumberFor<T>,
+        ) -> T::Unit {
+            Self::wrapping_add(
+                observation.price_cumulative,
+                pool_price.saturating_mul(Self::blocks(now - observation.block)),
+            )
+        }
+
+        /// `a + b` wrapped around the bound of `T::Unit`, so that the cumulative price never gets
+        /// stuck. Difference of two cumulative prices is taken with [`Self::wrapping_sub`].
+        fn wrapping_add(a: T::Unit, b: T::Unit) -> T::Unit {
+            a.checked_add(&b).unwrap_or_else(|| b - (T::Unit::max_value() - a) - One::one())
+        }
+
+        /// `a - b` wrapped around the bound of `T::Unit`
+        fn wrapping_sub(a: T::Unit, b: T::Unit) -> T::Unit {
+            a.checked_sub(&b).unwrap_or_else(|| T::Unit::max_value() - (b - a) + One::one())
+        }
+
+        fn blocks(blocks: BlockNumberFor<T>) -> T::Unit {
+            blocks.saturated_into::<u32>().into()
+        }
+
+        /// Update the candle of the current bucket for each of [`Config::CandleResolutions`] with
+        /// the execution price and quantity of `trade`. A new candle is opened on the slot of the
+        /// bucket if the stored one is of the older bucket.
+        fn update_candles(pool_id: &T::PoolId, trade: &TradeRecordOf<T>) {
+            let block = trade.block.saturated_into::<u32>();
+            let max_candles = T::MaxCandles::get().max(1);
+            for resolution in T::CandleResolutions::get() {
+                if resolution.is_zero() {
+                    continue;
+                }
+                let bucket = block / resolution;
+                let start = BlockNumberFor::<T>::from(bucket * resolution);
+                Candles::<T>::mutate((pool_id, resolution, bucket % max_candles), |candle| {
+                    match candle {
+                        Some(current) if current.start == start => {
+                            current.update(trade.price, trade.quantity)
+                        }
+                        _ => *candle = Some(Candle::new(start, trade.price, trade.quantity)),
+                    }
+                });
+            }
+        }
+
+        /// Price of `quantity` of base asset exchanged with `quote_amount` of quote asset, scaled
+        /// by `pool_decimals`. Inverse of [`Self::frozen_amount`] of bid.
+        fn execution_price(pool: &Pool<T>, quantity: T::Unit, quote_amount: T::Unit) -> T::Unit {
+            if quantity.is_zero() {
+                return Zero::zero();
+            }
+            let (p_adj, _, q_adj) = pool.decimal_adjustment();
+            quote_amount.normalize(Some(p_adj.unwrap_or(0) + q_adj.unwrap_or(0))) / quantity
+        }
+
         fn do_fill_book(
             is_bid: bool,
+            taker: &T::AccountId,
+            pool_id: &T::PoolId,
             pool: &mut Pool<T>,
             price: T::Unit,
             order_quantity: &mut T::Unit,
             filled_orders: &mut Vec<(T::AccountId, T::Unit, T::Unit)>,
-        ) -> Result<(), Error<T>> {
+        ) -> Result<u32, DispatchError> {
             let q = order_quantity.clone();
-            let filled = pool
+            let filled_before = filled_orders.len();
+            let now = frame_system::Pallet::<T>::block_number();
+            let mut filled: T::Unit = Zero::zero();
+            let maybe_filled = pool
                 .fill_order(is_bid, price, q)
-                .map_err(|_| Error::<T>::ErrorOnFillOrder)?
-                .map_or(Zero::zero(), |filled| {
-                    filled.iter().fold(Zero::zero(), |acc, order| {
-                        let filled_order = order.clone();
-                        filled_orders.push((filled_order.0, price, filled_order.1));
-                        acc + order.1
-                    })
-                });
+                .map_err(|_| Error::<T>::ErrorOnFillOrder)?;
+            for (order_id, owner, quantity, remain) in maybe_filled.unwrap_or_default() {
+                if remain.is_zero() {
+                    Self::take_open_order(&owner, pool_id, order_id)?;
+                }
+                // Same taker fee charged for the order on `handle_filled_orders`
+                let fee = pool.taker_fee(Self::frozen_amount(pool, true, price, quantity));
+                Self::record_trade(
+                    pool_id,
+                    TradeRecord {
+                        maker: owner.clone(),
+                        taker: taker.clone(),
+                        order_id: Some(order_id),
+                        is_bid,
+                        price,
+                        quantity,
+                        venue: Venue::Book,
+                        fee,
+                        block: now,
+                    },
+                );
+                filled_orders.push((owner, price, quantity));
+                filled += quantity;
+            }
             *order_quantity -= filled;
             log::debug!(target: LOG_TARGET, "🤑 {:?} of orders filled from book for {:?}. {:?} orders left", filled, price, order_quantity);
-            Ok(())
+            Ok((filled_orders.len() - filled_before) as u32)
+        }
+
+        /// Current price of the pool scaled by `pool_decimals`. Price of the pool with
+        /// [`LiquidityMode::Concentrated`] is tracked by its liquidity, while the others are priced
+        /// by [`Self::pool_price`] of their reserves.
+        pub fn current_price(pool_id: &T::PoolId, pool: &Pool<T>) -> Result<T::Unit, Error<T>> {
+            if pool.is_concentrated() {
+                let liquidity =
+                    ConcentratedLiquidities::<T>::get(pool_id).ok_or(Error::<T>::PoolNotFound)?;
+                return pool.price_of_sqrt(liquidity.sqrt_price);
+            }
+            let (b_r, q_r) = Self::get_reserves(&pool.base_asset, &pool.quote_asset)?;
+            Self::pool_price(pool, &b_r, &q_r)
+        }
+
+        /// Balances of base and quote asset of the pool. Unlike [`Self::get_reserves`], the pool
+        /// with [`LiquidityMode::Concentrated`] is not required to hold both assets, and its
+        /// balances include the fee owed to the positions.
+        fn pool_balances(
+            pool_id: &T::PoolId,
+            pool: &Pool<T>,
+        ) -> Result<(T::Unit, T::Unit), Error<T>> {
+            if !pool.is_concentrated() {
+                return Self::get_reserves(&pool.base_asset, &pool.quote_asset);
+            }
+            let pool_account =
+                T::PoolLocator::address(pool_id).map_err(|_| Error::<T>::InvalidAssetPair)?;
+            Ok((
+                Self::get_balance(&pool_account, &pool.base_asset),
+                Self::get_balance(&pool_account, &pool.quote_asset),
+            ))
         }
 
         /// Current price of the pool based on base and quote reserves
@@ -1349,7 +3873,48 @@ pub mod pallet {
             quote_asset: &T::AssetKind,
             remain_orders: T::Unit,
         ) -> Result<T::Unit, Error<T>> {
+            if pool.is_concentrated() {
+                let pool_id = T::PoolLocator::pool_id(base_asset, quote_asset)
+                    .map_err(|_| Error::<T>::InvalidAssetPair)?;
+                let liquidity =
+                    ConcentratedLiquidities::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+                return Self::find_max_swap_quantity_concentrated(
+                    is_bid,
+                    target,
+                    pool,
+                    liquidity,
+                    remain_orders,
+                );
+            }
             let (b_r, q_r) = Self::get_reserves(base_asset, quote_asset)?;
+            Self::find_max_swap_quantity_with_reserves(is_bid, target, pool, b_r, q_r, remain_orders)
+        }
+
+        /// Same as `find_max_swap_quantity` but for the given concentrated liquidity of the pool.
+        /// Rather than searching, the swap is simulated up to the target price, crossing the
+        /// boundaries of the ranges on the way.
+        fn find_max_swap_quantity_concentrated(
+            is_bid: bool,
+            target: T::Unit,
+            pool: &Pool<T>,
+            mut liquidity: ConcentratedLiquidity<T>,
+            remain_orders: T::Unit,
+        ) -> Result<T::Unit, Error<T>> {
+            let limit = pool.sqrt_price(target)?;
+            // Bid takes base asset out of the pool, which moves the price up
+            let swapped = liquidity.swap(!is_bid, !is_bid, remain_orders, Some(limit))?;
+            Ok(if is_bid { swapped.amount_out } else { swapped.amount_in })
+        }
+
+        /// Same as `find_max_swap_quantity` but for the given base and quote reserves of the pool
+        fn find_max_swap_quantity_with_reserves(
+            is_bid: bool,
+            target: T::Unit,
+            pool: &Pool<T>,
+            b_r: T::Unit,
+            q_r: T::Unit,
+            remain_orders: T::Unit,
+        ) -> Result<T::Unit, Error<T>> {
             let (p_adj, b_adj, q_adj) = pool.decimal_adjustment();
             let mut min: T::Unit = Zero::zero();
             let mut max: T::Unit = remain_orders;
@@ -1450,7 +4015,7 @@ pub mod pallet {
                 );
             }
 
-            Self::swap(false, &sender, &path, &send_to, keep_alive)?;
+            Self::swap(&sender, &path, &send_to, keep_alive)?;
 
             Self::deposit_event(Event::SwapExecuted {
                 who: sender.clone(),
@@ -1486,14 +4051,8 @@ pub mod pallet {
             if let Some(amount_in_max) = amount_in_max {
                 ensure!(amount_in_max > Zero::zero(), Error::<T>::ZeroAmount);
             }
-            if_std! {
-             println!("{:?}", path);
-            }
             Self::validate_swap_path(&path)?;
             let path = Self::balance_path_from_amount_out(amount_out, path)?;
-            if_std! {
-             println!("{:?}", path);
-            }
             let amount_in = path
                 .first()
                 .map(|(_, a)| *a)
@@ -1505,7 +4064,7 @@ pub mod pallet {
                 );
             }
 
-            Self::swap(true, sender, &path, send_to, keep_alive)?;
+            Self::swap(sender, &path, send_to, keep_alive)?;
 
             Self::deposit_event(Event::SwapExecuted {
                 who: sender.clone(),
@@ -1563,7 +4122,7 @@ pub mod pallet {
                 Err(e) => return Err((credit_in, e)),
             };
 
-            let credit_out = Self::credit_swap(true, credit_in, &path)?;
+            let credit_out = Self::credit_swap(credit_in, &path)?;
 
             Self::deposit_event(Event::SwapCreditExecuted {
                 amount_in,
@@ -1620,7 +4179,7 @@ pub mod pallet {
             };
 
             let (credit_in, credit_change) = credit_in.split(amount_in);
-            let credit_out = Self::credit_swap(false, credit_in, &path)?;
+            let credit_out = Self::credit_swap(credit_in, &path)?;
 
             Self::deposit_event(Event::SwapCreditExecuted {
                 amount_in,
@@ -1639,21 +4198,14 @@ pub mod pallet {
         /// only inside a transactional storage context and an Err result must imply a storage
         /// rollback.
         fn swap(
-            is_bid: bool,
             sender: &T::AccountId,
             path: &BalancePath<T>,
             send_to: &T::AccountId,
             keep_alive: bool,
         ) -> Result<(), DispatchError> {
             let (asset_in, amount_in) = path.first().ok_or(Error::<T>::InvalidPath)?;
-            if_std! {
-             println!("Swap => asset_in: {:?}, amount_in: {:?}", asset_in, amount_in);
-            }
             let credit_in = Self::withdraw(asset_in.clone(), sender, *amount_in, keep_alive)?;
-            if_std! {
-             println!("credit-in => credig_in: {:?}", credit_in);
-            }
-            let credit_out = Self::credit_swap(is_bid, credit_in, path).map_err(|(_, e)| e)?;
+            let credit_out = Self::credit_swap(credit_in, path).map_err(|(_, e)| e)?;
             T::Assets::resolve(send_to, credit_out).map_err(|_| Error::<T>::BelowMinimum)?;
 
             Ok(())
@@ -1671,44 +4223,41 @@ pub mod pallet {
         /// only inside a transactional storage context and an Err result must imply a storage
         /// rollback.
         fn credit_swap(
-            is_bid: bool,
             credit_in: CreditOf<T>,
             path: &BalancePath<T>,
         ) -> Result<CreditOf<T>, (CreditOf<T>, DispatchError)> {
             let resolve_path = || -> Result<CreditOf<T>, DispatchError> {
+                for hop in path.windows(2) {
+                    Self::observe_price(&hop[0].0, &hop[1].0)?;
+                }
+                for hop in path.windows(2) {
+                    let ((asset_in, amount_in), (asset_out, amount_out)) = (&hop[0], &hop[1]);
+                    Self::swap_concentrated_liquidity(
+                        asset_in,
+                        asset_out,
+                        *amount_in,
+                        *amount_out,
+                    )?;
+                }
                 for pos in 0..=path.len() {
                     if let Some([(asset1, _), (asset2, amount_out)]) = path.get(pos..=pos + 1) {
-                        let mut base_asset = asset1;
-                        let mut quote_asset = asset2;
-                        if is_bid {
-                            base_asset = asset2;
-                            quote_asset = asset1;
-                        }
-                        if_std! {
-                            println!("base => {:?}, quote => {:?}", base_asset, quote_asset);
+                        let pool_from = Self::get_pool_account(asset1, asset2)?;
+
+                        if let Some((asset3, _)) = path.get(pos + 2) {
+                            // Move the intermediate asset to the pool of the next hop
+                            let pool_to = Self::get_pool_account(asset2, asset3)?;
+                            T::Assets::transfer(
+                                asset2.clone(),
+                                &pool_from,
+                                &pool_to,
+                                *amount_out,
+                                Preserve,
+                            )?;
+                        } else {
+                            let credit_out =
+                                Self::withdraw(asset2.clone(), &pool_from, *amount_out, true)?;
+                            return Ok(credit_out);
                         }
-                        let pool_from = T::PoolLocator::pool_address(base_asset, quote_asset)
-                            .map_err(|_| Error::<T>::InvalidAssetPair)?;
-                        let credit_out =
-                            Self::withdraw(asset2.clone(), &pool_from, *amount_out, true)?;
-                        return Ok(credit_out);
-                        // TODO: Multi-hop
-                        // if let Some((asset3, _)) = path.get(pos + 2) {
-                        // 	let pool_to = T::PoolLocator::pool_address(asset2, asset3)
-                        // 		.map_err(|_| Error::<T>::InvalidAssetPair)?;
-
-                        // 	T::Assets::transfer(
-                        // 		asset2.clone(),
-                        // 		&pool_from,
-                        // 		&pool_to,
-                        // 		*amount_out,
-                        // 		Preserve,
-                        // 	)?;
-                        // } else {
-                        // 	let credit_out =
-                        // 		Self::withdraw(asset2.clone(), &pool_from, *amount_out, true)?;
-                        // 	return Ok(credit_out)
-                        // }
                     }
                 }
                 Err(Error::<T>::InvalidPath.into())
@@ -1720,18 +4269,9 @@ pub mod pallet {
             };
 
             let pool_to = if let Some([(asset1, _), (asset2, _)]) = path.get(0..2) {
-                let mut base_asset = asset1;
-                let mut quote_asset = asset2;
-                if is_bid {
-                    base_asset = asset2;
-                    quote_asset = asset1;
-                }
-                if_std! {
-                    println!("base => {:?}, quote => {:?}", base_asset, quote_asset);
-                }
-                match T::PoolLocator::pool_address(base_asset, quote_asset) {
+                match Self::get_pool_account(asset1, asset2) {
                     Ok(address) => address,
-                    Err(_) => return Err((credit_in, Error::<T>::InvalidAssetPair.into())),
+                    Err(e) => return Err((credit_in, e.into())),
                 }
             } else {
                 return Err((credit_in, Error::<T>::InvalidPath.into()));
@@ -1758,7 +4298,7 @@ pub mod pallet {
                 // TODO drop the ensure! when this issue addressed
                 // https://github.com/paritytech/polkadot-sdk/issues/1698
                 let free = T::Assets::reducible_balance(asset.clone(), who, preservation, Polite);
-                if_std! {println!("Free of AssetId {:?} => {:?}", asset.clone(), free)}
+                log::debug!(target: LOG_TARGET, "Free of {:?} => {:?}", asset, free);
                 ensure!(free >= value, TokenError::NotExpendable);
             }
             T::Assets::withdraw(asset, who, value, Exact, preservation, Polite)
@@ -1770,14 +4310,92 @@ pub mod pallet {
             T::Assets::reducible_balance(asset.clone(), owner, Expendable, Polite)
         }
 
+        /// Get the account of the pool of `asset1` and `asset2`. The pool could be created either
+        /// as `(asset1, asset2)` or `(asset2, asset1)` of `(base, quote)`.
+        pub(crate) fn get_pool_account(
+            asset1: &T::AssetKind,
+            asset2: &T::AssetKind,
+        ) -> Result<T::AccountId, Error<T>> {
+            let pool_id = Self::get_pool_id(asset1, asset2)?;
+            T::PoolLocator::address(&pool_id).map_err(|_| Error::<T>::InvalidAssetPair)
+        }
+
+        /// Get the id of the existing pool of `asset1` and `asset2` in either orientation.
+        fn get_pool_id(
+            asset1: &T::AssetKind,
+            asset2: &T::AssetKind,
+        ) -> Result<T::PoolId, Error<T>> {
+            let pool_id = T::PoolLocator::pool_id(asset1, asset2)
+                .map_err(|_| Error::<T>::InvalidAssetPair)?;
+            if Pools::<T>::contains_key(&pool_id) {
+                return Ok(pool_id);
+            }
+            let pool_id = T::PoolLocator::pool_id(asset2, asset1)
+                .map_err(|_| Error::<T>::InvalidAssetPair)?;
+            ensure!(Pools::<T>::contains_key(&pool_id), Error::<T>::PoolNotFound);
+            Ok(pool_id)
+        }
+
+        /// Swap `amount_in` of `asset_in` on the concentrated liquidity of the pool of `asset_in`
+        /// and `asset_out`, which should pay at least `amount_out`. Nothing is done for the pool
+        /// without [`LiquidityMode::Concentrated`], whose price follows its reserves.
+        fn swap_concentrated_liquidity(
+            asset_in: &T::AssetKind,
+            asset_out: &T::AssetKind,
+            amount_in: T::Unit,
+            amount_out: T::Unit,
+        ) -> DispatchResult {
+            let pool_id = Self::get_pool_id(asset_in, asset_out)?;
+            ConcentratedLiquidities::<T>::try_mutate_exists(&pool_id, |maybe_liquidity| {
+                let Some(liquidity) = maybe_liquidity else { return Ok(()) };
+                let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+                let swapped = liquidity.swap(pool.base_asset == *asset_in, true, amount_in, None)?;
+                ensure!(swapped.amount_out >= amount_out, Error::<T>::AmountOutTooHigh);
+                Ok(())
+            })
+        }
+
+        /// Amount of `asset_out` paid by the pool of `asset_in` and `asset_out` for `amount_in`
+        /// of `asset_in`, which is swapped on the concentrated liquidity of the pool if any.
+        pub(crate) fn get_pool_amount_out(
+            asset_in: &T::AssetKind,
+            asset_out: &T::AssetKind,
+            amount_in: T::Unit,
+        ) -> Result<T::Unit, Error<T>> {
+            let pool_id = Self::get_pool_id(asset_in, asset_out)?;
+            if let Some(mut liquidity) = ConcentratedLiquidities::<T>::get(&pool_id) {
+                let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+                let base_in = pool.base_asset == *asset_in;
+                return Ok(liquidity.swap(base_in, true, amount_in, None)?.amount_out);
+            }
+            let (reserve_in, reserve_out) = Self::get_reserves(asset_in, asset_out)?;
+            Self::get_amount_out(&amount_in, &reserve_in, &reserve_out)
+        }
+
+        /// Amount of `asset_in` paid to the pool of `asset_in` and `asset_out` for `amount_out`
+        /// of `asset_out`, which is swapped on the concentrated liquidity of the pool if any.
+        pub(crate) fn get_pool_amount_in(
+            asset_in: &T::AssetKind,
+            asset_out: &T::AssetKind,
+            amount_out: T::Unit,
+        ) -> Result<T::Unit, Error<T>> {
+            let pool_id = Self::get_pool_id(asset_in, asset_out)?;
+            if let Some(mut liquidity) = ConcentratedLiquidities::<T>::get(&pool_id) {
+                let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+                let base_in = pool.base_asset == *asset_in;
+                return Ok(liquidity.swap(base_in, false, amount_out, None)?.amount_in);
+            }
+            let (reserve_in, reserve_out) = Self::get_reserves(asset_in, asset_out)?;
+            Self::get_amount_in(&amount_out, &reserve_in, &reserve_out)
+        }
+
         /// Returns the balance of each asset in the pool.
         /// The tuple result is in the order requested (not necessarily the same as pool order).
         pub fn get_reserves(
             asset1: &T::AssetKind,
             asset2: &T::AssetKind,
         ) -> Result<(T::Unit, T::Unit), Error<T>> {
-            let pool_account = T::PoolLocator::pool_address(asset1, asset2)
-                .map_err(|_| Error::<T>::InvalidAssetPair)?;
+            let pool_account = Self::get_pool_account(asset1, asset2)?;
 
             let balance1 = Self::get_balance(&pool_account, asset1);
             let balance2 = Self::get_balance(&pool_account, asset2);
@@ -1807,13 +4425,9 @@ pub mod pallet {
                         break;
                     }
                 };
-                // TODO: Pool Id
-                let (b_r, q_r) = Self::get_reserves(&asset2, &asset1)?;
-                balance_path.push((asset2, amount_in));
-                amount_in = Self::get_amount_in(&amount_in, &q_r, &b_r)?;
-                if_std! {
-                    println!("base => {:?}, quote => {:?}, amount_in => {:?}", b_r, q_r, amount_in);
-                }
+                let amount_out = amount_in;
+                amount_in = Self::get_pool_amount_in(asset1, &asset2, amount_out)?;
+                balance_path.push((asset2, amount_out));
             }
             balance_path.reverse();
 
@@ -1837,9 +4451,9 @@ pub mod pallet {
                         break;
                     }
                 };
-                let (reserve_in, reserve_out) = Self::get_reserves(&asset1, &asset2)?;
-                balance_path.push((asset1, amount_out));
-                amount_out = Self::get_amount_out(&amount_out, &reserve_in, &reserve_out)?;
+                let amount_in = amount_out;
+                amount_out = Self::get_pool_amount_out(&asset1, asset2, amount_in)?;
+                balance_path.push((asset1, amount_in));
             }
             Ok(balance_path)
         }
@@ -1851,7 +4465,16 @@ pub mod pallet {
             amount: T::Unit,
             include_fee: bool,
         ) -> Option<T::Unit> {
-            let pool_account = T::PoolLocator::pool_address(&asset1, &asset2).ok()?;
+            let pool_id = Self::get_pool_id(&asset1, &asset2).ok()?;
+            if let Some(liquidity) = ConcentratedLiquidities::<T>::get(&pool_id) {
+                return if include_fee {
+                    Self::get_pool_amount_out(&asset1, &asset2, amount).ok()
+                } else {
+                    let pool = Pools::<T>::get(&pool_id)?;
+                    liquidity.spot_quote(pool.base_asset == asset1, amount).ok()
+                };
+            }
+            let pool_account = Self::get_pool_account(&asset1, &asset2).ok()?;
 
             let balance1 = Self::get_balance(&pool_account, &asset1);
             let balance2 = Self::get_balance(&pool_account, &asset2);
@@ -1873,7 +4496,16 @@ pub mod pallet {
             amount: T::Unit,
             include_fee: bool,
         ) -> Option<T::Unit> {
-            let pool_account = T::PoolLocator::pool_address(&asset1, &asset2).ok()?;
+            let pool_id = Self::get_pool_id(&asset1, &asset2).ok()?;
+            if let Some(liquidity) = ConcentratedLiquidities::<T>::get(&pool_id) {
+                return if include_fee {
+                    Self::get_pool_amount_in(&asset1, &asset2, amount).ok()
+                } else {
+                    let pool = Pools::<T>::get(&pool_id)?;
+                    liquidity.spot_quote(pool.base_asset == asset2, amount).ok()
+                };
+            }
+            let pool_account = Self::get_pool_account(&asset1, &asset2).ok()?;
 
             let balance1 = Self::get_balance(&pool_account, &asset1);
             let balance2 = Self::get_balance(&pool_account, &asset2);
@@ -2041,22 +4673,99 @@ pub mod pallet {
                 .expect("Next pool asset ID can not be None")
         }
     }
+
+    impl<T: Config> PriceOracle<T::AssetKind, T::Unit, BlockNumberFor<T>> for Pallet<T> {
+        fn twap(
+            base_asset: &T::AssetKind,
+            quote_asset: &T::AssetKind,
+            window: BlockNumberFor<T>,
+        ) -> Result<T::Unit, DispatchError> {
+            Self::twap(base_asset, quote_asset, window)
+        }
+
+        fn twap_weight() -> Weight {
+            Self::twap_weight()
+        }
+    }
+
+    impl<T: Config> PriceFeed<T::AssetKind, BlockNumberFor<T>> for Pallet<T> {
+        fn price(
+            base_asset: &T::AssetKind,
+            quote_asset: &T::AssetKind,
+        ) -> Option<PriceInfo<BlockNumberFor<T>>> {
+            Self::price_info(base_asset, quote_asset).ok()
+        }
+
+        fn price_weight() -> Weight {
+            Self::price_weight()
+        }
+    }
 }
 
 sp_api::decl_runtime_apis! {
     /// This runtime api allows people to query the size of the liquidity pools
     /// and quote prices for swaps.
-    pub trait HybridOrderbookApi<Balance, AssetId, Orderbook>
+    ///
+    /// Version 2 adds the reserves, depth, open orders, recent trades, time weighted average
+    /// price, candles and quote of the market order, with the `AccountId` of the open orders and
+    /// the trades.
+    #[api_version(2)]
+    pub trait HybridOrderbookApi<Balance, AssetId, Orderbook, AccountId>
     where
         Balance: frame_support::traits::tokens::Balance + MaybeDisplay,
         AssetId: Codec,
         Orderbook: Codec,
+        AccountId: Codec,
     {
         /// Returns query of the `pool`
         fn get_pool_query(base: AssetId, quote: AssetId) -> Option<PoolQuery<Orderbook, Balance>>;
 
         /// Returns the metadata of the pool
         fn get_pool_metadata(base: AssetId, quote: AssetId) -> Option<PoolMetadata<Balance>>;
+
+        /// Returns the reserves and the price of the pool without its orderbook
+        #[api_version(2)]
+        fn get_pool_reserves(base: AssetId, quote: AssetId) -> Option<PoolReserves<Balance>>;
+
+        /// Returns up to `levels` aggregated price levels of each side of the orderbook, best first
+        #[api_version(2)]
+        fn get_depth(base: AssetId, quote: AssetId, levels: u32) -> Option<PoolDepth<Balance>>;
+
+        /// Returns the open limit orders of `account` on the pool of `(base, quote)` if given,
+        /// otherwise on all pools
+        #[api_version(2)]
+        fn open_orders(
+            account: AccountId,
+            pool: Option<(AssetId, AssetId)>,
+        ) -> Vec<OpenOrder<(AssetId, AssetId), Balance, sp_runtime::traits::NumberFor<Block>>>;
+
+        /// Returns up to `limit` of the recent trades of the pool, the latest first
+        #[api_version(2)]
+        fn recent_trades(
+            base: AssetId,
+            quote: AssetId,
+            limit: u32,
+        ) -> Option<Vec<TradeRecord<AccountId, Balance, sp_runtime::traits::NumberFor<Block>>>>;
+
+        /// Returns the time weighted average pool price over the last `window` blocks
+        #[api_version(2)]
+        fn twap(base: AssetId, quote: AssetId, window: sp_runtime::traits::NumberFor<Block>) -> Option<Balance>;
+
+        /// Returns the candles of `resolution` whose buckets are within `from` and `to`, oldest
+        /// first
+        #[api_version(2)]
+        fn candles(
+            base: AssetId,
+            quote: AssetId,
+            resolution: u32,
+            from: sp_runtime::traits::NumberFor<Block>,
+            to: sp_runtime::traits::NumberFor<Block>,
+        ) -> Option<Vec<Candle<Balance, sp_runtime::traits::NumberFor<Block>>>>;
+
+        /// Returns the amount of quote asset to pay(bid) or receive(ask) for the market order of
+        /// `quantity`
+        #[api_version(2)]
+        fn quote_market_order(base: AssetId, quote: AssetId, quantity: Balance, is_bid: bool) -> Option<Balance>;
     }
 }
 
@@ -0,0 +1,222 @@
+// This file is part of Substrate.
+
+// Copyright (C) Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Migrations of the storage of the pallet.
+
+use super::*;
+use frame_support::{
+    migrations::VersionedMigration,
+    pallet_prelude::*,
+    storage_alias,
+    traits::UncheckedOnRuntimeUpgrade,
+};
+use frame_system::pallet_prelude::BlockNumberFor;
+
+/// Migration to v1, which moves the nodes of the orderbooks out of [`Pools`].
+pub mod v1 {
+    use super::*;
+
+    /// Storage layout before v1, where each pool holds its whole orderbooks inline.
+    pub mod v0 {
+        use super::*;
+
+        /// Order of the orderbook of v0
+        pub type OrderOf<T> =
+            Order<<T as Config>::Unit, <T as frame_system::Config>::AccountId, BlockNumberFor<T>>;
+
+        /// Orderbook of v0, which is the critbit tree holding all of its nodes
+        pub type OrderBookOf<T> = CritbitTree<<T as Config>::Unit, Tick<OrderOf<T>>>;
+
+        /// Pool of v0
+        pub type PoolOf<T> = Pool<
+            <T as Config>::PoolAssetId,
+            OrderBookOf<T>,
+            <T as Config>::Unit,
+        >;
+
+        #[storage_alias]
+        pub type Pools<T: Config> =
+            StorageMap<Pallet<T>, Blake2_128Concat, <T as Config>::PoolId, PoolOf<T>, OptionQuery>;
+
+        /// Critbit tree of v0
+        #[derive(Encode, Decode, Clone, RuntimeDebug)]
+        pub struct CritbitTree<K, V> {
+            pub root: K,
+            pub internal_nodes: BTreeMap<K, InternalNode<K>>,
+            pub leaves: BTreeMap<K, LeafNode<K, V>>,
+            pub max_leaf_index: K,
+            pub min_leaf_index: K,
+            pub next_internal_node_index: K,
+            pub next_leaf_node_index: K,
+        }
+
+        /// Internal node of the critbit tree of v0
+        #[derive(Encode, Decode, Clone, RuntimeDebug)]
+        pub struct InternalNode<K> {
+            pub mask: K,
+            pub parent: K,
+            pub left: K,
+            pub right: K,
+        }
+
+        /// Leaf node of the critbit tree of v0, whose `key` is the price of the orders
+        #[derive(Encode, Decode, Clone, RuntimeDebug)]
+        pub struct LeafNode<K, V> {
+            pub parent: K,
+            pub key: K,
+            pub value: V,
+        }
+
+        /// Price level of v0, which is not bounded by [`Config::MaxOrdersPerTick`]
+        #[derive(Encode, Decode, Clone, RuntimeDebug)]
+        pub struct Tick<Order> {
+            pub open_orders: BTreeMap<OrderId, Order>,
+        }
+
+        /// Pool of v0, which has neither the assets nor the maker fee rate
+        #[derive(Encode, Decode, Clone, RuntimeDebug)]
+        pub struct Pool<PoolAssetId, OrderBook, Unit> {
+            pub lp_token: PoolAssetId,
+            pub bids: OrderBook,
+            pub asks: OrderBook,
+            pub next_bid_order_id: OrderId,
+            pub next_ask_order_id: OrderId,
+            pub taker_fee_rate: Permill,
+            pub tick_size: Unit,
+            pub lot_size: Unit,
+            pub pool_decimals: u8,
+            pub base_decimals: u8,
+            pub quote_decimals: u8,
+        }
+    }
+
+    /// Move the nodes of the orderbooks of every pool to [`InternalNodes`] and [`LeafNodes`], and
+    /// fill the assets and the maker fee rate of the pool. Open orders are restored with their
+    /// ids and indexed on [`OpenOrders`] and [`OrderExpiries`] without the order deposit. Orders
+    /// which don't fit into [`Config::MaxTicks`] or [`Config::MaxOrdersPerTick`] are dropped and
+    /// their frozen asset is released.
+    ///
+    /// Requires [`Config::PoolId`] to be the pair of the base and quote asset, as with
+    /// [`BaseQuoteAsset`].
+    pub struct UncheckedMigrateToV1<T>(PhantomData<T>);
+
+    impl<T> UncheckedOnRuntimeUpgrade for UncheckedMigrateToV1<T>
+    where
+        T: Config<PoolId = (<T as Config>::AssetKind, <T as Config>::AssetKind)>,
+    {
+        fn on_runtime_upgrade() -> Weight {
+            let now = frame_system::Pallet::<T>::block_number();
+            let (mut reads, mut writes) = (0u64, 0u64);
+            Pools::<T>::translate::<v0::PoolOf<T>, _>(|pool_id, old| {
+                // Read and write of the pool and `NextOrderBookId` for each orderbook
+                reads += 3;
+                writes += 3;
+                let (base_asset, quote_asset) = pool_id.clone();
+                let mut pool = Pool::<T>::new(
+                    old.lp_token,
+                    base_asset,
+                    quote_asset,
+                    old.taker_fee_rate,
+                    MakerFeeRate::default(),
+                    old.tick_size,
+                    old.lot_size,
+                    old.pool_decimals,
+                    old.base_decimals,
+                    old.quote_decimals,
+                    LiquidityMode::ConstantProduct,
+                );
+                pool.restore_next_order_ids(old.next_bid_order_id, old.next_ask_order_id);
+                let leaves = old.bids.leaves.into_values().chain(old.asks.leaves.into_values());
+                for leaf in leaves {
+                    let price = leaf.key;
+                    for (order_id, order) in leaf.value.open_orders {
+                        let owner = order.owner();
+                        let (quantity, expired_at) = (order.quantity(), order.expired_at());
+                        // Nodes of the orderbook, `OpenOrders`, `OpenOrdersCount` and
+                        // `OrderExpiries`
+                        reads += 4;
+                        writes += 5;
+                        if let Err(e) =
+                            pool.restore_order(order_id, &owner, price, quantity, expired_at)
+                        {
+                            let is_bid = order_id.is_bid();
+                            let asset = if is_bid { &pool.quote_asset } else { &pool.base_asset };
+                            let amount = Pallet::<T>::frozen_amount(&pool, is_bid, price, quantity);
+                            let released = Pallet::<T>::unfreeze_asset(&owner, asset, amount);
+                            log::warn!(
+                                target: LOG_TARGET,
+                                "Drop order {:?} of {:?} at {:?}: {:?}, released: {:?}",
+                                order_id,
+                                owner,
+                                price,
+                                e,
+                                released,
+                            );
+                            continue;
+                        }
+                        // Expired orders are swept from the block of the upgrade
+                        OrderExpiries::<T>::insert(
+                            expired_at.max(now),
+                            (pool_id.clone(), order_id),
+                            price,
+                        );
+                        OpenOrders::<T>::insert(
+                            (owner.clone(), pool_id.clone(), order_id),
+                            (price, T::Unit::zero()),
+                        );
+                        OpenOrdersCount::<T>::mutate(&owner, |count| {
+                            *count = count.saturating_add(1)
+                        });
+                    }
+                }
+                Some(pool)
+            });
+            T::DbWeight::get().reads_writes(reads, writes)
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, sp_runtime::TryRuntimeError> {
+            let pools = v0::Pools::<T>::iter_values().collect::<Vec<_>>();
+            let orders = pools
+                .iter()
+                .flat_map(|pool| pool.bids.leaves.values().chain(pool.asks.leaves.values()))
+                .map(|leaf| leaf.value.open_orders.len() as u64)
+                .sum::<u64>();
+            Ok((pools.len() as u64, orders).encode())
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
+            let (pools, orders) = <(u64, u64)>::decode(&mut &state[..])
+                .map_err(|_| "Failed to decode the state of pre_upgrade")?;
+            ensure!(Pools::<T>::iter().count() as u64 == pools, "Pools are lost on the upgrade");
+            let restored = OpenOrders::<T>::iter().count() as u64;
+            ensure!(restored <= orders, "More orders are restored than stored");
+            Ok(())
+        }
+    }
+
+    /// [`UncheckedMigrateToV1`] which is only run if the storage version of the pallet is 0.
+    pub type MigrateToV1<T> = VersionedMigration<
+        0,
+        1,
+        UncheckedMigrateToV1<T>,
+        Pallet<T>,
+        <T as frame_system::Config>::DbWeight,
+    >;
+}
+
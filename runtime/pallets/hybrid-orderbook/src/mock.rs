@@ -77,7 +77,7 @@ impl pallet_balances::Config for Test {
     type ReserveIdentifier = [u8; 8];
     type FreezeIdentifier = RuntimeFreezeReason;
     type MaxFreezes = ();
-    type RuntimeHoldReason = ();
+    type RuntimeHoldReason = RuntimeHoldReason;
     type RuntimeFreezeReason = RuntimeFreezeReason;
     type DoneSlashHandler = ();
 }
@@ -158,6 +158,20 @@ pub type OrderbookLocator = BaseQuoteAsset<AccountId, NativeOrWithId<u32>>;
 
 parameter_types! {
     pub const OrderExpiration: u64 = 100;
+    pub storage MaxTicks: u32 = 1000;
+    pub storage MaxOpenOrders: u32 = 1000;
+    pub storage MaxStopOrders: u32 = 10;
+    pub storage MaxOrdersFilled: u32 = 100;
+    pub storage MaxRecentTrades: u32 = 100;
+    pub storage MaxPriceObservations: u32 = 100;
+    pub storage OraclePriceSource: PriceSource<u64> = PriceSource::Spot;
+    pub storage MaxExpiredPriceLevels: u32 = 4;
+    pub const MaxCandleResolutions: u32 = 4;
+    pub CandleResolutions: BoundedVec<u32, MaxCandleResolutions> =
+        BoundedVec::truncate_from(vec![10, 600]);
+    pub storage MaxCandles: u32 = 10;
+    pub storage MaxLiquidityBoundaries: u32 = 100;
+    pub storage OrderDeposit: Balance = 0;
 }
 
 impl Config for Test {
@@ -167,8 +181,24 @@ impl Config for Test {
     type AssetKind = NativeOrWithId<u32>;
     type Assets = NativeAndAssets;
     type AssetsFreezer = NativeAndAssetsFreezer;
-    type OrderBook = CritbitTree<Balance, Tick<Balance, AccountId, u64>>;
+    type OrderBook =
+        CritbitTree<Balance, Tick<Balance, AccountId, u64, ConstU32<10>>, OrderBookNodes<Test>>;
     type OrderExpiration = OrderExpiration;
+    type MaxExpiredOrdersPerBlock = ConstU32<100>;
+    type MaxTriggeredStopOrders = ConstU32<10>;
+    type MaxStopOrders = MaxStopOrders;
+    type MaxTicks = MaxTicks;
+    type MaxOrdersPerTick = ConstU32<10>;
+    type MaxOpenOrders = MaxOpenOrders;
+    type MaxOrdersFilled = MaxOrdersFilled;
+    type MaxRecentTrades = MaxRecentTrades;
+    type MaxPriceObservations = MaxPriceObservations;
+    type OraclePriceSource = OraclePriceSource;
+    type MaxExpiredPriceLevels = MaxExpiredPriceLevels;
+    type MaxCandleResolutions = MaxCandleResolutions;
+    type CandleResolutions = CandleResolutions;
+    type MaxCandles = MaxCandles;
+    type MaxLiquidityBoundaries = MaxLiquidityBoundaries;
     type PoolId = (Self::AssetKind, Self::AssetKind);
     type PoolLocator = OrderbookLocator;
     type PoolAssetId = u32;
@@ -176,7 +206,11 @@ impl Config for Test {
     type PoolSetupFee = ConstU64<100>; // should be more or equal to the existential deposit
     type PoolSetupFeeAsset = Native;
     type PoolSetupFeeTarget = ResolveAssetTo<HybridOrderbookOrigin, Self::Assets>;
+    type TakerFeeTarget = ResolveAssetTo<HybridOrderbookOrigin, Self::Assets>;
     type RuntimeFreezeReason = RuntimeFreezeReason;
+    type RuntimeHoldReason = RuntimeHoldReason;
+    type Currency = Balances;
+    type OrderDeposit = OrderDeposit;
     type PalletId = HybridOrderbookPalletId;
     type WeightInfo = ();
     type LPFee = ConstU32<3>; // means 0.3%
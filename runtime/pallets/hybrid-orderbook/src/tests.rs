@@ -22,11 +22,14 @@ use crate::{
 use frame_support::{
     assert_noop, assert_ok,
     traits::{
-        fungible::NativeOrWithId,
-        fungibles::{Inspect, InspectEnumerable},
+        fungible::{InspectHold, NativeOrWithId},
+        fungibles::{Inspect, InspectEnumerable, InspectFreeze, MutateFreeze},
+        Hooks,
     },
+    weights::Weight,
 };
 use sp_arithmetic::Permill;
+use sp_runtime::{FixedPointNumber, FixedU128};
 
 fn events() -> Vec<Event<Test>> {
     let result = System::events()
@@ -90,6 +93,27 @@ fn balance(owner: MockAccountId, token_id: NativeOrWithId<u32>) -> MockBalance {
     <<Test as Config>::Assets>::balance(token_id, &owner)
 }
 
+fn frozen_balance(owner: MockAccountId, token_id: NativeOrWithId<u32>) -> MockBalance {
+    <<Test as Config>::AssetsFreezer as InspectFreeze<MockAccountId>>::balance_frozen(
+        token_id,
+        &FreezeReason::LimitOrder.into(),
+        &owner,
+    )
+}
+
+fn open_orders_of(
+    owner: MockAccountId,
+    base: &NativeOrWithId<u32>,
+    quote: &NativeOrWithId<u32>,
+    is_bid: bool,
+) -> Vec<OpenOrderOf<Test>> {
+    HybridOrderbook::open_orders(&owner, Some((base, quote)))
+        .unwrap()
+        .into_iter()
+        .filter(|order| order.is_bid == is_bid)
+        .collect()
+}
+
 fn pool_balance(owner: MockAccountId, token_id: u32) -> MockBalance {
     <<Test as Config>::PoolAssets>::balance(token_id, owner)
 }
@@ -121,9 +145,11 @@ fn pool_with_default_liquidity(
         Box::new(quote.clone()),
         9,
         Permill::zero(),
+        MakerFeeRate::default(),
         tick_size,
         lot_size,
-        0
+        0,
+        LiquidityMode::ConstantProduct,
     ));
     let ed = get_native_ed();
     assert_ok!(Balances::force_set_balance(
@@ -167,6 +193,7 @@ fn pool_with_default_liquidity(
             true,
             order_price,
             order_quantity,
+            TimeInForce::GoodTilCancel,
         ));
         order_price -= tick_size;
     }
@@ -182,6 +209,7 @@ fn pool_with_default_liquidity(
             false,
             order_price,
             order_quantity,
+            TimeInForce::GoodTilCancel,
         ));
 
         order_price += tick_size;
@@ -232,9 +260,11 @@ fn create_pool_works() {
             Box::new(quote_asset.clone()),
             9,
             Permill::zero(),
+            MakerFeeRate::default(),
             5,
             1,
-            0
+            0,
+            LiquidityMode::ConstantProduct,
         ));
         let Pool {
             lp_token,
@@ -253,9 +283,11 @@ fn create_pool_works() {
                 Box::new(base_asset.clone()),
                 9,
                 Permill::zero(),
+                MakerFeeRate::default(),
                 5,
                 1,
-                0
+                0,
+                LiquidityMode::ConstantProduct,
             ),
             Error::<Test>::InvalidAssetPair
         );
@@ -267,9 +299,11 @@ fn create_pool_works() {
                 Box::new(quote_asset.clone()),
                 9,
                 Permill::zero(),
+                MakerFeeRate::default(),
                 5,
                 1,
-                0
+                0,
+                LiquidityMode::ConstantProduct,
             ),
             Error::<Test>::InvalidAssetPair
         );
@@ -296,9 +330,11 @@ fn pool_price_works() {
             Box::new(quote.clone()),
             6,
             Permill::zero(),
+            MakerFeeRate::default(),
             5,
             1,
-            2
+            2,
+            LiquidityMode::ConstantProduct,
         ));
         assert_ok!(HybridOrderbook::add_liquidity(
             RuntimeOrigin::signed(user),
@@ -338,9 +374,11 @@ fn add_liquidity_works() {
             Box::new(quote.clone()),
             9,
             Permill::zero(),
+            MakerFeeRate::default(),
             5,
             1,
-            0
+            0,
+            LiquidityMode::ConstantProduct,
         ));
         let ed = get_native_ed();
         let base_provided = 100;
@@ -416,9 +454,11 @@ fn limit_order_works() {
             Box::new(quote.clone()),
             9,
             Permill::zero(),
+            MakerFeeRate::default(),
             tick_size,
             lot_size,
-            0
+            0,
+            LiquidityMode::ConstantProduct,
         ));
         let ed = get_native_ed();
         let base_provided = 10000;
@@ -450,7 +490,8 @@ fn limit_order_works() {
                 Box::new(quote.clone()),
                 true,
                 5,
-                100
+                100,
+                TimeInForce::GoodTilCancel
             ),
             Error::<Test>::ZeroLiquidity
         );
@@ -476,7 +517,8 @@ fn limit_order_works() {
                 Box::new(quote.clone()),
                 true,
                 2,
-                100
+                100,
+                TimeInForce::GoodTilCancel
             ),
             Error::<Test>::InvalidOrderPrice
         );
@@ -492,6 +534,7 @@ fn limit_order_works() {
                 is_bid,
                 order_price,
                 order_quantity,
+                TimeInForce::GoodTilCancel,
             ));
             assert!(events().contains(&Event::<Test>::LimitOrder {
                 pool_id: pool_id.clone(),
@@ -514,6 +557,7 @@ fn limit_order_works() {
                 !is_bid,
                 order_price,
                 order_quantity,
+                TimeInForce::GoodTilCancel,
             ));
             assert!(events().contains(&Event::<Test>::LimitOrder {
                 pool_id: pool_id.clone(),
@@ -531,6 +575,8 @@ fn limit_order_works() {
             Box::new(quote.clone()),
             150,
             true,
+            None,
+            None,
         ));
         let balance1 = Assets::balance(1, &user);
         let balance2 = Assets::balance(2, &user);
@@ -575,7 +621,8 @@ fn market_order_works() {
             Box::new(quote.clone()),
             false,
             101,
-            100
+            100,
+            TimeInForce::GoodTilCancel
         ));
         assert_ok!(HybridOrderbook::limit_order(
             RuntimeOrigin::signed(user3),
@@ -583,7 +630,8 @@ fn market_order_works() {
             Box::new(quote.clone()),
             false,
             101,
-            200
+            200,
+            TimeInForce::GoodTilCancel
         ));
         assert_ok!(HybridOrderbook::limit_order(
             RuntimeOrigin::signed(user4),
@@ -591,7 +639,8 @@ fn market_order_works() {
             Box::new(quote.clone()),
             false,
             101,
-            300
+            300,
+            TimeInForce::GoodTilCancel
         ));
         let pool = Pools::<Test>::get(&pool_id).unwrap();
         println!("Before => {:?}", pool.get_orderbook(false));
@@ -601,6 +650,8 @@ fn market_order_works() {
             Box::new(quote.clone()),
             450,
             true,
+            None,
+            None,
         ));
         let pool = Pools::<Test>::get(&pool_id).unwrap();
         println!("After => {:?}", pool.get_orderbook(false));
@@ -613,7 +664,6 @@ fn cancel_order_works() {
         let initial_provider: MockAccountId = 1;
         let base = NativeOrWithId::WithId(1);
         let quote = NativeOrWithId::WithId(2);
-        let pool_id = (base.clone(), quote.clone());
         let order_quantity = 50;
         // Default pool price => 100
         let base_provided = 1000;
@@ -637,7 +687,8 @@ fn cancel_order_works() {
             Box::new(quote.clone()),
             false,
             order_price,
-            50
+            50,
+            TimeInForce::GoodTilCancel
         ));
         assert_ok!(HybridOrderbook::cancel_order(
             RuntimeOrigin::signed(2),
@@ -669,7 +720,2817 @@ fn cancel_order_works() {
             9223372036854775908.into(),
             40,
         ));
+        assert!(open_orders_of(2, &base, &quote, false).is_empty());
+    })
+}
+
+#[test]
+fn cancel_order_releases_frozen_funds() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let pool_id = (base.clone(), quote.clone());
+        let order_quantity = 50;
+        // Default pool price => 100
+        let base_provided = 1000;
+        let quote_provided = 100000;
+        let tick_size = 1;
+        let lot_size = 1;
+        pool_with_default_liquidity(
+            initial_provider,
+            &base,
+            &quote,
+            order_quantity,
+            base_provided,
+            quote_provided,
+            tick_size,
+            lot_size,
+        );
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 1, maker, 1000));
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 2, maker, 10000));
+
+        // Ask order freezes `quantity` of base asset
+        let ask_price = 101;
+        let ask_order_id: OrderId = 9223372036854775908.into();
+        assert_ok!(HybridOrderbook::limit_order(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            false,
+            ask_price,
+            50,
+            TimeInForce::GoodTilCancel
+        ));
+        assert_eq!(frozen_balance(maker, base.clone()), 50);
+        assert_ok!(HybridOrderbook::cancel_order(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            ask_price,
+            ask_order_id,
+            20
+        ));
+        assert!(events().contains(&Event::<Test>::OrderCancelled {
+            pool_id: pool_id.clone(),
+            owner: maker,
+            order_id: ask_order_id,
+            released: 20,
+        }));
+        assert_eq!(frozen_balance(maker, base.clone()), 30);
+        assert_ok!(HybridOrderbook::cancel_order(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            ask_price,
+            ask_order_id,
+            30
+        ));
+        assert_eq!(frozen_balance(maker, base.clone()), 0);
+        assert_eq!(balance(maker, base.clone()), 1000);
+
+        // Bid order freezes `price * quantity` of quote asset
+        let bid_price = 99;
+        let bid_order_id: OrderId = 99.into();
+        assert_ok!(HybridOrderbook::limit_order(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            true,
+            bid_price,
+            50,
+            TimeInForce::GoodTilCancel
+        ));
+        assert_eq!(frozen_balance(maker, quote.clone()), bid_price * 50);
+        assert_ok!(HybridOrderbook::cancel_order(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            bid_price,
+            bid_order_id,
+            50
+        ));
+        assert!(events().contains(&Event::<Test>::OrderCancelled {
+            pool_id: pool_id.clone(),
+            owner: maker,
+            order_id: bid_order_id,
+            released: bid_price * 50,
+        }));
+        assert_eq!(frozen_balance(maker, quote.clone()), 0);
+        assert_eq!(balance(maker, quote.clone()), 10000);
+        assert!(open_orders_of(maker, &base, &quote, true).is_empty());
+        assert!(open_orders_of(maker, &base, &quote, false).is_empty());
+    })
+}
+
+#[test]
+fn cancel_order_by_id_works() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let taker: MockAccountId = 3;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let pool_id = (base.clone(), quote.clone());
+        // Ask order of 50 @ 101 placed by `maker`
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::zero(),
+            MakerFeeRate::default(),
+        );
+        let order_ids: Vec<OrderId> =
+            OpenOrders::<Test>::iter_key_prefix((maker, pool_id.clone())).collect();
+        assert_eq!(order_ids.len(), 1);
+        let ask_order_id = order_ids[0];
+        assert_eq!(OpenOrders::<Test>::get((maker, pool_id.clone(), ask_order_id)), Some((101, 0)));
+
+        // Partially fill the ask order
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 2, taker, 10000));
+        assert_ok!(HybridOrderbook::market_order(
+            RuntimeOrigin::signed(taker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            30,
+            true,
+            None,
+            None,
+        ));
+        let remaining = Pools::<Test>::get(&pool_id)
+            .unwrap()
+            .get_order(false, 101, ask_order_id)
+            .unwrap()
+            .quantity();
+        assert!(remaining > 0 && remaining < 50);
+        assert_eq!(frozen_balance(maker, base.clone()), remaining);
+
+        // Only the owner can cancel the order
+        assert_noop!(
+            HybridOrderbook::cancel_order_by_id(
+                RuntimeOrigin::signed(taker),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                ask_order_id,
+            ),
+            Error::<Test>::OrderNotFound
+        );
+        assert_ok!(HybridOrderbook::cancel_order_by_id(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            ask_order_id,
+        ));
+        assert!(events().contains(&Event::<Test>::OrderCancelled {
+            pool_id: pool_id.clone(),
+            owner: maker,
+            order_id: ask_order_id,
+            released: remaining,
+        }));
+        assert_eq!(frozen_balance(maker, base.clone()), 0);
+        assert_eq!(OpenOrders::<Test>::get((maker, pool_id.clone(), ask_order_id)), None);
+        assert!(open_orders_of(maker, &base, &quote, false).is_empty());
+        let pool = Pools::<Test>::get(&pool_id).unwrap();
+        assert!(pool.next_ask_order().is_none());
+        assert_noop!(
+            HybridOrderbook::cancel_order_by_id(
+                RuntimeOrigin::signed(maker),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                ask_order_id,
+            ),
+            Error::<Test>::OrderNotFound
+        );
+    })
+}
+
+#[test]
+fn open_orders_are_removed_when_filled() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let taker: MockAccountId = 3;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let pool_id = (base.clone(), quote.clone());
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::zero(),
+            MakerFeeRate::default(),
+        );
+        assert_eq!(OpenOrders::<Test>::iter_key_prefix((maker, pool_id.clone())).count(), 1);
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 2, taker, 30000));
+        assert_ok!(HybridOrderbook::market_order(
+            RuntimeOrigin::signed(taker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            100,
+            true,
+            None,
+            None,
+        ));
+        assert_eq!(frozen_balance(maker, base.clone()), 0);
+        assert_eq!(OpenOrders::<Test>::iter_key_prefix((maker, pool_id.clone())).count(), 0);
+    })
+}
+
+#[test]
+fn cancel_all_orders_works() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let pool_id = (base.clone(), quote.clone());
+        // Ask order of 50 @ 101 placed by `maker`
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::zero(),
+            MakerFeeRate::default(),
+        );
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 2, maker, 10000));
+        assert_ok!(HybridOrderbook::limit_order(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            false,
+            102,
+            10,
+            TimeInForce::GoodTilCancel
+        ));
+        assert_ok!(HybridOrderbook::limit_order(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            true,
+            99,
+            10,
+            TimeInForce::GoodTilCancel
+        ));
+        assert_eq!(frozen_balance(maker, base.clone()), 60);
+        assert_eq!(frozen_balance(maker, quote.clone()), 990);
+        assert_eq!(OpenOrders::<Test>::iter_key_prefix((maker, pool_id.clone())).count(), 3);
+        System::reset_events();
+
+        let post_info = HybridOrderbook::cancel_all_orders(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+        )
+        .unwrap();
+        // Weight is refunded down to the orders cancelled
+        assert_eq!(post_info.actual_weight, Some(<() as WeightInfo>::cancel_all_orders(3)));
+        let cancelled = events()
+            .into_iter()
+            .filter(|e| matches!(e, Event::<Test>::OrderCancelled { .. }))
+            .count();
+        assert_eq!(cancelled, 3);
+        assert_eq!(frozen_balance(maker, base.clone()), 0);
+        assert_eq!(frozen_balance(maker, quote.clone()), 0);
+        assert_eq!(OpenOrders::<Test>::iter_key_prefix((maker, pool_id.clone())).count(), 0);
+        let pool = Pools::<Test>::get(&pool_id).unwrap();
+        assert!(pool.next_ask_order().is_none());
+        assert!(pool.next_bid_order().is_none());
+        assert_noop!(
+            HybridOrderbook::cancel_all_orders(
+                RuntimeOrigin::signed(maker),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+            ),
+            Error::<Test>::OrderNotFound
+        );
+    })
+}
+
+#[test]
+fn amend_order_works() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let maker2: MockAccountId = 3;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let pool_id = (base.clone(), quote.clone());
+        // Ask order of 50 @ 101 placed by `maker`
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::zero(),
+            MakerFeeRate::default(),
+        );
+        let order_id = OpenOrders::<Test>::iter_key_prefix((maker, pool_id.clone()))
+            .next()
+            .unwrap();
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 1, maker2, 1000));
+        assert_ok!(HybridOrderbook::limit_order(
+            RuntimeOrigin::signed(maker2),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            false,
+            101,
+            10,
+            TimeInForce::GoodTilCancel
+        ));
+        let order_id2 = OpenOrders::<Test>::iter_key_prefix((maker2, pool_id.clone()))
+            .next()
+            .unwrap();
+
+        // Decreasing the quantity keeps the order id(time priority)
+        assert_ok!(HybridOrderbook::amend_order(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            order_id,
+            101,
+            30
+        ));
+        assert!(events().contains(&Event::<Test>::OrderAmended {
+            pool_id: pool_id.clone(),
+            owner: maker,
+            order_id,
+            new_order_id: order_id,
+            price: 101,
+            quantity: 30,
+            is_bid: false,
+        }));
+        assert_eq!(frozen_balance(maker, base.clone()), 30);
+        let pool = Pools::<Test>::get(&pool_id).unwrap();
+        assert_eq!(pool.get_order(false, 101, order_id).unwrap().quantity(), 30);
+
+        // Increasing the quantity loses the time priority
+        assert_ok!(HybridOrderbook::amend_order(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            order_id,
+            101,
+            60
+        ));
+        let new_order_id = OpenOrders::<Test>::iter_key_prefix((maker, pool_id.clone()))
+            .next()
+            .unwrap();
+        assert!(new_order_id > order_id2);
+        assert_eq!(OpenOrders::<Test>::get((maker, pool_id.clone(), order_id)), None);
+        assert_eq!(frozen_balance(maker, base.clone()), 60);
+        let pool = Pools::<Test>::get(&pool_id).unwrap();
+        assert!(pool.get_order(false, 101, order_id).is_none());
+        assert_eq!(pool.get_order(false, 101, new_order_id).unwrap().quantity(), 60);
+
+        // Moving the price re-places the order
+        assert_ok!(HybridOrderbook::amend_order(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            new_order_id,
+            105,
+            40
+        ));
+        let moved_order_id = OpenOrders::<Test>::iter_key_prefix((maker, pool_id.clone()))
+            .next()
+            .unwrap();
+        assert_eq!(
+            OpenOrders::<Test>::get((maker, pool_id.clone(), moved_order_id)),
+            Some((105, 0))
+        );
+        assert_eq!(frozen_balance(maker, base.clone()), 40);
         let pool = Pools::<Test>::get(&pool_id).unwrap();
-        assert!(pool.orders_for(&2, false).is_empty());
+        assert!(pool.get_order(false, 101, new_order_id).is_none());
+        assert_eq!(pool.get_order(false, 105, moved_order_id).unwrap().quantity(), 40);
+
+        // Amended order should not cross the pool price
+        assert_noop!(
+            HybridOrderbook::amend_order(
+                RuntimeOrigin::signed(maker),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                moved_order_id,
+                99,
+                40
+            ),
+            Error::<Test>::InvalidOrderPrice
+        );
+        // Only the owner can amend the order
+        assert_noop!(
+            HybridOrderbook::amend_order(
+                RuntimeOrigin::signed(maker2),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                moved_order_id,
+                105,
+                20
+            ),
+            Error::<Test>::OrderNotFound
+        );
+    })
+}
+
+#[test]
+fn amend_bid_order_adjusts_frozen_quote() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let pool_id = (base.clone(), quote.clone());
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::zero(),
+            MakerFeeRate::default(),
+        );
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 2, maker, 10000));
+        assert_ok!(HybridOrderbook::limit_order(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            true,
+            99,
+            10,
+            TimeInForce::GoodTilCancel
+        ));
+        assert_eq!(frozen_balance(maker, quote.clone()), 990);
+        let bid_order_id = OpenOrders::<Test>::iter_key_prefix((maker, pool_id.clone()))
+            .find(|order_id| order_id.is_bid())
+            .unwrap();
+        assert_ok!(HybridOrderbook::amend_order(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            bid_order_id,
+            98,
+            20
+        ));
+        assert_eq!(frozen_balance(maker, quote.clone()), 1960);
+        assert_ok!(HybridOrderbook::amend_order(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            OpenOrders::<Test>::iter_key_prefix((maker, pool_id.clone()))
+                .find(|order_id| order_id.is_bid())
+                .unwrap(),
+            98,
+            5
+        ));
+        assert_eq!(frozen_balance(maker, quote.clone()), 490);
+        // Ask order of `maker` is untouched
+        assert_eq!(frozen_balance(maker, base.clone()), 50);
+    })
+}
+
+#[test]
+fn time_in_force_works() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let trader: MockAccountId = 3;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let pool_id = (base.clone(), quote.clone());
+        // Pool price => 100, ask order of 50 @ 101 placed by `maker`
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::zero(),
+            MakerFeeRate::default(),
+        );
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 2, trader, 100000));
+
+        // `PostOnly` order is rejected if it crosses the pool price
+        assert_noop!(
+            HybridOrderbook::limit_order(
+                RuntimeOrigin::signed(trader),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                true,
+                101,
+                10,
+                TimeInForce::PostOnly
+            ),
+            Error::<Test>::OrderWouldCross
+        );
+        assert_ok!(HybridOrderbook::limit_order(
+            RuntimeOrigin::signed(trader),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            true,
+            99,
+            10,
+            TimeInForce::PostOnly
+        ));
+        assert_eq!(frozen_balance(trader, quote.clone()), 990);
+
+        // `GoodTilBlock` order rests with the given expiry
+        assert_noop!(
+            HybridOrderbook::limit_order(
+                RuntimeOrigin::signed(trader),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                true,
+                98,
+                10,
+                TimeInForce::GoodTilBlock(1)
+            ),
+            Error::<Test>::InvalidExpiry
+        );
+        // Not later than `OrderExpiration` from now
+        assert_noop!(
+            HybridOrderbook::limit_order(
+                RuntimeOrigin::signed(trader),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                true,
+                98,
+                10,
+                TimeInForce::GoodTilBlock(System::block_number() + OrderExpiration::get() + 1)
+            ),
+            Error::<Test>::InvalidExpiry
+        );
+        assert_ok!(HybridOrderbook::limit_order(
+            RuntimeOrigin::signed(trader),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            true,
+            98,
+            10,
+            TimeInForce::GoodTilBlock(10)
+        ));
+        assert_eq!(OrderExpiries::<Test>::iter_prefix(10).count(), 1);
+        assert_eq!(OpenOrders::<Test>::iter_key_prefix((trader, pool_id.clone())).count(), 2);
+
+        // `ImmediateOrCancel` order never rests on the orderbook
+        assert_ok!(HybridOrderbook::limit_order(
+            RuntimeOrigin::signed(trader),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            true,
+            95,
+            10,
+            TimeInForce::ImmediateOrCancel
+        ));
+        assert_ok!(HybridOrderbook::limit_order(
+            RuntimeOrigin::signed(trader),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            true,
+            101,
+            10,
+            TimeInForce::ImmediateOrCancel
+        ));
+        assert_eq!(balance(trader, base.clone()), 10);
+        assert_eq!(frozen_balance(trader, quote.clone()), 990 + 980);
+        assert_eq!(OpenOrders::<Test>::iter_key_prefix((trader, pool_id.clone())).count(), 2);
+
+        // `FillOrKill` order reverts unless the whole quantity is filled
+        assert_noop!(
+            HybridOrderbook::limit_order(
+                RuntimeOrigin::signed(trader),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                true,
+                95,
+                10,
+                TimeInForce::FillOrKill
+            ),
+            Error::<Test>::OrderNotFilled
+        );
+        assert_ok!(HybridOrderbook::limit_order(
+            RuntimeOrigin::signed(trader),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            true,
+            101,
+            10,
+            TimeInForce::FillOrKill
+        ));
+        assert_eq!(balance(trader, base.clone()), 20);
+    })
+}
+
+#[test]
+fn crossing_limit_order_respects_limit_price() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let trader: MockAccountId = 3;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let pool_id = (base.clone(), quote.clone());
+        // Pool price => 100, ask order of 50 @ 101 placed by `maker`
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::zero(),
+            MakerFeeRate::default(),
+        );
+        assert_ok!(HybridOrderbook::limit_order(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            false,
+            105,
+            50,
+            TimeInForce::GoodTilCancel
+        ));
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 2, trader, 100000));
+        assert_ok!(HybridOrderbook::limit_order(
+            RuntimeOrigin::signed(trader),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            true,
+            101,
+            200,
+            TimeInForce::GoodTilCancel
+        ));
+        // Ask order @ 101 is filled while the one @ 105 is untouched
+        let pool = Pools::<Test>::get(&pool_id).unwrap();
+        assert_eq!(pool.next_ask_order().map(|(price, _)| price), Some(105));
+        assert_eq!(frozen_balance(maker, base.clone()), 50);
+        // Pool is swapped only up to the limit price
+        let (b_r, q_r) = HybridOrderbook::get_reserves(&base, &quote).unwrap();
+        assert!(HybridOrderbook::pool_price(&pool, &b_r, &q_r).unwrap() <= 101);
+        // Remain orders rest on the orderbook at the limit price
+        let bid_order_id = OpenOrders::<Test>::iter_key_prefix((trader, pool_id.clone()))
+            .next()
+            .unwrap();
+        assert_eq!(
+            OpenOrders::<Test>::get((trader, pool_id.clone(), bid_order_id)),
+            Some((101, 0))
+        );
+        let rested = pool.get_order(true, 101, bid_order_id).unwrap().quantity();
+        assert!(rested > 0 && rested < 150);
+        assert_eq!(balance(trader, base.clone()) + rested, 200);
+        assert_eq!(frozen_balance(trader, quote.clone()), 101 * rested);
+    })
+}
+
+#[test]
+fn market_order_slippage_protection_works() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let trader: MockAccountId = 3;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        // Pool price => 100, ask order of 50 @ 101 placed by `maker`
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::from_percent(1),
+            MakerFeeRate::default(),
+        );
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 2, trader, 100000));
+        // Buying 30 base costs more than 30 * 100 quote from both the pool and the orderbook
+        assert_noop!(
+            HybridOrderbook::market_order(
+                RuntimeOrigin::signed(trader),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                30,
+                true,
+                Some(3000),
+                None,
+            ),
+            Error::<Test>::ProvidedMaximumNotSufficientForSwap
+        );
+        assert_ok!(HybridOrderbook::market_order(
+            RuntimeOrigin::signed(trader),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            30,
+            true,
+            Some(3100),
+            None,
+        ));
+        assert_eq!(balance(trader, base.clone()), 30);
+        assert!(100000 - balance(trader, quote.clone()) <= 3100);
+
+        // Selling 30 base back can't receive as much as paid
+        assert_noop!(
+            HybridOrderbook::market_order(
+                RuntimeOrigin::signed(trader),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                30,
+                false,
+                Some(3000),
+                None,
+            ),
+            Error::<Test>::ProvidedMinimumNotSufficientForSwap
+        );
+
+        // Order is not valid after the deadline
+        System::set_block_number(5);
+        assert_noop!(
+            HybridOrderbook::market_order(
+                RuntimeOrigin::signed(trader),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                30,
+                false,
+                None,
+                Some(4),
+            ),
+            Error::<Test>::DeadlinePassed
+        );
+        assert_ok!(HybridOrderbook::market_order(
+            RuntimeOrigin::signed(trader),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            30,
+            false,
+            None,
+            Some(5),
+        ));
+        assert_eq!(balance(trader, base.clone()), 0);
+    })
+}
+
+#[test]
+fn market_order_with_quote_works() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let trader: MockAccountId = 3;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let pool_id = (base.clone(), quote.clone());
+        // Pool price => 100, ask order of 50 @ 101 placed by `maker`
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::from_percent(1),
+            MakerFeeRate::default(),
+        );
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 2, trader, 10000));
+        assert_noop!(
+            HybridOrderbook::market_order_with_quote(
+                RuntimeOrigin::signed(trader),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                3000,
+                Some(30),
+                None,
+            ),
+            Error::<Test>::ProvidedMinimumNotSufficientForSwap
+        );
+        assert_ok!(HybridOrderbook::market_order_with_quote(
+            RuntimeOrigin::signed(trader),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            3000,
+            Some(25),
+            None,
+        ));
+        // Quote which could not afford another lot is not spent
+        let spent = 10000 - balance(trader, quote.clone());
+        assert!(spent <= 3000 && spent > 3000 - 103);
+        let bought = balance(trader, base.clone());
+        assert!(bought >= 25 && bought < 30);
+        // Rest is filled from the ask order of `maker`
+        let order_id = OpenOrders::<Test>::iter_key_prefix((maker, pool_id.clone()))
+            .next()
+            .unwrap();
+        let pool = Pools::<Test>::get(&pool_id).unwrap();
+        let remaining = pool.get_order(false, 101, order_id).unwrap().quantity();
+        assert!(remaining < 50);
+        assert_eq!(balance(maker, quote.clone()), 101 * (50 - remaining));
+    })
+}
+
+#[test]
+fn orderbook_nodes_are_stored_per_price_level() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let pool_id = (base.clone(), quote.clone());
+        // Ask order of 50 @ 101 placed by `maker`
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::zero(),
+            MakerFeeRate::default(),
+        );
+        assert_eq!(LeafNodes::<Test>::iter().count(), 1);
+        assert_eq!(InternalNodes::<Test>::iter().count(), 0);
+
+        // Same price level is stored on the same leaf while new price level adds a leaf
+        for price in [101, 102] {
+            assert_ok!(HybridOrderbook::limit_order(
+                RuntimeOrigin::signed(maker),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                false,
+                price,
+                10,
+                TimeInForce::GoodTilCancel,
+            ));
+        }
+        assert_eq!(LeafNodes::<Test>::iter().count(), 2);
+        assert_eq!(InternalNodes::<Test>::iter().count(), 1);
+        let last_order_id = OpenOrders::<Test>::iter_key_prefix((maker, pool_id.clone()))
+            .max()
+            .unwrap();
+        let pool = Pools::<Test>::get(&pool_id).unwrap();
+        assert_eq!(pool.get_order(false, 102, last_order_id).unwrap().quantity(), 10);
+
+        // Emptied price levels are removed from the storage
+        assert_ok!(HybridOrderbook::cancel_all_orders(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+        ));
+        assert_eq!(LeafNodes::<Test>::iter().count(), 0);
+        assert_eq!(InternalNodes::<Test>::iter().count(), 0);
+        assert!(HybridOrderbook::get_pool_query(&base, &quote).is_ok());
+    });
+}
+
+#[test]
+fn orderbook_limits_work() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        // Ask order of 50 @ 101 placed by `maker`
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::zero(),
+            MakerFeeRate::default(),
+        );
+        let place_ask = |price: MockBalance| {
+            HybridOrderbook::limit_order(
+                RuntimeOrigin::signed(maker),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                false,
+                price,
+                10,
+                TimeInForce::GoodTilCancel,
+            )
+        };
+
+        // `MaxOrdersPerTick` is 10
+        for _ in 1..10 {
+            assert_ok!(place_ask(101));
+        }
+        assert_noop!(place_ask(101), Error::<Test>::TooManyOrdersPerTick);
+
+        // `MaxTicks` of each side
+        MaxTicks::set(&2);
+        assert_ok!(place_ask(102));
+        assert_noop!(place_ask(103), Error::<Test>::TooManyTicks);
+        MaxTicks::set(&1000);
+
+        // `MaxOpenOrders` of the account
+        assert_eq!(OpenOrdersCount::<Test>::get(maker), 11);
+        MaxOpenOrders::set(&11);
+        assert_noop!(place_ask(103), Error::<Test>::TooManyOpenOrders);
+
+        assert_ok!(HybridOrderbook::cancel_all_orders(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+        ));
+        assert!(!OpenOrdersCount::<Test>::contains_key(maker));
+        assert_ok!(place_ask(103));
+    });
+}
+
+#[test]
+fn migration_to_v1_moves_orderbooks_to_node_storage() {
+    use crate::migration::v1::{v0, MigrateToV1};
+    use frame_support::traits::{OnRuntimeUpgrade, StorageVersion};
+
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let pool_id = (base.clone(), quote.clone());
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::zero(),
+            MakerFeeRate::default(),
+        );
+        assert_ok!(HybridOrderbook::cancel_all_orders(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+        ));
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 2, maker, 1000));
+        let lp_token = Pools::<Test>::get(&pool_id).unwrap().lp_token();
+
+        // Orderbooks of v0 hold all of their nodes inline, whose assets are frozen on placing
+        let expired_at = 1 + OrderExpiration::get();
+        let ask_id = |n: u64| OrderId::from((1 << 63) + n);
+        let leaf = |price: MockBalance, orders: Vec<(OrderId, MockBalance)>| v0::LeafNode {
+            parent: 0,
+            key: price,
+            value: v0::Tick {
+                open_orders: orders
+                    .into_iter()
+                    .map(|(order_id, quantity)| (order_id, Order::new(maker, quantity, expired_at)))
+                    .collect(),
+            },
+        };
+        let orderbook = |leaves: Vec<v0::LeafNode<MockBalance, _>>| v0::CritbitTree {
+            root: 0,
+            internal_nodes: Default::default(),
+            leaves: leaves.into_iter().enumerate().map(|(i, leaf)| (i as u64, leaf)).collect(),
+            max_leaf_index: 0,
+            min_leaf_index: 0,
+            next_internal_node_index: 0,
+            next_leaf_node_index: 0,
+        };
+        // 11 asks @ 103 exceeds `MaxOrdersPerTick` of 10
+        let asks_at_103 = (2..13).map(|n| (ask_id(n), 10)).collect();
+        v0::Pools::<Test>::insert(
+            &pool_id,
+            v0::Pool {
+                lp_token,
+                bids: orderbook(vec![leaf(90, vec![(OrderId::from(0), 10)])]),
+                asks: orderbook(vec![
+                    leaf(101, vec![(ask_id(0), 10), (ask_id(1), 20)]),
+                    leaf(103, asks_at_103),
+                ]),
+                next_bid_order_id: OrderId::from(1),
+                next_ask_order_id: ask_id(13),
+                taker_fee_rate: Permill::from_percent(1),
+                tick_size: 1,
+                lot_size: 1,
+                pool_decimals: 0,
+                base_decimals: 9,
+                quote_decimals: 9,
+            },
+        );
+        for (asset, amount) in [(base.clone(), 140), (quote.clone(), 900)] {
+            assert_ok!(<<Test as Config>::AssetsFreezer as MutateFreeze<_>>::increase_frozen(
+                asset,
+                &FreezeReason::LimitOrder.into(),
+                &maker,
+                amount,
+            ));
+        }
+        StorageVersion::new(0).put::<HybridOrderbook>();
+
+        #[cfg(feature = "try-runtime")]
+        assert_ok!(MigrateToV1::<Test>::try_on_runtime_upgrade(true));
+        #[cfg(not(feature = "try-runtime"))]
+        MigrateToV1::<Test>::on_runtime_upgrade();
+
+        assert_eq!(StorageVersion::get::<HybridOrderbook>(), 1);
+        let pool = Pools::<Test>::get(&pool_id).unwrap();
+        assert_eq!(pool.base_asset, base);
+        assert_eq!(pool.quote_asset, quote);
+        assert_eq!(pool.maker_fee_rate(), MakerFeeRate::default());
+        assert_eq!(pool.taker_fee(100), 1);
+        assert_eq!(LeafNodes::<Test>::iter().count(), 3);
+        assert_eq!(pool.get_order(false, 101, ask_id(1)).unwrap().quantity(), 20);
+        assert_eq!(pool.get_order(true, 90, OrderId::from(0)).unwrap().quantity(), 10);
+        // Orders are indexed while the one which doesn't fit into the price level is dropped
+        assert_eq!(open_orders_of(maker, &base, &quote, true).len(), 1);
+        assert_eq!(open_orders_of(maker, &base, &quote, false).len(), 12);
+        assert_eq!(OpenOrdersCount::<Test>::get(maker), 13);
+        assert_eq!(OrderExpiries::<Test>::iter_prefix(expired_at).count(), 13);
+        assert!(pool.get_order(false, 103, ask_id(12)).is_none());
+        assert_eq!(frozen_balance(maker, base.clone()), 130);
+        assert_eq!(frozen_balance(maker, quote.clone()), 900);
+
+        // New order continues from the order id of v0
+        assert_ok!(HybridOrderbook::limit_order(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            false,
+            104,
+            10,
+            TimeInForce::GoodTilCancel,
+        ));
+        assert!(OpenOrders::<Test>::contains_key((maker, pool_id.clone(), ask_id(13))));
+
+        // Orders of v0 are cancelled like any other order
+        assert_ok!(HybridOrderbook::cancel_all_orders(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+        ));
+        assert_eq!(frozen_balance(maker, base.clone()), 0);
+        assert_eq!(frozen_balance(maker, quote.clone()), 0);
+    });
+}
+
+#[test]
+fn order_deposit_works() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let taker: MockAccountId = 3;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let held_deposit =
+            |who: MockAccountId| Balances::balance_on_hold(&HoldReason::OrderDeposit.into(), &who);
+        OrderDeposit::set(&10);
+        // Ask order of 50 @ 101 placed by `maker`
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::zero(),
+            MakerFeeRate::default(),
+        );
+        assert_eq!(held_deposit(maker), 10);
+        assert_ok!(HybridOrderbook::limit_order(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            false,
+            102,
+            10,
+            TimeInForce::GoodTilCancel,
+        ));
+        assert_eq!(held_deposit(maker), 20);
+
+        // Deposit held when placed is released, even though `OrderDeposit` has been changed
+        OrderDeposit::set(&30);
+        let pool_id = (base.clone(), quote.clone());
+        let ask_102 = OpenOrders::<Test>::iter_prefix((maker, pool_id.clone()))
+            .find(|(_, (price, _))| *price == 102)
+            .map(|(order_id, _)| order_id)
+            .unwrap();
+        assert_eq!(OpenOrders::<Test>::get((maker, pool_id.clone(), ask_102)), Some((102, 10)));
+
+        // Deposit is released once the order is fully filled
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 2, taker, 20000));
+        let taker_held = held_deposit(taker);
+        assert_ok!(HybridOrderbook::market_order(
+            RuntimeOrigin::signed(taker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            60,
+            true,
+            None,
+            None,
+        ));
+        // Order @ 101 is filled, while the one @ 102 is left untouched
+        let open_orders: Vec<_> =
+            OpenOrders::<Test>::iter_key_prefix((maker, pool_id.clone())).collect();
+        assert_eq!(open_orders, vec![ask_102]);
+        assert_eq!(OpenOrdersCount::<Test>::get(maker), 1);
+        assert_eq!(held_deposit(maker), 10);
+        // Market order doesn't hold the deposit
+        assert_eq!(held_deposit(taker), taker_held);
+
+        // Deposit is released once the order is cancelled
+        assert_ok!(HybridOrderbook::cancel_all_orders(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+        ));
+        assert_eq!(held_deposit(maker), 0);
+    });
+}
+
+#[test]
+fn orders_filled_limit_works() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let taker: MockAccountId = 3;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        // Ask order of 50 @ 101 placed by `maker`
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::zero(),
+            MakerFeeRate::default(),
+        );
+        for price in [102, 103] {
+            assert_ok!(HybridOrderbook::limit_order(
+                RuntimeOrigin::signed(maker),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                false,
+                price,
+                10,
+                TimeInForce::GoodTilCancel,
+            ));
+        }
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 2, taker, 20000));
+        let market_buy = || {
+            HybridOrderbook::market_order(
+                RuntimeOrigin::signed(taker),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                100,
+                true,
+                None,
+                None,
+            )
+        };
+
+        // Crossing the third price level exceeds `MaxOrdersFilled`
+        MaxOrdersFilled::set(&2);
+        assert_noop!(market_buy(), Error::<Test>::TooManyOrdersFilled);
+        MaxOrdersFilled::set(&100);
+
+        // Weight is refunded down to the orders filled
+        let max = HybridOrderbook::max_match_count();
+        let post_info = market_buy().unwrap();
+        assert_eq!(post_info.actual_weight, Some(<() as WeightInfo>::market_order(3, 3)));
+        assert!(
+            <() as WeightInfo>::market_order(3, 3)
+                .all_lt(<() as WeightInfo>::market_order(max.levels, max.orders))
+        );
+        let open_orders =
+            OpenOrders::<Test>::iter_prefix((maker, (base.clone(), quote.clone()))).count();
+        assert_eq!(open_orders, 0);
+    });
+}
+
+#[test]
+fn expired_orders_are_swept_on_idle() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let pool_id = (base.clone(), quote.clone());
+        pool_with_default_liquidity(
+            initial_provider,
+            &base,
+            &quote,
+            50,
+            1000,
+            100000,
+            1,
+            1,
+        );
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 1, maker, 1000));
+        let ask_price = 101;
+        let ask_order_id: OrderId = 9223372036854775908.into();
+        assert_ok!(HybridOrderbook::limit_order(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            false,
+            ask_price,
+            50,
+            TimeInForce::GoodTilCancel
+        ));
+        assert_eq!(frozen_balance(maker, base.clone()), 50);
+        // 99 bids and 100 asks of provider + 1 ask of maker, all placed at block 1
+        let expired_at = 1 + OrderExpiration::get();
+        assert_eq!(OrderExpiries::<Test>::iter_prefix(expired_at).count(), 200);
+
+        // Nothing is expired yet
+        System::set_block_number(expired_at - 1);
+        HybridOrderbook::on_idle(expired_at - 1, Weight::MAX);
+        assert_eq!(OrderExpiries::<Test>::iter_prefix(expired_at).count(), 200);
+        assert_eq!(frozen_balance(maker, base.clone()), 50);
+
+        // Sweep is bounded by `MaxExpiredOrdersPerBlock`
+        System::set_block_number(expired_at);
+        HybridOrderbook::on_idle(expired_at, Weight::MAX);
+        assert_eq!(OrderExpiries::<Test>::iter_prefix(expired_at).count(), 100);
+        System::set_block_number(expired_at + 1);
+        HybridOrderbook::on_idle(expired_at + 1, Weight::MAX);
+        assert_eq!(OrderExpiries::<Test>::iter_prefix(expired_at).count(), 0);
+        assert_eq!(ExpirySweepCursor::<Test>::get(), Some(expired_at + 1));
+
+        assert!(events().contains(&Event::<Test>::OrderExpired {
+            pool_id: pool_id.clone(),
+            owner: maker,
+            order_id: ask_order_id,
+            released: 50,
+        }));
+        assert_eq!(frozen_balance(maker, base.clone()), 0);
+        assert_eq!(frozen_balance(initial_provider, base.clone()), 0);
+        assert_eq!(frozen_balance(initial_provider, quote.clone()), 0);
+        let pool = Pools::<Test>::get(&pool_id).unwrap();
+        assert!(pool.next_bid_order().is_none());
+        assert!(pool.next_ask_order().is_none());
+    })
+}
+
+#[test]
+fn expired_orders_are_not_matched() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let taker: MockAccountId = 3;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let pool_id = (base.clone(), quote.clone());
+        pool_with_default_liquidity(
+            initial_provider,
+            &base,
+            &quote,
+            50,
+            1000,
+            100000,
+            1,
+            1,
+        );
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 1, maker, 1000));
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 2, taker, 10000));
+        let ask_price = 101;
+        let ask_order_id: OrderId = 9223372036854775908.into();
+        assert_ok!(HybridOrderbook::limit_order(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            false,
+            ask_price,
+            50,
+            TimeInForce::GoodTilCancel
+        ));
+
+        // Orders are expired but not swept yet
+        System::set_block_number(1 + OrderExpiration::get());
+        assert_ok!(HybridOrderbook::market_order(
+            RuntimeOrigin::signed(taker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            10,
+            true,
+            None,
+            None,
+        ));
+        assert!(events().contains(&Event::<Test>::OrderExpired {
+            pool_id: pool_id.clone(),
+            owner: maker,
+            order_id: ask_order_id,
+            released: 50,
+        }));
+        // Expired ask order of maker is not matched and its asset is released
+        assert_eq!(balance(maker, base.clone()), 1000);
+        assert_eq!(frozen_balance(maker, base.clone()), 0);
+        // Filled from the pool
+        assert_eq!(balance(taker, base.clone()), 10);
+        let pool = Pools::<Test>::get(&pool_id).unwrap();
+        assert!(pool.next_ask_order().is_none());
+        assert!(open_orders_of(maker, &base, &quote, false).is_empty());
+    })
+}
+
+/// Create the pool of `base` and `quote` with given fee rates where pool price is `100`. `maker`
+/// places ask order of 50 at 101.
+fn pool_with_fee_rates(
+    provider: MockAccountId,
+    maker: MockAccountId,
+    base: &NativeOrWithId<u32>,
+    quote: &NativeOrWithId<u32>,
+    taker_fee_rate: Permill,
+    maker_fee_rate: MakerFeeRate,
+) {
+    create_tokens(provider, vec![base.clone(), quote.clone()]);
+    assert_ok!(Balances::force_set_balance(
+        RuntimeOrigin::root(),
+        provider,
+        10000 * 2 + get_native_ed()
+    ));
+    assert_ok!(HybridOrderbook::create_pool(
+        RuntimeOrigin::signed(provider),
+        Box::new(base.clone()),
+        9,
+        Box::new(quote.clone()),
+        9,
+        taker_fee_rate,
+        maker_fee_rate,
+        1,
+        1,
+        0,
+        LiquidityMode::ConstantProduct,
+    ));
+    assert_ok!(Assets::mint(RuntimeOrigin::signed(provider), 1, provider, 1000));
+    assert_ok!(Assets::mint(RuntimeOrigin::signed(provider), 2, provider, 100000));
+    assert_ok!(HybridOrderbook::add_liquidity(
+        RuntimeOrigin::signed(provider),
+        Box::new(base.clone()),
+        Box::new(quote.clone()),
+        1000,
+        100000,
+        1000,
+        100000,
+        provider,
+    ));
+    assert_ok!(Assets::mint(RuntimeOrigin::signed(provider), 1, maker, 1000));
+    assert_ok!(HybridOrderbook::limit_order(
+        RuntimeOrigin::signed(maker),
+        Box::new(base.clone()),
+        Box::new(quote.clone()),
+        false,
+        101,
+        50,
+        TimeInForce::GoodTilCancel
+    ));
+}
+
+#[test]
+fn taker_fee_is_charged_on_book_fills() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let taker: MockAccountId = 3;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let fee_collector = HybridOrderbookOrigin::get();
+        // 1% of taker fee
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::from_percent(1),
+            MakerFeeRate::default(),
+        );
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 2, taker, 20000));
+
+        // Buy through the pool, the whole ask order of maker and the pool again
+        assert_ok!(HybridOrderbook::market_order(
+            RuntimeOrigin::signed(taker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            100,
+            true,
+            None,
+            None,
+        ));
+        // Maker receives the full `p*q` amount of quote asset
+        assert_eq!(balance(maker, base.clone()), 950);
+        assert_eq!(balance(maker, quote.clone()), 101 * 50);
+        assert_eq!(frozen_balance(maker, base.clone()), 0);
+        // Taker pays 1% of `p*q`(5050) rounded up, which goes to `TakerFeeTarget`
+        assert_eq!(balance(taker, base.clone()), 100);
+        assert_eq!(balance(fee_collector, quote.clone()), 51);
+        assert!(events().contains(&Event::<Test>::OrderMatched {
+            orderer: taker,
+            filled: 100,
+            is_bid: true,
+            fee: 51,
+        }));
+    })
+}
+
+#[test]
+fn quote_market_order_works() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let taker: MockAccountId = 3;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::from_percent(1),
+            MakerFeeRate::default(),
+        );
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 2, taker, 20000));
+        assert_noop!(
+            HybridOrderbook::quote_market_order(&base, &NativeOrWithId::WithId(3), 100, true),
+            Error::<Test>::PoolNotFound
+        );
+        assert_noop!(
+            HybridOrderbook::quote_market_order(&base, &quote, 0, true),
+            Error::<Test>::InvalidOrderQuantity
+        );
+
+        // Matched from the pool, the ask order of maker and the pool again
+        let quoted = HybridOrderbook::quote_market_order(&base, &quote, 100, true).unwrap();
+        assert_ok!(HybridOrderbook::market_order(
+            RuntimeOrigin::signed(taker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            100,
+            true,
+            None,
+            None,
+        ));
+        assert_eq!(balance(taker, base.clone()), 100);
+        assert_eq!(20000 - balance(taker, quote.clone()), quoted);
+
+        // Matched from the pool only since there is no bid order
+        let quoted = HybridOrderbook::quote_market_order(&base, &quote, 50, false).unwrap();
+        let before = balance(taker, quote.clone());
+        assert_ok!(HybridOrderbook::market_order(
+            RuntimeOrigin::signed(taker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            50,
+            false,
+            None,
+            None,
+        ));
+        assert_eq!(balance(taker, quote.clone()) - before, quoted);
+    })
+}
+
+#[test]
+fn get_depth_works() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let trader: MockAccountId = 3;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        // Pool price => 100, ask order of 50 @ 101 placed by `maker`
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::zero(),
+            MakerFeeRate::default(),
+        );
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 2, trader, 100000));
+        for (who, is_bid, price, quantity) in vec![
+            (maker, false, 101, 20),
+            (maker, false, 103, 10),
+            (trader, true, 99, 30),
+            (trader, true, 97, 10),
+            (trader, true, 98, 5),
+        ] {
+            assert_ok!(HybridOrderbook::limit_order(
+                RuntimeOrigin::signed(who),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                is_bid,
+                price,
+                quantity,
+                TimeInForce::GoodTilCancel
+            ));
+        }
+        let depth = HybridOrderbook::get_depth(&base, &quote, 2).unwrap();
+        assert_eq!(depth.bids, vec![(99, 30, 1), (98, 5, 1)]);
+        assert_eq!(depth.asks, vec![(101, 70, 2), (103, 10, 1)]);
+        let depth = HybridOrderbook::get_depth(&base, &quote, 10).unwrap();
+        assert_eq!(depth.bids.len(), 3);
+        assert_eq!(depth.asks.len(), 2);
+        assert_noop!(
+            HybridOrderbook::get_depth(&base, &NativeOrWithId::WithId(3), 10),
+            Error::<Test>::PoolNotFound
+        );
+        let query = HybridOrderbook::get_pool_query(&base, &quote).unwrap();
+        assert_eq!(
+            HybridOrderbook::get_pool_reserves(&base, &quote),
+            Ok(PoolReserves {
+                base_reserve: query.base_reserve,
+                quote_reserve: query.quote_reserve,
+                pool_price: query.pool_price,
+            })
+        );
+    })
+}
+
+#[test]
+fn open_orders_works() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let trader: MockAccountId = 3;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let pool_id = (base.clone(), quote.clone());
+        let expired_at = 1 + OrderExpiration::get();
+        // Pool price => 100, ask order of 50 @ 101 placed by `maker`
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::zero(),
+            MakerFeeRate::default(),
+        );
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 2, trader, 100000));
+        assert_ok!(HybridOrderbook::limit_order(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            false,
+            103,
+            20,
+            TimeInForce::GoodTilCancel
+        ));
+        assert_ok!(HybridOrderbook::limit_order(
+            RuntimeOrigin::signed(trader),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            true,
+            99,
+            30,
+            TimeInForce::GoodTilCancel
+        ));
+
+        let mut orders = HybridOrderbook::open_orders(&maker, None).unwrap();
+        orders.sort_by_key(|order| order.price);
+        assert_eq!(orders.len(), 2);
+        assert_eq!(
+            orders.iter().map(|o| (o.is_bid, o.price, o.quantity)).collect::<Vec<_>>(),
+            vec![(false, 101, 50), (false, 103, 20)]
+        );
+        assert!(orders.iter().all(|o| o.pool_id == pool_id && o.expired_at == expired_at));
+        assert_eq!(
+            HybridOrderbook::open_orders(&maker, Some((&base, &quote))).unwrap().len(),
+            2
+        );
+        let bids = HybridOrderbook::open_orders(&trader, Some((&base, &quote))).unwrap();
+        assert_eq!(bids.len(), 1);
+        assert!(bids[0].is_bid);
+        assert_eq!((bids[0].price, bids[0].quantity), (99, 30));
+        assert!(HybridOrderbook::open_orders(&initial_provider, None).unwrap().is_empty());
+        assert_noop!(
+            HybridOrderbook::open_orders(&maker, Some((&base, &NativeOrWithId::WithId(3)))),
+            Error::<Test>::PoolNotFound
+        );
+
+        // Remaining quantity is updated and cancelled order is removed
+        assert_ok!(HybridOrderbook::amend_order(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            orders[1].order_id,
+            103,
+            10,
+        ));
+        assert_ok!(HybridOrderbook::cancel_order_by_id(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            orders[0].order_id,
+        ));
+        let orders = HybridOrderbook::open_orders(&maker, None).unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!((orders[0].price, orders[0].quantity), (103, 10));
+    })
+}
+
+#[test]
+fn trade_events_and_recent_trades_work() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let taker: MockAccountId = 3;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let pool_id = (base.clone(), quote.clone());
+        // Keep only the latest 2 trades
+        MaxRecentTrades::set(&2);
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::from_percent(1),
+            MakerFeeRate::default(),
+        );
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 2, taker, 20000));
+        let ask_order_id = HybridOrderbook::open_orders(&maker, None).unwrap()[0].order_id;
+        events();
+
+        // Buy through the pool, the whole ask order of maker and the pool again
+        assert_ok!(HybridOrderbook::market_order(
+            RuntimeOrigin::signed(taker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            100,
+            true,
+            None,
+            None,
+        ));
+        let pool_account = <Test as Config>::PoolLocator::address(&pool_id).unwrap();
+        let trades: Vec<_> = events()
+            .into_iter()
+            .filter(|e| matches!(e, Event::<Test>::Trade { .. }))
+            .collect();
+        assert_eq!(trades.len(), 3);
+        assert_eq!(
+            trades[1],
+            Event::<Test>::Trade {
+                pool_id: pool_id.clone(),
+                maker,
+                taker,
+                order_id: Some(ask_order_id),
+                is_bid: true,
+                price: 101,
+                quantity: 50,
+                venue: Venue::Book,
+                fee: 51,
+            }
+        );
+        for trade in [&trades[0], &trades[2]] {
+            assert!(matches!(
+                trade,
+                Event::<Test>::Trade { maker, venue: Venue::Pool, fee: 0, .. }
+                    if *maker == pool_account
+            ));
+        }
+
+        // Latest first, the oldest trade is overwritten
+        assert_eq!(TradeCount::<Test>::get(&pool_id), 3);
+        let recent = HybridOrderbook::recent_trades(&base, &quote, 10).unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].venue, Venue::Pool);
+        assert_eq!(recent[0].maker, pool_account);
+        assert_eq!(recent[1].venue, Venue::Book);
+        assert_eq!((recent[1].maker, recent[1].price, recent[1].quantity), (maker, 101, 50));
+        assert_eq!(recent[1].block, System::block_number());
+        assert_eq!(HybridOrderbook::recent_trades(&base, &quote, 1).unwrap().len(), 1);
+        assert_noop!(
+            HybridOrderbook::recent_trades(&base, &NativeOrWithId::WithId(3), 10),
+            Error::<Test>::PoolNotFound
+        );
+    })
+}
+
+#[test]
+fn candles_work() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let taker: MockAccountId = 3;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        // Keep only the latest 2 candles of each resolution
+        MaxCandles::set(&2);
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::from_percent(1),
+            MakerFeeRate::default(),
+        );
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 2, taker, 20000));
+
+        // Buy through the pool, the whole ask order of maker and the pool again
+        assert_ok!(HybridOrderbook::market_order(
+            RuntimeOrigin::signed(taker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            100,
+            true,
+            None,
+            None,
+        ));
+        let trades = HybridOrderbook::recent_trades(&base, &quote, 10).unwrap();
+        let prices: Vec<_> = trades.iter().rev().map(|trade| trade.price).collect();
+        let candles = HybridOrderbook::candles(&base, &quote, 10, 0, 9).unwrap();
+        assert_eq!(
+            candles,
+            vec![Candle {
+                start: 0,
+                open: prices[0],
+                high: *prices.iter().max().unwrap(),
+                low: *prices.iter().min().unwrap(),
+                close: prices[2],
+                volume: 100,
+                trades: 3,
+            }]
+        );
+        assert!(candles[0].high >= 101);
+
+        // Sell on the next buckets of 10 blocks
+        for block in [15, 25] {
+            System::set_block_number(block);
+            assert_ok!(HybridOrderbook::market_order(
+                RuntimeOrigin::signed(taker),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                10,
+                false,
+                None,
+                None,
+            ));
+        }
+        let last_price = HybridOrderbook::recent_trades(&base, &quote, 1).unwrap()[0].price;
+        // The candle of the first bucket is overwritten
+        let candles = HybridOrderbook::candles(&base, &quote, 10, 0, 30).unwrap();
+        assert_eq!(candles.iter().map(|candle| candle.start).collect::<Vec<_>>(), vec![10, 20]);
+        assert_eq!((candles[1].close, candles[1].volume, candles[1].trades), (last_price, 10, 1));
+        assert_eq!(HybridOrderbook::candles(&base, &quote, 10, 20, 30).unwrap().len(), 1);
+        // All trades are on the first bucket of 600 blocks
+        let candles = HybridOrderbook::candles(&base, &quote, 600, 0, 30).unwrap();
+        assert_eq!(candles.len(), 1);
+        assert_eq!((candles[0].open, candles[0].close), (prices[0], last_price));
+        assert_eq!((candles[0].volume, candles[0].trades), (120, 5));
+        assert_noop!(
+            HybridOrderbook::candles(&base, &quote, 60, 0, 30),
+            Error::<Test>::InvalidCandleResolution
+        );
+    })
+}
+
+#[test]
+fn twap_works() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let taker: MockAccountId = 3;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::zero(),
+            MakerFeeRate::default(),
+        );
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 1, taker, 1000));
+        let pool_price =
+            || HybridOrderbook::get_pool_query(&base, &quote).unwrap().pool_price;
+        let sell = |quantity| {
+            assert_ok!(HybridOrderbook::market_order(
+                RuntimeOrigin::signed(taker),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                quantity,
+                false,
+                None,
+                None,
+            ));
+        };
+
+        // Zero window is rejected, no observation yet for the others
+        assert_noop!(HybridOrderbook::twap(&base, &quote, 0), Error::<Test>::ZeroTwapWindow);
+        assert_noop!(
+            HybridOrderbook::twap(&base, &quote, 1),
+            Error::<Test>::InsufficientPriceHistory
+        );
+
+        // First observation on the swap
+        System::set_block_number(10);
+        sell(10);
+        let p1 = pool_price();
+        assert_eq!(PriceObservationCount::<Test>::get((base.clone(), quote.clone())), 1);
+        System::set_block_number(20);
+        assert_eq!(HybridOrderbook::twap(&base, &quote, 5), Ok(p1));
+        assert_eq!(HybridOrderbook::twap(&base, &quote, 10), Ok(p1));
+        assert_noop!(
+            HybridOrderbook::twap(&base, &quote, 11),
+            Error::<Test>::InsufficientPriceHistory
+        );
+
+        // Price moved within the block is not counted until the next block
+        sell(300);
+        let p2 = pool_price();
+        assert!(p2 < p1);
+        assert_eq!(HybridOrderbook::twap(&base, &quote, 5), Ok(p1));
+        System::set_block_number(30);
+        assert_eq!(HybridOrderbook::twap(&base, &quote, 10), Ok(p2));
+        assert_eq!(HybridOrderbook::twap(&base, &quote, 20), Ok((p1 + p2) / 2));
+        assert_eq!(
+            <HybridOrderbook as PriceOracle<_, _, _>>::twap(&base, &quote, 15),
+            Ok((p1 * 5 + p2 * 10) / 15)
+        );
+    })
+}
+
+#[test]
+fn twap_searches_wrapped_observations() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let taker: MockAccountId = 3;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let pool_id = (base.clone(), quote.clone());
+        MaxPriceObservations::set(&3);
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::zero(),
+            MakerFeeRate::default(),
+        );
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 1, taker, 1000));
+        let pool_price =
+            || HybridOrderbook::get_pool_query(&base, &quote).unwrap().pool_price;
+
+        // Observations of the blocks 10, 20, 30 and 40, where the first one is overwritten
+        let mut prices = vec![];
+        for block in [10, 20, 30, 40] {
+            System::set_block_number(block);
+            assert_ok!(HybridOrderbook::market_order(
+                RuntimeOrigin::signed(taker),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                10,
+                false,
+                None,
+                None,
+            ));
+            prices.push(pool_price());
+        }
+        assert_eq!(PriceObservationCount::<Test>::get(&pool_id), 4);
+        System::set_block_number(50);
+        let (p2, p3, p4) = (prices[1], prices[2], prices[3]);
+        let assert_twap = || {
+            assert_eq!(HybridOrderbook::twap(&base, &quote, 30), Ok((p2 + p3 + p4) / 3));
+            assert_eq!(
+                HybridOrderbook::twap(&base, &quote, 25),
+                Ok((p2 * 5 + p3 * 10 + p4 * 10) / 25)
+            );
+            assert_eq!(HybridOrderbook::twap(&base, &quote, 5), Ok(p4));
+            assert_noop!(
+                HybridOrderbook::twap(&base, &quote, 31),
+                Error::<Test>::InsufficientPriceHistory
+            );
+        };
+        assert_twap();
+
+        // Cumulative price wrapped around `u64::MAX` gives the same average
+        for slot in 0..3 {
+            PriceObservations::<Test>::mutate(&pool_id, slot, |observation| {
+                let observation = observation.as_mut().unwrap();
+                observation.price_cumulative =
+                    observation.price_cumulative.wrapping_add(u64::MAX - 100);
+            });
+        }
+        assert_twap();
+    })
+}
+
+#[test]
+fn price_feed_works() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let taker: MockAccountId = 3;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let pool_id = (base.clone(), quote.clone());
+        let price = |n| Some(FixedU128::from_u32(n));
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::zero(),
+            MakerFeeRate::default(),
+        );
+
+        // Spot price without any bid order and trade
+        let info = HybridOrderbook::price(&base, &quote).unwrap();
+        assert_eq!(Some(info.price), price(100));
+        assert_eq!(Some(info.pool_price), price(100));
+        assert_eq!((info.best_bid, info.best_ask, info.mid), (None, price(101), None));
+        assert_eq!(info.last_trade, None);
+        OraclePriceSource::set(&PriceSource::Mid);
+        assert_eq!(HybridOrderbook::price(&base, &quote), None);
+        assert_noop!(
+            HybridOrderbook::price_info(&base, &quote),
+            Error::<Test>::PriceNotAvailable
+        );
+
+        // Mid price of the best bid and ask
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 2, maker, 10000));
+        assert_ok!(HybridOrderbook::limit_order(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            true,
+            97,
+            10,
+            TimeInForce::GoodTilCancel
+        ));
+        let info = HybridOrderbook::price(&base, &quote).unwrap();
+        assert_eq!((info.best_bid, info.mid), (price(97), price(99)));
+        assert_eq!(Some(info.price), price(99));
+
+        // Block of the last trade
+        System::set_block_number(5);
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 2, taker, 10000));
+        assert_ok!(HybridOrderbook::market_order(
+            RuntimeOrigin::signed(taker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            1,
+            true,
+            None,
+            None,
+        ));
+        assert_eq!(HybridOrderbook::price(&base, &quote).unwrap().last_trade, Some(5));
+
+        // Time weighted average pool price
+        OraclePriceSource::set(&PriceSource::Twap(10));
+        assert_eq!(HybridOrderbook::price(&base, &quote), None);
+        System::set_block_number(15);
+        let info = HybridOrderbook::price(&base, &quote).unwrap();
+        assert_eq!(info.price, info.pool_price);
+
+        // Normalised by `pool_decimals`
+        OraclePriceSource::set(&PriceSource::Spot);
+        Pools::<Test>::mutate(&pool_id, |pool| pool.as_mut().unwrap().pool_decimals = 2);
+        let info = HybridOrderbook::price(&base, &quote).unwrap();
+        assert_eq!(Some(info.best_bid.unwrap() * FixedU128::from_u32(100)), price(97));
+        let pool_price = HybridOrderbook::get_pool_query(&base, &quote).unwrap().pool_price;
+        assert_eq!(Some(info.pool_price), FixedU128::checked_from_rational(pool_price, 100));
+
+        // Price levels of the expired orders are skipped
+        assert_ok!(HybridOrderbook::limit_order(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            true,
+            96,
+            10,
+            TimeInForce::GoodTilBlock(110)
+        ));
+        System::set_block_number(101);
+        let info = HybridOrderbook::price(&base, &quote).unwrap();
+        assert_eq!(info.best_bid, FixedU128::checked_from_rational(96, 100));
+        assert_eq!((info.best_ask, info.mid), (None, None));
+        System::set_block_number(110);
+        assert_eq!(HybridOrderbook::price(&base, &quote).unwrap().best_bid, None);
+    })
+}
+
+#[test]
+fn maker_fee_and_rebate_works() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let taker: MockAccountId = 3;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let fee_collector = HybridOrderbookOrigin::get();
+        // 0.5% of rebate funded by 1% of taker fee
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::from_percent(1),
+            MakerFeeRate::Rebate(Permill::from_rational(5u32, 1000u32)),
+        );
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 2, taker, 20000));
+        assert_ok!(HybridOrderbook::market_order(
+            RuntimeOrigin::signed(taker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            100,
+            true,
+            None,
+            None,
+        ));
+        // Rebate => 0.5% of 5050 rounded down
+        assert_eq!(balance(maker, quote.clone()), 101 * 50 + 25);
+        assert_eq!(balance(fee_collector, quote.clone()), 51 - 25);
+    });
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let taker: MockAccountId = 3;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let fee_collector = HybridOrderbookOrigin::get();
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::from_percent(1),
+            MakerFeeRate::Fee(Permill::from_percent(1)),
+        );
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 2, taker, 20000));
+        assert_ok!(HybridOrderbook::market_order(
+            RuntimeOrigin::signed(taker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            100,
+            true,
+            None,
+            None,
+        ));
+        // Maker pays 1% of received quote asset rounded up
+        assert_eq!(balance(maker, quote.clone()), 101 * 50 - 51);
+        assert_eq!(balance(fee_collector, quote.clone()), 51 + 51);
+    });
+}
+
+#[test]
+fn rebate_over_taker_fee_rate_fails() {
+    new_test_ext().execute_with(|| {
+        let user: MockAccountId = 1;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        create_tokens(user, vec![base.clone(), quote.clone()]);
+        assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 1000));
+        assert_noop!(
+            HybridOrderbook::create_pool(
+                RuntimeOrigin::signed(user),
+                Box::new(base.clone()),
+                9,
+                Box::new(quote.clone()),
+                9,
+                Permill::from_percent(1),
+                MakerFeeRate::Rebate(Permill::from_percent(2)),
+                1,
+                1,
+                0,
+                LiquidityMode::ConstantProduct,
+            ),
+            Error::<Test>::InvalidMakerFeeRate
+        );
+    })
+}
+
+#[test]
+fn stop_limit_order_works() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let taker: MockAccountId = 3;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let pool_id = (base.clone(), quote.clone());
+        // Pool price => 100. Maker has 50 of ask order at 101.
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::zero(),
+            MakerFeeRate::default(),
+        );
+        // Stop price should be below the pool price for `ask`
+        assert_noop!(
+            HybridOrderbook::stop_limit_order(
+                RuntimeOrigin::signed(maker),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                false,
+                100,
+                98,
+                10
+            ),
+            Error::<Test>::InvalidStopPrice
+        );
+        // Stop loss
+        assert_ok!(HybridOrderbook::stop_limit_order(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            false,
+            99,
+            98,
+            10
+        ));
+        assert!(events().contains(&Event::<Test>::StopLimitOrderPlaced {
+            pool_id: pool_id.clone(),
+            owner: maker,
+            stop_order_id: 0,
+            stop_price: 99,
+            limit_price: 98,
+            quantity: 10,
+            is_bid: false,
+        }));
+        // Collateral is frozen on placement, and counted as the open order
+        assert_eq!(frozen_balance(maker, base.clone()), 50 + 10);
+        assert_eq!(OpenOrdersCount::<Test>::get(maker), 2);
+
+        // Pool price goes down under the stop price
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 1, taker, 100));
+        let post_info = HybridOrderbook::market_order(
+            RuntimeOrigin::signed(taker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            30,
+            false,
+            None,
+            None,
+        )
+        .unwrap();
+        // Triggered order is charged to the taker
+        let triggered = <() as WeightInfo>::cancel_stop_limit_order(1)
+            .saturating_add(<() as WeightInfo>::limit_order(0, 0));
+        assert!(post_info
+            .actual_weight
+            .unwrap()
+            .all_gte(<() as WeightInfo>::market_order(0, 0).saturating_add(triggered)));
+        assert!(events().contains(&Event::<Test>::StopLimitOrderTriggered {
+            pool_id: pool_id.clone(),
+            owner: maker,
+            stop_order_id: 0,
+            stop_price: 99,
+            is_bid: false,
+            result: Ok(()),
+        }));
+        assert!(TriggerBooks::<Test>::get(&pool_id).is_empty());
+        assert_eq!(StopOrders::<Test>::get(&pool_id, 0), None);
+        // Converted to the limit order which is placed on the orderbook
+        assert_eq!(open_orders_of(maker, &base, &quote, false).len(), 2);
+        assert_eq!(frozen_balance(maker, base.clone()), 50 + 10);
+        assert_eq!(OpenOrdersCount::<Test>::get(maker), 2);
+    })
+}
+
+#[test]
+fn cancel_stop_limit_order_works() {
+    new_test_ext().execute_with(|| {
+        let initial_provider: MockAccountId = 1;
+        let maker: MockAccountId = 2;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let pool_id = (base.clone(), quote.clone());
+        pool_with_fee_rates(
+            initial_provider,
+            maker,
+            &base,
+            &quote,
+            Permill::zero(),
+            MakerFeeRate::default(),
+        );
+        let held_deposit =
+            |who: MockAccountId| Balances::balance_on_hold(&HoldReason::OrderDeposit.into(), &who);
+        OrderDeposit::set(&10);
+        MaxStopOrders::set(&1);
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(initial_provider), 2, maker, 10000));
+        assert_ok!(HybridOrderbook::stop_limit_order(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            true,
+            105,
+            106,
+            10
+        ));
+        assert_eq!(frozen_balance(maker, quote.clone()), 106 * 10);
+        assert_eq!(held_deposit(maker), 10);
+        assert_eq!(OpenOrdersCount::<Test>::get(maker), 2);
+        // Trigger book of the pool is full
+        assert_noop!(
+            HybridOrderbook::stop_limit_order(
+                RuntimeOrigin::signed(maker),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                true,
+                105,
+                106,
+                10
+            ),
+            Error::<Test>::TooManyStopOrders
+        );
+        assert_noop!(
+            HybridOrderbook::cancel_stop_limit_order(
+                RuntimeOrigin::signed(initial_provider),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                0
+            ),
+            Error::<Test>::NoPermission
+        );
+        assert_ok!(HybridOrderbook::cancel_stop_limit_order(
+            RuntimeOrigin::signed(maker),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            0
+        ));
+        assert!(events().contains(&Event::<Test>::StopLimitOrderCancelled {
+            pool_id: pool_id.clone(),
+            owner: maker,
+            stop_order_id: 0,
+            released: 106 * 10,
+        }));
+        assert_eq!(frozen_balance(maker, quote.clone()), 0);
+        assert_eq!(held_deposit(maker), 0);
+        assert_eq!(OpenOrdersCount::<Test>::get(maker), 1);
+        assert_noop!(
+            HybridOrderbook::cancel_stop_limit_order(
+                RuntimeOrigin::signed(maker),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                0
+            ),
+            Error::<Test>::StopOrderNotFound
+        );
+    })
+}
+
+/// Create the pools of `(1, 2)`, `(3, 2)` and `(3, 4)` with `10000` of each asset. Pool of `(3, 2)`
+/// is created reversely so that the hop from `2` to `3` swaps quote for base.
+fn pools_for_multi_hop(provider: MockAccountId, user: MockAccountId) -> Vec<NativeOrWithId<u32>> {
+    let tokens: Vec<NativeOrWithId<u32>> = (1..=4).map(NativeOrWithId::WithId).collect();
+    create_tokens(provider, tokens.clone());
+    assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), provider, 10000));
+    for (base, quote) in [(1, 2), (3, 2), (3, 4)] {
+        let (base, quote) = (NativeOrWithId::WithId(base), NativeOrWithId::WithId(quote));
+        assert_ok!(HybridOrderbook::create_pool(
+            RuntimeOrigin::signed(provider),
+            Box::new(base.clone()),
+            9,
+            Box::new(quote.clone()),
+            9,
+            Permill::zero(),
+            MakerFeeRate::default(),
+            1,
+            1,
+            0,
+            LiquidityMode::ConstantProduct,
+        ));
+        for asset in [&base, &quote] {
+            let id = match asset {
+                NativeOrWithId::WithId(id) => *id,
+                _ => unreachable!("invalid token"),
+            };
+            assert_ok!(Assets::mint(RuntimeOrigin::signed(provider), id, provider, 10000));
+        }
+        assert_ok!(HybridOrderbook::add_liquidity(
+            RuntimeOrigin::signed(provider),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            10000,
+            10000,
+            10000,
+            10000,
+            provider,
+        ));
+    }
+    assert_ok!(Assets::mint(RuntimeOrigin::signed(provider), 1, user, 1000));
+    tokens
+}
+
+#[test]
+fn swap_exact_tokens_for_tokens_multi_hop_works() {
+    new_test_ext().execute_with(|| {
+        let provider: MockAccountId = 1;
+        let user: MockAccountId = 2;
+        let tokens = pools_for_multi_hop(provider, user);
+        let (t1, t2, t3, t4) = (&tokens[0], &tokens[1], &tokens[2], &tokens[3]);
+        let pool_12 = HybridOrderbook::get_pool_account(t1, t2).unwrap();
+        let pool_32 = HybridOrderbook::get_pool_account(t2, t3).unwrap();
+        let pool_34 = HybridOrderbook::get_pool_account(t3, t4).unwrap();
+        assert_eq!(pool_32, HybridOrderbook::get_pool_account(t3, t2).unwrap());
+
+        // 3 assets
+        let amount_in = 100;
+        let out_1 = HybridOrderbook::get_amount_out(&amount_in, &10000, &10000).unwrap();
+        let out_2 = HybridOrderbook::get_amount_out(&out_1, &10000, &10000).unwrap();
+        assert_ok!(<HybridOrderbook as Swap<MockAccountId>>::swap_exact_tokens_for_tokens(
+            user,
+            vec![t1.clone(), t2.clone(), t3.clone()],
+            amount_in,
+            Some(out_2),
+            user,
+            false,
+        ));
+        assert_eq!(balance(user, t1.clone()), 1000 - amount_in);
+        assert_eq!(balance(user, t2.clone()), 0);
+        assert_eq!(balance(user, t3.clone()), out_2);
+        assert_eq!(balance(pool_12, t1.clone()), 10000 + amount_in);
+        assert_eq!(balance(pool_12, t2.clone()), 10000 - out_1);
+        assert_eq!(balance(pool_32, t2.clone()), 10000 + out_1);
+        assert_eq!(balance(pool_32, t3.clone()), 10000 - out_2);
+
+        // 4 assets
+        let (r1_in, r1_out) = HybridOrderbook::get_reserves(t1, t2).unwrap();
+        let (r2_in, r2_out) = HybridOrderbook::get_reserves(t2, t3).unwrap();
+        let out_1 = HybridOrderbook::get_amount_out(&amount_in, &r1_in, &r1_out).unwrap();
+        let out_2 = HybridOrderbook::get_amount_out(&out_1, &r2_in, &r2_out).unwrap();
+        let out_3 = HybridOrderbook::get_amount_out(&out_2, &10000, &10000).unwrap();
+        assert_ok!(<HybridOrderbook as Swap<MockAccountId>>::swap_exact_tokens_for_tokens(
+            user,
+            vec![t1.clone(), t2.clone(), t3.clone(), t4.clone()],
+            amount_in,
+            Some(out_3),
+            user,
+            false,
+        ));
+        assert_eq!(balance(user, t1.clone()), 1000 - amount_in * 2);
+        assert_eq!(balance(user, t4.clone()), out_3);
+        assert_eq!(balance(pool_34, t3.clone()), 10000 + out_2);
+        assert_eq!(balance(pool_34, t4.clone()), 10000 - out_3);
+    })
+}
+
+#[test]
+fn swap_tokens_for_exact_tokens_multi_hop_works() {
+    new_test_ext().execute_with(|| {
+        let provider: MockAccountId = 1;
+        let user: MockAccountId = 2;
+        let tokens = pools_for_multi_hop(provider, user);
+        let (t1, t2, t3, t4) = (&tokens[0], &tokens[1], &tokens[2], &tokens[3]);
+        let pool_12 = HybridOrderbook::get_pool_account(t1, t2).unwrap();
+        let pool_32 = HybridOrderbook::get_pool_account(t2, t3).unwrap();
+        let pool_34 = HybridOrderbook::get_pool_account(t3, t4).unwrap();
+
+        // 3 assets
+        let amount_out = 50;
+        let in_2 = HybridOrderbook::get_amount_in(&amount_out, &10000, &10000).unwrap();
+        let in_1 = HybridOrderbook::get_amount_in(&in_2, &10000, &10000).unwrap();
+        assert_ok!(<HybridOrderbook as Swap<MockAccountId>>::swap_tokens_for_exact_tokens(
+            user,
+            vec![t1.clone(), t2.clone(), t3.clone()],
+            amount_out,
+            Some(in_1),
+            user,
+            false,
+        ));
+        assert_eq!(balance(user, t1.clone()), 1000 - in_1);
+        assert_eq!(balance(user, t3.clone()), amount_out);
+        assert_eq!(balance(pool_12, t1.clone()), 10000 + in_1);
+        assert_eq!(balance(pool_12, t2.clone()), 10000 - in_2);
+        assert_eq!(balance(pool_32, t2.clone()), 10000 + in_2);
+        assert_eq!(balance(pool_32, t3.clone()), 10000 - amount_out);
+
+        // 4 assets
+        let (r1_in, r1_out) = HybridOrderbook::get_reserves(t1, t2).unwrap();
+        let (r2_in, r2_out) = HybridOrderbook::get_reserves(t2, t3).unwrap();
+        let in_3 = HybridOrderbook::get_amount_in(&amount_out, &10000, &10000).unwrap();
+        let in_2 = HybridOrderbook::get_amount_in(&in_3, &r2_in, &r2_out).unwrap();
+        let in_1_4 = HybridOrderbook::get_amount_in(&in_2, &r1_in, &r1_out).unwrap();
+        assert_ok!(<HybridOrderbook as Swap<MockAccountId>>::swap_tokens_for_exact_tokens(
+            user,
+            vec![t1.clone(), t2.clone(), t3.clone(), t4.clone()],
+            amount_out,
+            Some(in_1_4),
+            user,
+            false,
+        ));
+        assert_eq!(balance(user, t1.clone()), 1000 - in_1 - in_1_4);
+        assert_eq!(balance(user, t4.clone()), amount_out);
+        assert_eq!(balance(pool_34, t3.clone()), 10000 + in_3);
+        assert_eq!(balance(pool_34, t4.clone()), 10000 - amount_out);
+    })
+}
+
+#[test]
+fn swap_extrinsics_work() {
+    new_test_ext().execute_with(|| {
+        let provider: MockAccountId = 1;
+        let user: MockAccountId = 2;
+        let receiver: MockAccountId = 3;
+        let tokens = pools_for_multi_hop(provider, user);
+        let path: Vec<Box<NativeOrWithId<u32>>> =
+            tokens.iter().take(3).cloned().map(Box::new).collect();
+        let (t1, t3) = (&tokens[0], &tokens[2]);
+
+        let out_1 = HybridOrderbook::get_amount_out(&100, &10000, &10000).unwrap();
+        let out_2 = HybridOrderbook::get_amount_out(&out_1, &10000, &10000).unwrap();
+        assert_noop!(
+            HybridOrderbook::swap_exact_tokens_for_tokens(
+                RuntimeOrigin::signed(user),
+                path.clone(),
+                100,
+                out_2 + 1,
+                receiver,
+                false,
+            ),
+            Error::<Test>::ProvidedMinimumNotSufficientForSwap
+        );
+        let post_info = HybridOrderbook::swap_exact_tokens_for_tokens(
+            RuntimeOrigin::signed(user),
+            path.clone(),
+            100,
+            out_2,
+            receiver,
+            false,
+        )
+        .unwrap();
+        // Weight reserved for the stop limit orders is refunded as none is triggered
+        assert_eq!(
+            post_info.actual_weight,
+            Some(<() as WeightInfo>::swap_exact_tokens_for_tokens(3))
+        );
+        assert_eq!(balance(user, t1.clone()), 900);
+        assert_eq!(balance(receiver, t3.clone()), out_2);
+
+        let (r1_in, r1_out) = HybridOrderbook::get_reserves(&tokens[0], &tokens[1]).unwrap();
+        let (r2_in, r2_out) = HybridOrderbook::get_reserves(&tokens[1], &tokens[2]).unwrap();
+        let in_2 = HybridOrderbook::get_amount_in(&50, &r2_in, &r2_out).unwrap();
+        let in_1 = HybridOrderbook::get_amount_in(&in_2, &r1_in, &r1_out).unwrap();
+        assert_noop!(
+            HybridOrderbook::swap_tokens_for_exact_tokens(
+                RuntimeOrigin::signed(user),
+                path.clone(),
+                50,
+                in_1 - 1,
+                receiver,
+                false,
+            ),
+            Error::<Test>::ProvidedMaximumNotSufficientForSwap
+        );
+        assert_ok!(HybridOrderbook::swap_tokens_for_exact_tokens(
+            RuntimeOrigin::signed(user),
+            path,
+            50,
+            in_1,
+            receiver,
+            false,
+        ));
+        assert_eq!(balance(user, t1.clone()), 900 - in_1);
+        assert_eq!(balance(receiver, t3.clone()), out_2 + 50);
+    })
+}
+
+fn concentrated_pool(
+    provider: MockAccountId,
+    base: &NativeOrWithId<u32>,
+    quote: &NativeOrWithId<u32>,
+) {
+    create_tokens(provider, vec![base.clone(), quote.clone()]);
+    assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), provider, 10000));
+    assert_ok!(HybridOrderbook::create_pool(
+        RuntimeOrigin::signed(provider),
+        Box::new(base.clone()),
+        9,
+        Box::new(quote.clone()),
+        9,
+        Permill::zero(),
+        MakerFeeRate::default(),
+        1,
+        1,
+        0,
+        LiquidityMode::Concentrated { initial_price: 100 },
+    ));
+    assert_ok!(Assets::mint(RuntimeOrigin::signed(provider), 1, provider, 10000));
+    assert_ok!(Assets::mint(RuntimeOrigin::signed(provider), 2, provider, 1000000));
+}
+
+/// Open the positions of `[81, 121]`, which is in range, and `[121, 144]`, which is above the
+/// price of 100. Prices are the squares of 9, 10, 11 and 12 so that the amounts are exact.
+fn open_positions(
+    provider: MockAccountId,
+    base: &NativeOrWithId<u32>,
+    quote: &NativeOrWithId<u32>,
+) {
+    for (lower, upper) in [(81, 121), (121, 144)] {
+        assert_ok!(HybridOrderbook::open_position(
+            RuntimeOrigin::signed(provider),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            lower,
+            upper,
+            1000,
+            100000,
+            0,
+            0,
+        ));
+    }
+}
+
+#[test]
+fn create_concentrated_pool_works() {
+    new_test_ext().execute_with(|| {
+        let provider: MockAccountId = 1;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let pool_id = (base.clone(), quote.clone());
+        concentrated_pool(provider, &base, &quote);
+
+        let pool = Pools::<Test>::get(&pool_id).unwrap();
+        assert!(pool.is_concentrated());
+        let liquidity = ConcentratedLiquidities::<Test>::get(&pool_id).unwrap();
+        assert_eq!(liquidity.sqrt_price, FixedU128::from_u32(10));
+        assert_eq!(liquidity.liquidity, 0);
+        assert_eq!(liquidity.boundaries_len(), 0);
+        assert_eq!(HybridOrderbook::current_price(&pool_id, &pool).unwrap(), 100);
+
+        // Liquidity is provided only as positions
+        assert_noop!(
+            HybridOrderbook::add_liquidity(
+                RuntimeOrigin::signed(provider),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                1000,
+                100000,
+                1000,
+                100000,
+                provider,
+            ),
+            Error::<Test>::WrongLiquidityMode
+        );
+
+        let other = NativeOrWithId::WithId(3);
+        create_tokens(provider, vec![other.clone()]);
+        assert_noop!(
+            HybridOrderbook::create_pool(
+                RuntimeOrigin::signed(provider),
+                Box::new(base.clone()),
+                9,
+                Box::new(other.clone()),
+                9,
+                Permill::zero(),
+                MakerFeeRate::default(),
+                1,
+                1,
+                0,
+                LiquidityMode::Concentrated { initial_price: 0 },
+            ),
+            Error::<Test>::InvalidInitialPrice
+        );
+    })
+}
+
+#[test]
+fn open_and_close_position_works() {
+    new_test_ext().execute_with(|| {
+        let provider: MockAccountId = 1;
+        let user: MockAccountId = 2;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let pool_id = (base.clone(), quote.clone());
+        concentrated_pool(provider, &base, &quote);
+
+        for (lower, upper) in [(121, 81), (0, 121)] {
+            assert_noop!(
+                HybridOrderbook::open_position(
+                    RuntimeOrigin::signed(provider),
+                    Box::new(base.clone()),
+                    Box::new(quote.clone()),
+                    lower,
+                    upper,
+                    1000,
+                    100000,
+                    0,
+                    0,
+                ),
+                Error::<Test>::InvalidPositionRange
+            );
+        }
+
+        open_positions(provider, &base, &quote);
+        // Range containing the price takes both assets, while the one above takes base asset only
+        assert!(events().contains(&Event::<Test>::PositionOpened {
+            who: provider,
+            pool_id: pool_id.clone(),
+            position_id: 0,
+            lower: 81,
+            upper: 121,
+            liquidity: 100000,
+            base_asset_provided: 910,
+            quote_asset_provided: 100000,
+        }));
+        assert_eq!(
+            Positions::<Test>::get(&pool_id, 1),
+            Some(Position {
+                owner: provider,
+                lower: 121,
+                upper: 144,
+                liquidity: 132000,
+                fee_growth_inside_base: FixedU128::from_inner(0),
+                fee_growth_inside_quote: FixedU128::from_inner(0),
+            })
+        );
+        assert_eq!(balance(provider, base.clone()), 10000 - 910 - 1000);
+        assert_eq!(balance(provider, quote.clone()), 1000000 - 100000);
+        let liquidity = ConcentratedLiquidities::<Test>::get(&pool_id).unwrap();
+        assert_eq!(liquidity.liquidity, 100000);
+        assert_eq!(liquidity.boundaries_len(), 3);
+
+        assert_noop!(
+            HybridOrderbook::close_position(
+                RuntimeOrigin::signed(user),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                0,
+                0,
+                0,
+            ),
+            Error::<Test>::NoPermission
+        );
+        // Withdrawn amounts are rounded down
+        assert_noop!(
+            HybridOrderbook::close_position(
+                RuntimeOrigin::signed(provider),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                0,
+                910,
+                0,
+            ),
+            Error::<Test>::AssetOneWithdrawalDidNotMeetMinimum
+        );
+        assert_ok!(HybridOrderbook::close_position(
+            RuntimeOrigin::signed(provider),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            0,
+            909,
+            100000,
+        ));
+        assert!(events().contains(&Event::<Test>::PositionClosed {
+            who: provider,
+            pool_id: pool_id.clone(),
+            position_id: 0,
+            base_asset_amount: 909,
+            quote_asset_amount: 100000,
+            base_fee: 0,
+            quote_fee: 0,
+        }));
+        assert_eq!(balance(provider, base.clone()), 10000 - 1 - 1000);
+        assert_eq!(balance(provider, quote.clone()), 1000000);
+        assert_eq!(Positions::<Test>::get(&pool_id, 0), None);
+        // Boundary at 81 is removed, while the one at 121 is still used by the other position
+        let liquidity = ConcentratedLiquidities::<Test>::get(&pool_id).unwrap();
+        assert_eq!(liquidity.liquidity, 0);
+        assert_eq!(liquidity.boundaries_len(), 2);
+        assert_noop!(
+            HybridOrderbook::close_position(
+                RuntimeOrigin::signed(provider),
+                Box::new(base.clone()),
+                Box::new(quote.clone()),
+                0,
+                0,
+                0,
+            ),
+            Error::<Test>::PositionNotFound
+        );
+    })
+}
+
+#[test]
+fn concentrated_swap_crosses_boundaries() {
+    new_test_ext().execute_with(|| {
+        let provider: MockAccountId = 1;
+        let user: MockAccountId = 2;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let pool_id = (base.clone(), quote.clone());
+        concentrated_pool(provider, &base, &quote);
+        open_positions(provider, &base, &quote);
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(provider), 2, user, 150000));
+
+        // 100301 of quote asset moves the price to 121, where the liquidity of the first range is
+        // replaced by the second one for the rest
+        assert_eq!(HybridOrderbook::get_pool_amount_out(&quote, &base, 150000).unwrap(), 1304);
+        assert_noop!(
+            HybridOrderbook::swap_exact_tokens_for_tokens(
+                RuntimeOrigin::signed(user),
+                bvec![quote.clone(), base.clone()],
+                150000,
+                1305,
+                user,
+                false,
+            ),
+            Error::<Test>::ProvidedMinimumNotSufficientForSwap
+        );
+        let post_info = HybridOrderbook::swap_exact_tokens_for_tokens(
+            RuntimeOrigin::signed(user),
+            bvec![quote.clone(), base.clone()],
+            150000,
+            1304,
+            user,
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            post_info.actual_weight,
+            Some(
+                <() as WeightInfo>::swap_exact_tokens_for_tokens(2)
+                    .saturating_add(<() as WeightInfo>::swap_concentrated(
+                        MaxLiquidityBoundaries::get()
+                    ))
+            )
+        );
+        assert_eq!(balance(user, base.clone()), 1304);
+        assert_eq!(balance(user, quote.clone()), 0);
+        let pool = Pools::<Test>::get(&pool_id).unwrap();
+        assert_eq!(HybridOrderbook::current_price(&pool_id, &pool).unwrap(), 129);
+        let liquidity = ConcentratedLiquidities::<Test>::get(&pool_id).unwrap();
+        assert_eq!(liquidity.liquidity, 132000);
+
+        // Each position earns the fee paid while the price was within its range
+        assert_ok!(HybridOrderbook::close_position(
+            RuntimeOrigin::signed(provider),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            0,
+            0,
+            0,
+        ));
+        assert_ok!(HybridOrderbook::close_position(
+            RuntimeOrigin::signed(provider),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            1,
+            0,
+            0,
+        ));
+        let events = events();
+        assert!(events.contains(&Event::<Test>::PositionClosed {
+            who: provider,
+            pool_id: pool_id.clone(),
+            position_id: 0,
+            base_asset_amount: 0,
+            quote_asset_amount: 200301,
+            base_fee: 0,
+            quote_fee: 301,
+        }));
+        assert!(events.contains(&Event::<Test>::PositionClosed {
+            who: provider,
+            pool_id: pool_id.clone(),
+            position_id: 1,
+            base_asset_amount: 604,
+            quote_asset_amount: 49697,
+            base_fee: 0,
+            quote_fee: 149,
+        }));
+        // Only the dust of the rounding is left on the pool
+        let pool_account = HybridOrderbook::get_pool_account(&base, &quote).unwrap();
+        assert_eq!(balance(pool_account, base.clone()), 910 + 1000 - 1304 - 604);
+        assert_eq!(balance(pool_account, quote.clone()), 100000 + 150000 - 200301 - 49697);
+    })
+}
+
+#[test]
+fn orders_are_matched_with_concentrated_liquidity() {
+    new_test_ext().execute_with(|| {
+        let provider: MockAccountId = 1;
+        let user: MockAccountId = 2;
+        let base = NativeOrWithId::WithId(1);
+        let quote = NativeOrWithId::WithId(2);
+        let pool_id = (base.clone(), quote.clone());
+        concentrated_pool(provider, &base, &quote);
+        open_positions(provider, &base, &quote);
+        assert_ok!(Assets::mint(RuntimeOrigin::signed(provider), 2, user, 200000));
+
+        // Bid is filled from the pool only up to the boundary at its limit price
+        assert_ok!(HybridOrderbook::limit_order(
+            RuntimeOrigin::signed(user),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            true,
+            121,
+            2000,
+            TimeInForce::ImmediateOrCancel,
+        ));
+        assert_eq!(balance(user, base.clone()), 909);
+        assert_eq!(balance(user, quote.clone()), 200000 - 100301);
+        let pool = Pools::<Test>::get(&pool_id).unwrap();
+        assert_eq!(HybridOrderbook::current_price(&pool_id, &pool).unwrap(), 121);
+
+        assert_ok!(HybridOrderbook::market_order(
+            RuntimeOrigin::signed(user),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            909,
+            false,
+            Some(99690),
+            None,
+        ));
+        assert_eq!(balance(user, base.clone()), 0);
+        assert_eq!(balance(user, quote.clone()), 200000 - 100301 + 99690);
+        let pool = Pools::<Test>::get(&pool_id).unwrap();
+        assert_eq!(HybridOrderbook::current_price(&pool_id, &pool).unwrap(), 100);
+
+        // Fee of both sides is earned by the position in range
+        assert_ok!(HybridOrderbook::close_position(
+            RuntimeOrigin::signed(provider),
+            Box::new(base.clone()),
+            Box::new(quote.clone()),
+            0,
+            0,
+            0,
+        ));
+        assert!(events().contains(&Event::<Test>::PositionClosed {
+            who: provider,
+            pool_id: pool_id.clone(),
+            position_id: 0,
+            base_asset_amount: 908,
+            quote_asset_amount: 100610,
+            base_fee: 3,
+            quote_fee: 301,
+        }));
     })
 }
@@ -18,13 +18,20 @@
 use super::*;
 use codec::{Decode, DecodeWithMemTracking, Encode, MaxEncodedLen};
 use core::{marker::PhantomData, ops::BitAnd};
+use frame_support::{
+    weights::Weight, CloneNoBound, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound,
+};
 use frame_system::pallet_prelude::BlockNumberFor;
 use scale_info::TypeInfo;
 use sp_core::{RuntimeDebug, U256};
-use sp_runtime::traits::{AtLeast32BitUnsigned, TryConvert};
-use sp_std::{prelude::ToOwned, vec::Vec};
+use sp_runtime::{
+    traits::{AtLeast32BitUnsigned, TryConvert},
+    BoundedBTreeMap, BoundedBTreeSet, PerThing,
+};
+use sp_std::vec::Vec;
 
-pub use traits::{OrderBook, OrderBookIndex};
+pub use traits::{OrderBook, OrderBookDepth, OrderBookIndex, PriceFeed, PriceOracle};
+use traits::OrderInterface;
 
 pub type AssetIdOf<T> =
     <<T as Config>::Assets as Inspect<<T as frame_system::Config>::AccountId>>::AssetId;
@@ -82,7 +89,7 @@ impl OrderId {
         Some(new)
     }
 
-    fn is_bid(&self) -> bool {
+    pub(crate) fn is_bid(&self) -> bool {
         self.0 & Self::start_ask_id().0 == 0
     }
 }
@@ -213,36 +220,94 @@ pub struct PoolInfo<PoolAssetId> {
     pub lp_token: PoolAssetId,
 }
 
-/// Value of Orderbook. All orders for the `Tick` level stored.
-#[derive(
-    Encode,
-    Decode,
-    DecodeWithMemTracking,
-    Default,
-    Clone,
-    PartialEq,
-    Eq,
-    PartialOrd,
-    RuntimeDebug,
-    TypeInfo,
-)]
-pub struct Tick<Quantity, Account, BlockNumber> {
-    /// All open orders for this `Tick` level. Stored on `BTreeMap`, where key is `OrderId` and the
-    /// value is `Order`.
-    open_orders: BTreeMap<OrderId, Order<Quantity, Account, BlockNumber>>,
+/// Price level of the pool's orderbook
+pub type TickOf<T> = Tick<
+    <T as Config>::Unit,
+    <T as frame_system::Config>::AccountId,
+    BlockNumberFor<T>,
+    <T as Config>::MaxOrdersPerTick,
+>;
+
+/// Value of Orderbook. All orders for the `Tick` level stored, which are bounded by `MaxOrders`.
+#[derive(Encode, Decode, DecodeWithMemTracking, MaxEncodedLen, TypeInfo)]
+#[scale_info(skip_type_params(MaxOrders))]
+#[codec(mel_bound(Quantity: MaxEncodedLen, Account: MaxEncodedLen, BlockNumber: MaxEncodedLen))]
+pub struct Tick<Quantity, Account, BlockNumber, MaxOrders: Get<u32>> {
+    /// All open orders for this `Tick` level. Stored on `BoundedBTreeMap`, where key is `OrderId`
+    /// and the value is `Order`.
+    open_orders: BoundedBTreeMap<OrderId, Order<Quantity, Account, BlockNumber>, MaxOrders>,
 }
 
-impl<Quantity, Account: Clone, BlockNumber> Tick<Quantity, Account, BlockNumber> {
-    /// Create new instance of `Tick`
+impl<Quantity, Account: Clone, BlockNumber, MaxOrders: Get<u32>>
+    Tick<Quantity, Account, BlockNumber, MaxOrders>
+{
+    /// Create new instance of `Tick`. Fails if `MaxOrders` is 0.
     pub fn new(
         order_id: OrderId,
         owner: Account,
         quantity: Quantity,
         expired_at: BlockNumber,
-    ) -> Self {
-        let mut open_orders = BTreeMap::new();
-        open_orders.insert(order_id, Order::new(owner, quantity, expired_at));
-        Self { open_orders }
+    ) -> Result<Self, traits::OrderError> {
+        let mut open_orders = BoundedBTreeMap::new();
+        open_orders
+            .try_insert(order_id, Order::new(owner, quantity, expired_at))
+            .map_err(|_| traits::OrderError::TooManyOrders)?;
+        Ok(Self { open_orders })
+    }
+}
+
+impl<Quantity: AtLeast32BitUnsigned + Copy, Account, BlockNumber, MaxOrders: Get<u32>>
+    Tick<Quantity, Account, BlockNumber, MaxOrders>
+{
+    /// Total quantity and the number of the open orders of this `Tick` level
+    pub fn depth(&self) -> (Quantity, u32) {
+        let quantity = self
+            .open_orders
+            .values()
+            .fold(Zero::zero(), |acc: Quantity, order| acc.saturating_add(order.quantity));
+        (quantity, self.open_orders.len() as u32)
+    }
+}
+
+// Manually implemented since `MaxOrders` doesn't need to implement these traits
+impl<Quantity, Account, BlockNumber, MaxOrders: Get<u32>> Default
+    for Tick<Quantity, Account, BlockNumber, MaxOrders>
+{
+    fn default() -> Self {
+        Self { open_orders: BoundedBTreeMap::new() }
+    }
+}
+
+impl<Quantity: Clone, Account: Clone, BlockNumber: Clone, MaxOrders: Get<u32>> Clone
+    for Tick<Quantity, Account, BlockNumber, MaxOrders>
+{
+    fn clone(&self) -> Self {
+        Self { open_orders: self.open_orders.clone() }
+    }
+}
+
+impl<Quantity: PartialEq, Account: PartialEq, BlockNumber: PartialEq, MaxOrders: Get<u32>>
+    PartialEq for Tick<Quantity, Account, BlockNumber, MaxOrders>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.open_orders == other.open_orders
+    }
+}
+
+impl<Quantity: Eq, Account: Eq, BlockNumber: Eq, MaxOrders: Get<u32>> Eq
+    for Tick<Quantity, Account, BlockNumber, MaxOrders>
+{
+}
+
+impl<
+        Quantity: sp_std::fmt::Debug,
+        Account: sp_std::fmt::Debug,
+        BlockNumber: sp_std::fmt::Debug,
+        MaxOrders: Get<u32>,
+    > sp_std::fmt::Debug for Tick<Quantity, Account, BlockNumber, MaxOrders>
+{
+    fn fmt(&self, f: &mut sp_std::fmt::Formatter<'_>) -> sp_std::fmt::Result {
+        f.debug_struct("Tick").field("open_orders", &self.open_orders).finish()
     }
 }
 
@@ -257,6 +322,7 @@ impl<Quantity, Account: Clone, BlockNumber> Tick<Quantity, Account, BlockNumber>
     Eq,
     PartialOrd,
     RuntimeDebug,
+    MaxEncodedLen,
     TypeInfo,
 )]
 pub struct Order<Quantity, Account, BlockNumber> {
@@ -279,31 +345,370 @@ impl<Quantity, Account: Clone, BlockNumber> Order<Quantity, Account, BlockNumber
     }
 }
 
+impl<Quantity: Copy, Account, BlockNumber: Copy> Order<Quantity, Account, BlockNumber> {
+    /// Remaining quantity of the order
+    pub fn quantity(&self) -> Quantity {
+        self.quantity
+    }
+
+    /// Block number at which the order is expired
+    pub fn expired_at(&self) -> BlockNumber {
+        self.expired_at
+    }
+}
+
+/// Work done on the orderbook while matching an order, which the actual weight of the order is
+/// computed from.
+#[derive(Default, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct MatchCount {
+    /// Number of price levels crossed
+    pub levels: u32,
+    /// Number of resting orders filled or expired
+    pub orders: u32,
+    /// Whether the pool filled from has the concentrated liquidity, whose boundaries are crossed
+    /// on each fill
+    pub concentrated: bool,
+}
+
+impl MatchCount {
+    /// Add the work done on another price level
+    pub fn accrue(&mut self, orders: u32) {
+        self.levels = self.levels.saturating_add(1);
+        self.orders = self.orders.saturating_add(orders);
+    }
+}
+
 #[derive(Encode, Decode, TypeInfo)]
 pub struct PoolQuery<Orderbook, Unit> {
-    bids: Orderbook,
-    asks: Orderbook,
-    base_reserve: Unit,
-    quote_reserve: Unit,
-    pool_price: Unit,
+    /// The orderbook of the bid
+    pub bids: Orderbook,
+    /// The orderbook of the ask
+    pub asks: Orderbook,
+    /// Base asset reserve of the pool
+    pub base_reserve: Unit,
+    /// Quote asset reserve of the pool
+    pub quote_reserve: Unit,
+    /// Price of the pool scaled by `pool_decimals`
+    pub pool_price: Unit,
+}
+
+/// Reserves and price of the pool without its orderbook
+#[derive(Encode, Decode, Debug, Clone, PartialEq, TypeInfo)]
+pub struct PoolReserves<Unit> {
+    /// Base asset reserve of the pool
+    pub base_reserve: Unit,
+    /// Quote asset reserve of the pool
+    pub quote_reserve: Unit,
+    /// Price of the pool scaled by `pool_decimals`
+    pub pool_price: Unit,
+}
+
+/// Aggregated price levels of the pool's orderbook. Each level is `(price, total quantity, number
+/// of orders)`.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, TypeInfo)]
+pub struct PoolDepth<Unit> {
+    /// Bid levels from the highest price
+    pub bids: Vec<(Unit, Unit, u32)>,
+    /// Ask levels from the lowest price
+    pub asks: Vec<(Unit, Unit, u32)>,
+}
+
+/// Venue where the order is filled
+#[derive(
+    Decode,
+    DecodeWithMemTracking,
+    Encode,
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    MaxEncodedLen,
+    TypeInfo,
+)]
+pub enum Venue {
+    /// Filled against the resting order of the orderbook
+    Book,
+    /// Filled from the liquidity pool
+    Pool,
+}
+
+/// Trade stored on the recent trades of the pool
+pub type TradeRecordOf<T> = TradeRecord<
+    <T as frame_system::Config>::AccountId,
+    <T as Config>::Unit,
+    BlockNumberFor<T>,
+>;
+
+/// Fill of an order executed on the orderbook or the pool
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq, MaxEncodedLen, TypeInfo)]
+pub struct TradeRecord<AccountId, Unit, BlockNumber> {
+    /// Owner of the resting order, or the account of the pool if it is filled from the pool
+    pub maker: AccountId,
+    /// The account whose order is matched
+    pub taker: AccountId,
+    /// Id of the resting order. `None` if it is filled from the pool.
+    pub order_id: Option<OrderId>,
+    /// Whether the taker is `bid` or not
+    pub is_bid: bool,
+    /// Execution price scaled by `pool_decimals`
+    pub price: Unit,
+    /// Quantity of base asset filled
+    pub quantity: Unit,
+    /// Where the order is filled
+    pub venue: Venue,
+    /// Taker fee charged in quote asset. Zero for the pool whose fee is included in the price.
+    pub fee: Unit,
+    /// Block number when the trade is executed
+    pub block: BlockNumber,
+}
+
+/// Candle stored for each resolution of the pool
+pub type CandleOf<T> = Candle<<T as Config>::Unit, BlockNumberFor<T>>;
+
+/// OHLCV of the trades executed within `resolution` blocks from `start`
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq, MaxEncodedLen, TypeInfo)]
+pub struct Candle<Unit, BlockNumber> {
+    /// First block of the bucket, which is a multiple of the resolution
+    pub start: BlockNumber,
+    /// Execution price of the first trade
+    pub open: Unit,
+    /// Highest execution price
+    pub high: Unit,
+    /// Lowest execution price
+    pub low: Unit,
+    /// Execution price of the last trade
+    pub close: Unit,
+    /// Quantity of base asset traded
+    pub volume: Unit,
+    /// The number of trades
+    pub trades: u32,
+}
+
+impl<Unit: Copy + Ord + Saturating, BlockNumber> Candle<Unit, BlockNumber> {
+    /// Open a new candle from the first trade of the bucket
+    pub fn new(start: BlockNumber, price: Unit, quantity: Unit) -> Self {
+        Self {
+            start,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: quantity,
+            trades: 1,
+        }
+    }
+
+    /// Update the candle with the trade of `quantity` at `price`
+    pub fn update(&mut self, price: Unit, quantity: Unit) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.volume = self.volume.saturating_add(quantity);
+        self.trades = self.trades.saturating_add(1);
+    }
+}
+
+/// Observation of the cumulative price of the pool
+pub type PriceObservationOf<T> = PriceObservation<<T as Config>::Unit, BlockNumberFor<T>>;
+
+/// Sum of the pool price weighted by the number of blocks it lasted, observed on the first reserve
+/// change of the block. Time weighted average price between two observations is the difference
+/// of `price_cumulative` divided by the number of blocks between them. The sum wraps around on
+/// overflow, so the difference should be taken with the wrapping arithmetic.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq, MaxEncodedLen, TypeInfo)]
+pub struct PriceObservation<Unit, BlockNumber> {
+    /// Block number of the observation
+    pub block: BlockNumber,
+    /// Sum of the pool price scaled by `pool_decimals` over each block until `block`
+    pub price_cumulative: Unit,
+}
+
+/// Source of the price provided by [`PriceFeed`]
+#[derive(
+    Decode,
+    DecodeWithMemTracking,
+    Encode,
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    MaxEncodedLen,
+    TypeInfo,
+)]
+pub enum PriceSource<BlockNumber> {
+    /// Current pool price
+    Spot,
+    /// Middle of the best bid and the best ask of the orderbook
+    Mid,
+    /// Time weighted average pool price over the given number of blocks, which should be greater
+    /// than zero
+    Twap(BlockNumber),
+}
+
+/// Prices of the pool normalised by `pool_decimals`
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq, MaxEncodedLen, TypeInfo)]
+pub struct PriceInfo<BlockNumber> {
+    /// Price from the configured [`PriceSource`]
+    pub price: FixedU128,
+    /// Current pool price
+    pub pool_price: FixedU128,
+    /// Price of the best bid order which is not expired. `None` if there is no such bid order
+    /// within [`Config::MaxExpiredPriceLevels`] price levels.
+    pub best_bid: Option<FixedU128>,
+    /// Price of the best ask order which is not expired. `None` if there is no such ask order
+    /// within [`Config::MaxExpiredPriceLevels`] price levels.
+    pub best_ask: Option<FixedU128>,
+    /// Middle of `best_bid` and `best_ask`. `None` if either of them is `None`.
+    pub mid: Option<FixedU128>,
+    /// Block number of the last trade of the pool, which tells how stale the prices are. `None` if
+    /// no trade has been executed.
+    pub last_trade: Option<BlockNumber>,
+}
+
+/// Open limit order of an account
+pub type OpenOrderOf<T> =
+    OpenOrder<<T as Config>::PoolId, <T as Config>::Unit, BlockNumberFor<T>>;
+
+/// Open limit order of an account with its remaining quantity on the orderbook
+#[derive(Encode, Decode, Debug, Clone, PartialEq, TypeInfo)]
+pub struct OpenOrder<PoolId, Unit, BlockNumber> {
+    /// Pool where the order is placed
+    pub pool_id: PoolId,
+    /// Id of the order
+    pub order_id: OrderId,
+    /// Whether it is bid order
+    pub is_bid: bool,
+    /// Price of the order
+    pub price: Unit,
+    /// Quantity of the order which is not filled yet
+    pub quantity: Unit,
+    /// Block number when the order is expired
+    pub expired_at: BlockNumber,
+}
+
+/// Fee rate of the maker whose order is filled from the orderbook
+#[derive(
+    Decode,
+    DecodeWithMemTracking,
+    Encode,
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    MaxEncodedLen,
+    TypeInfo,
+)]
+pub enum MakerFeeRate {
+    /// Fee charged to the maker from the asset the maker received
+    Fee(Permill),
+    /// Rebate paid to the maker in quote asset. Funded by the taker fee.
+    Rebate(Permill),
+}
+
+impl Default for MakerFeeRate {
+    fn default() -> Self {
+        Self::Fee(Permill::zero())
+    }
+}
+
+impl MakerFeeRate {
+    /// Rebate should not be greater than the `taker_fee_rate` since it is paid out of the taker
+    /// fee
+    pub fn is_valid(&self, taker_fee_rate: Permill) -> bool {
+        match self {
+            Self::Fee(_) => true,
+            Self::Rebate(rate) => *rate <= taker_fee_rate,
+        }
+    }
+}
+
+/// How the liquidity of the pool is provided, which is chosen on the creation of the pool
+#[derive(
+    Decode,
+    DecodeWithMemTracking,
+    Encode,
+    Debug,
+    Default,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    MaxEncodedLen,
+    TypeInfo,
+)]
+pub enum LiquidityMode<Unit> {
+    /// Liquidity is provided along the whole price curve for the fungible lp token
+    #[default]
+    ConstantProduct,
+    /// Liquidity is provided within the price ranges of the positions. The pool starts from
+    /// `initial_price` scaled by `pool_decimals`.
+    Concentrated { initial_price: Unit },
+}
+
+/// How long the limit order stays active
+#[derive(
+    Decode,
+    DecodeWithMemTracking,
+    Encode,
+    Debug,
+    Default,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    MaxEncodedLen,
+    TypeInfo,
+)]
+pub enum TimeInForce<BlockNumber> {
+    /// Matched up to the limit price if it crosses the pool price. Remain orders rest on the
+    /// orderbook until cancelled or expired after `OrderExpiration`
+    #[default]
+    GoodTilCancel,
+    /// Fill as much as possible and drop the rest
+    ImmediateOrCancel,
+    /// Fill the whole quantity or revert
+    FillOrKill,
+    /// Rest on the orderbook only. Rejected if it crosses the pool price.
+    PostOnly,
+    /// Same as `GoodTilCancel` but expired at the given block number, which should not be later
+    /// than `OrderExpiration` from now
+    GoodTilBlock(BlockNumber),
 }
 
 #[derive(Encode, Decode, TypeInfo)]
 pub struct PoolMetadata<Unit> {
-    taker_fee_rate: Permill,
-    lot_size: Unit,
-    tick_size: Unit,
-    pool_decimals: u8,
-    base_decimals: u8,
-    quote_decimals: u8,
+    /// The fee rate of the taker
+    pub taker_fee_rate: Permill,
+    /// The fee rate of the maker. Could be rebate.
+    pub maker_fee_rate: MakerFeeRate,
+    /// Order quantity should be multiple of `lot_size`
+    pub lot_size: Unit,
+    /// Order price should be multiple of `tick_size`
+    pub tick_size: Unit,
+    /// Decimals of the price
+    pub pool_decimals: u8,
+    /// Decimals of the base asset
+    pub base_decimals: u8,
+    /// Decimals of the quote asset
+    pub quote_decimals: u8,
+    /// How the liquidity of the pool is provided
+    pub liquidity_mode: LiquidityMode<Unit>,
 }
 
 /// Detail of the pool
-#[derive(Encode, Decode, Default, Debug, Clone, PartialEq, TypeInfo)]
+#[derive(Encode, Decode, Default, Debug, Clone, PartialEq, MaxEncodedLen, TypeInfo)]
 #[scale_info(skip_type_params(T))]
+#[codec(mel_bound())]
 pub struct Pool<T: Config> {
     /// Id of lp token
     pub lp_token: T::PoolAssetId,
+    /// The base asset of the pool
+    pub base_asset: T::AssetKind,
+    /// The quote asset of the pool
+    pub quote_asset: T::AssetKind,
     /// The orderbook of the bid.
     bids: T::OrderBook,
     /// The orderbook of the ask.
@@ -314,6 +719,8 @@ pub struct Pool<T: Config> {
     next_ask_order_id: OrderId,
     /// The fee rate of the taker.
     taker_fee_rate: Permill,
+    /// The fee rate of the maker. Could be rebate.
+    maker_fee_rate: MakerFeeRate,
     /// The size of each tick based on the quote asset. Tick size woule be based on the `pool_decimals`
     pub tick_size: T::Unit,
     /// The minimum amount of the order. Decimal is same as `base` asset
@@ -324,38 +731,48 @@ pub struct Pool<T: Config> {
     pub base_decimals: u8,
     /// The decimals of the quote asset
     pub quote_decimals: u8,
+    /// How the liquidity of the pool is provided
+    pub liquidity_mode: LiquidityMode<T::Unit>,
 }
 
 impl<T: Config> Pool<T> {
     /// Create new instance of Pool
     pub fn new(
         lp_token: T::PoolAssetId,
+        base_asset: T::AssetKind,
+        quote_asset: T::AssetKind,
         taker_fee_rate: Permill,
+        maker_fee_rate: MakerFeeRate,
         tick_size: T::Unit,
         lot_size: T::Unit,
         pool_decimals: u8,
         base_decimals: u8,
         quote_decimals: u8,
+        liquidity_mode: LiquidityMode<T::Unit>,
     ) -> Self {
         Self {
             lp_token,
+            base_asset,
+            quote_asset,
             bids: T::OrderBook::new(),
             asks: T::OrderBook::new(),
             next_bid_order_id: OrderId::new(true),
             next_ask_order_id: OrderId::new(false),
             taker_fee_rate,
+            maker_fee_rate,
             tick_size,
             lot_size,
             pool_decimals,
             base_decimals,
-            quote_decimals
+            quote_decimals,
+            liquidity_mode,
         }
     }
 
-    pub fn to_pool_query(self, base_reserve: T::Unit, quote_reserve: T::Unit, pool_price: T::Unit) -> PoolQuery<T::OrderBook, T::Unit> {
+    pub fn to_pool_query(self, base_reserve: T::Unit, quote_reserve: T::Unit, pool_price: T::Unit) -> PoolQuery<OrderBookSnapshot<T>, T::Unit> {
         PoolQuery {
-            bids: self.bids,
-            asks: self.asks,
+            bids: self.bids.snapshot(),
+            asks: self.asks.snapshot(),
             base_reserve,
             quote_reserve,
             pool_price,
@@ -365,11 +782,13 @@ impl<T: Config> Pool<T> {
     pub fn to_pool_metadata(self) -> PoolMetadata<T::Unit> {
         PoolMetadata {
             taker_fee_rate: self.taker_fee_rate,
+            maker_fee_rate: self.maker_fee_rate,
             lot_size: self.lot_size,
             tick_size: self.tick_size,
             pool_decimals: self.pool_decimals,
             base_decimals: self.base_decimals,
             quote_decimals: self.quote_decimals,
+            liquidity_mode: self.liquidity_mode,
         }
     }
 
@@ -411,21 +830,13 @@ impl<T: Config> Pool<T> {
         quantity >= self.lot_size && quantity % self.lot_size == Zero::zero()
     }
 
-    pub fn lp_token(&self) -> T::PoolAssetId {
-        self.lp_token.clone()
+    /// Whether the liquidity of the pool is provided within the price ranges of the positions
+    pub fn is_concentrated(&self) -> bool {
+        matches!(self.liquidity_mode, LiquidityMode::Concentrated { .. })
     }
 
-    // test only
-    pub fn orders_for(
-        &self,
-        owner: &T::AccountId,
-        is_bid: bool,
-    ) -> Orders<T::Unit, T::AccountId, BlockNumberFor<T>> {
-        if is_bid {
-            self.bids.get_orders(owner)
-        } else {
-            self.asks.get_orders(owner)
-        }
+    pub fn lp_token(&self) -> T::PoolAssetId {
+        self.lp_token.clone()
     }
 
     pub fn next_bid_order_id(&mut self) -> Result<OrderId, Error<T>> {
@@ -462,17 +873,49 @@ impl<T: Config> Pool<T> {
         self.asks.min_order()
     }
 
-    /// Place `quantity` amount of orders of give `price`. This method will be only called when
-    /// `do_limit_order`. `OrderId` will be returned if success.
+    /// Price of the best bid or ask order which is not expired at `now`. Up to `max_expired` price
+    /// levels of which all orders are expired are skipped.
+    pub fn best_price(
+        &self,
+        is_bid: bool,
+        now: &BlockNumberFor<T>,
+        max_expired: u32,
+    ) -> Option<T::Unit> {
+        self.get_orderbook(is_bid)
+            .depth(is_bid, max_expired.saturating_add(1))
+            .into_iter()
+            .find(|(price, _, orders)| {
+                (self.expired_orders_at(is_bid, *price, now).len() as u32) < *orders
+            })
+            .map(|(price, _, _)| price)
+    }
+
+    /// Taker fee of the pool for the given `amount`
+    pub fn taker_fee(&self, amount: T::Unit) -> T::Unit {
+        self.taker_fee_rate.mul_ceil(amount)
+    }
+
+    /// Maker fee rate of the pool
+    pub fn maker_fee_rate(&self) -> MakerFeeRate {
+        self.maker_fee_rate
+    }
+
+    /// Minimum amount of the order of the pool
+    pub fn lot_size(&self) -> T::Unit {
+        self.lot_size
+    }
+
+    /// Place `quantity` amount of orders of give `price` which is expired at `expired_at`. This
+    /// method will be only called when `do_limit_order`. `OrderId` will be returned if success.
     pub fn place_order(
         &mut self,
         is_bid: bool,
         owner: &T::AccountId,
         price: T::Unit,
         quantity: T::Unit,
+        expired_at: BlockNumberFor<T>,
     ) -> Result<(T::Unit, OrderId), Error<T>> {
-        let current = frame_system::Pallet::<T>::block_number();
-        let expired_at = current + T::OrderExpiration::get();
+        self.ensure_room_for_order(is_bid, price)?;
         let order_id: OrderId;
         if is_bid {
             order_id = self.next_bid_order_id()?;
@@ -489,13 +932,59 @@ impl<T: Config> Pool<T> {
         Ok((price, order_id))
     }
 
+    /// Rest the order of the older pool again with its `order_id`, whose asset is already frozen.
+    /// Only used by [`crate::migration`].
+    pub(crate) fn restore_order(
+        &mut self,
+        order_id: OrderId,
+        owner: &T::AccountId,
+        price: T::Unit,
+        quantity: T::Unit,
+        expired_at: BlockNumberFor<T>,
+    ) -> Result<(), Error<T>> {
+        let is_bid = order_id.is_bid();
+        self.ensure_room_for_order(is_bid, price)?;
+        let orderbook = if is_bid { &mut self.bids } else { &mut self.asks };
+        orderbook
+            .place_order(order_id, owner, price, quantity, expired_at)
+            .map_err(|_| Error::<T>::ErrorOnPlaceOrder)
+    }
+
+    /// Keep the next order ids of the older pool. Only used by [`crate::migration`].
+    pub(crate) fn restore_next_order_ids(&mut self, next_bid: OrderId, next_ask: OrderId) {
+        self.next_bid_order_id = next_bid;
+        self.next_ask_order_id = next_ask;
+    }
+
+    /// Check whether the orderbook has room for a new order of given `price`, bounded by
+    /// [`Config::MaxTicks`] and [`Config::MaxOrdersPerTick`].
+    fn ensure_room_for_order(&self, is_bid: bool, price: T::Unit) -> Result<(), Error<T>> {
+        let orderbook = self.get_orderbook(is_bid);
+        let orders_at_price = orderbook
+            .open_orders_at(price)
+            .map_err(|_| Error::<T>::ErrorOnPlaceOrder)?
+            .map_or(0, |orders| orders.len());
+        if orders_at_price == 0 {
+            // New price level is added
+            ensure!(
+                (orderbook.size() as u32) < T::MaxTicks::get(),
+                Error::<T>::TooManyTicks
+            );
+        }
+        ensure!(
+            (orders_at_price as u32) < T::MaxOrdersPerTick::get(),
+            Error::<T>::TooManyOrdersPerTick
+        );
+        Ok(())
+    }
+
     /// Fill `quantity` amount of orders of given `price`.
     pub fn fill_order(
         &mut self,
         is_bid: bool,
         price: T::Unit,
         quantity: T::Unit,
-    ) -> Result<Option<Vec<(T::AccountId, T::Unit)>>, Error<T>> {
+    ) -> Result<Option<Vec<(OrderId, T::AccountId, T::Unit, T::Unit)>>, Error<T>> {
         let res = if is_bid {
             self.asks.fill_order(price, quantity)
         } else {
@@ -524,6 +1013,233 @@ impl<T: Config> Pool<T> {
         }
         Ok(order_id)
     }
+
+    /// Get the order of `order_id` placed at `price`
+    pub fn get_order(
+        &self,
+        is_bid: bool,
+        price: T::Unit,
+        order_id: OrderId,
+    ) -> Option<Order<T::Unit, T::AccountId, BlockNumberFor<T>>> {
+        self.get_orderbook(is_bid).get_order(price, order_id)
+    }
+
+    /// Remove the order of `order_id` placed at `price`. Return the removed order if success.
+    pub fn remove_order(
+        &mut self,
+        is_bid: bool,
+        price: T::Unit,
+        order_id: OrderId,
+    ) -> Result<Order<T::Unit, T::AccountId, BlockNumberFor<T>>, Error<T>> {
+        let res = if is_bid {
+            self.bids.remove_order(price, order_id)
+        } else {
+            self.asks.remove_order(price, order_id)
+        };
+
+        res.map_err(|_| Error::<T>::OrderNotFound)
+    }
+
+    /// Get all orders placed at `price` which are expired at `now`
+    pub fn expired_orders_at(
+        &self,
+        is_bid: bool,
+        price: T::Unit,
+        now: &BlockNumberFor<T>,
+    ) -> Vec<(OrderId, Order<T::Unit, T::AccountId, BlockNumberFor<T>>)> {
+        self.get_orderbook(is_bid).expired_orders_at(price, now)
+    }
+}
+
+/// Id of the orderbook whose nodes are stored on [`InternalNodes`] and [`LeafNodes`]. Increments
+/// for each orderbook.
+pub type OrderBookId = u64;
+
+/// Orderbook of the pool copied out of the storage
+pub type OrderBookSnapshot<T> = <<T as Config>::OrderBook as OrderBook<
+    <T as frame_system::Config>::AccountId,
+    <T as Config>::Unit,
+    BlockNumberFor<T>,
+>>::Snapshot;
+
+/// Nodes of the critbit tree stored on [`InternalNodes`] and [`LeafNodes`], so that each node is
+/// read and written on its own rather than with the whole pool.
+#[derive(
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    CloneNoBound,
+    PartialEqNoBound,
+    EqNoBound,
+    RuntimeDebugNoBound,
+    MaxEncodedLen,
+    TypeInfo,
+)]
+#[scale_info(skip_type_params(T))]
+#[codec(mel_bound())]
+pub struct OrderBookNodes<T> {
+    /// Id of the orderbook which is the first key of the nodes
+    orderbook_id: OrderBookId,
+    #[codec(skip)]
+    _phantom: PhantomData<T>,
+}
+
+impl<T: Config> NodeStore<T::Unit, TickOf<T>> for OrderBookNodes<T> {
+    fn new() -> Self {
+        let orderbook_id = NextOrderBookId::<T>::mutate(|id| {
+            let orderbook_id = *id;
+            *id = id.saturating_add(1);
+            orderbook_id
+        });
+        Self { orderbook_id, _phantom: PhantomData }
+    }
+
+    fn internal_node(&self, index: &T::Unit) -> Option<InternalNode<T::Unit>> {
+        InternalNodes::<T>::get(self.orderbook_id, index)
+    }
+
+    fn insert_internal_node(
+        &mut self,
+        index: T::Unit,
+        node: InternalNode<T::Unit>,
+    ) -> Option<InternalNode<T::Unit>> {
+        InternalNodes::<T>::mutate(self.orderbook_id, index, |maybe_node| {
+            maybe_node.replace(node)
+        })
+    }
+
+    fn remove_internal_node(&mut self, index: &T::Unit) -> Option<InternalNode<T::Unit>> {
+        InternalNodes::<T>::take(self.orderbook_id, index)
+    }
+
+    fn leaf(&self, index: &T::Unit) -> Option<LeafNode<T::Unit, TickOf<T>>> {
+        LeafNodes::<T>::get(self.orderbook_id, index)
+    }
+
+    fn insert_leaf(
+        &mut self,
+        index: T::Unit,
+        leaf: LeafNode<T::Unit, TickOf<T>>,
+    ) -> Option<LeafNode<T::Unit, TickOf<T>>> {
+        LeafNodes::<T>::mutate(self.orderbook_id, index, |maybe_leaf| maybe_leaf.replace(leaf))
+    }
+
+    fn remove_leaf(&mut self, index: &T::Unit) -> Option<LeafNode<T::Unit, TickOf<T>>> {
+        LeafNodes::<T>::take(self.orderbook_id, index)
+    }
+
+    fn internal_nodes(&self) -> Vec<(T::Unit, InternalNode<T::Unit>)> {
+        InternalNodes::<T>::iter_prefix(self.orderbook_id).collect()
+    }
+
+    fn leaves(&self) -> Vec<(T::Unit, LeafNode<T::Unit, TickOf<T>>)> {
+        LeafNodes::<T>::iter_prefix(self.orderbook_id).collect()
+    }
+}
+
+/// Id of the stop limit order. Increments for each pool.
+pub type StopOrderId = u64;
+
+/// Conditional order which is converted to the limit order once the pool price reaches the stop
+/// price.
+#[derive(
+    Encode,
+    Decode,
+    DecodeWithMemTracking,
+    Clone,
+    PartialEq,
+    Eq,
+    RuntimeDebug,
+    MaxEncodedLen,
+    TypeInfo,
+)]
+pub struct StopLimitOrder<Account, Unit> {
+    /// Owner of the order
+    pub owner: Account,
+    /// Whether the order is `bid` or not
+    pub is_bid: bool,
+    /// Pool price at which the order is triggered
+    pub stop_price: Unit,
+    /// Price of the limit order placed once triggered
+    pub limit_price: Unit,
+    /// Quantity of the limit order placed once triggered
+    pub quantity: Unit,
+    /// Amount of `OrderDeposit` held for the order
+    pub deposit: Unit,
+}
+
+pub type StopLimitOrderOf<T> =
+    StopLimitOrder<<T as frame_system::Config>::AccountId, <T as Config>::Unit>;
+
+pub type TriggerBookOf<T> = TriggerBook<<T as Config>::Unit, <T as Config>::MaxStopOrders>;
+
+/// Stop limit orders of the pool waiting to be triggered, which are bounded by `MaxOrders`. Sorted
+/// by `(stop_price, id)`, while the orders themselves are stored by id.
+#[derive(Encode, Decode, DecodeWithMemTracking, MaxEncodedLen, TypeInfo)]
+#[scale_info(skip_type_params(MaxOrders))]
+#[codec(mel_bound(Unit: MaxEncodedLen))]
+pub struct TriggerBook<Unit: Ord, MaxOrders: Get<u32>> {
+    /// `Bid` orders which are triggered when the pool price goes up to the stop price
+    bids: BoundedBTreeSet<(Unit, StopOrderId), MaxOrders>,
+    /// `Ask` orders which are triggered when the pool price goes down to the stop price
+    asks: BoundedBTreeSet<(Unit, StopOrderId), MaxOrders>,
+    /// Id of the next stop limit order
+    next_order_id: StopOrderId,
+}
+
+impl<Unit: Ord, MaxOrders: Get<u32>> Default for TriggerBook<Unit, MaxOrders> {
+    fn default() -> Self {
+        Self { bids: BoundedBTreeSet::new(), asks: BoundedBTreeSet::new(), next_order_id: 0 }
+    }
+}
+
+impl<Unit: Ord + Copy, MaxOrders: Get<u32>> TriggerBook<Unit, MaxOrders> {
+    /// Place the order which is triggered at `stop_price`. Return the id of the order, or error if
+    /// there are already `MaxOrders` orders.
+    pub fn place(&mut self, is_bid: bool, stop_price: Unit) -> Result<StopOrderId, ()> {
+        if self.len() >= MaxOrders::get() {
+            return Err(())
+        }
+        let id = self.next_order_id;
+        let book = if is_bid { &mut self.bids } else { &mut self.asks };
+        book.try_insert((stop_price, id)).map_err(|_| ())?;
+        self.next_order_id = id.saturating_add(1);
+        Ok(id)
+    }
+
+    /// Remove the order of `id` triggered at `stop_price`. Return whether it existed.
+    pub fn remove(&mut self, is_bid: bool, stop_price: Unit, id: StopOrderId) -> bool {
+        let book = if is_bid { &mut self.bids } else { &mut self.asks };
+        book.remove(&(stop_price, id))
+    }
+
+    /// Remove the order which should be triggered at `pool_price` and return its id, if any. `Bid`
+    /// orders with the lowest stop price and `Ask` orders with the highest stop price come first.
+    pub fn pop_triggered(&mut self, pool_price: Unit) -> Option<StopOrderId> {
+        if let Some(&(stop_price, id)) = self.bids.first() {
+            if stop_price <= pool_price {
+                self.bids.remove(&(stop_price, id));
+                return Some(id);
+            }
+        }
+        if let Some(&(stop_price, id)) = self.asks.last() {
+            if stop_price >= pool_price {
+                self.asks.remove(&(stop_price, id));
+                return Some(id);
+            }
+        }
+        None
+    }
+
+    /// The number of orders waiting to be triggered
+    pub fn len(&self) -> u32 {
+        (self.bids.len() + self.asks.len()) as u32
+    }
+
+    /// Whether there are no orders waiting to be triggered
+    pub fn is_empty(&self) -> bool {
+        self.bids.is_empty() && self.asks.is_empty()
+    }
 }
 
 /// Provides means to resolve the `PoolId` and `AccountId` from a pair of assets.
@@ -683,6 +1399,8 @@ pub mod traits {
         type OrderId: OrderbookOrderId;
         /// Type of error of orderbook
         type Error;
+        /// Type of the orderbook copied out of the storage, which could be used off-chain
+        type Snapshot;
 
         /// Create new instance of `Self`
         fn new() -> Self;
@@ -692,8 +1410,9 @@ pub mod traits {
         /// Check if the orderbook is empty
         fn is_empty(&self) -> bool;
 
-        /// Get all orders of given `owner`
-        fn get_orders(&self, owner: &Account) -> Orders<Unit, Account, BlockNumber>;
+        /// Copy the whole orderbook. Every node of the tree is read from the storage, so it must
+        /// only be used by the runtime API, never by the dispatchables or the hooks.
+        fn snapshot(&self) -> Self::Snapshot;
 
         /// Try get all orders for given `key`
         fn open_orders_at(&self, key: Unit) -> Result<Option<Self::Order>, Self::Error>;
@@ -716,13 +1435,14 @@ pub mod traits {
             expired_at: BlockNumber,
         ) -> Result<(), Self::Error>;
 
-        /// Fill order on orderbook used for order matching. Return (Account, Unit) which means
-        /// orderer and the amount of filled orders
+        /// Fill order on orderbook used for order matching. Return (OrderId, Account, Unit, Unit)
+        /// which means id and owner of the order, the amount of filled orders and the amount
+        /// remained. Fully filled order is removed from the orderbook.
         fn fill_order(
             &mut self,
             key: Unit,
             quantity: Unit,
-        ) -> Result<Option<Vec<(Account, Unit)>>, Self::Error>;
+        ) -> Result<Option<Vec<(Self::OrderId, Account, Unit, Unit)>>, Self::Error>;
 
         /// Cancel the order
         fn cancel_order(
@@ -732,6 +1452,23 @@ pub mod traits {
             order_id: Self::OrderId,
             quantity: Unit,
         ) -> Result<(), Self::Error>;
+
+        /// Get the order of given `order_id` placed at `key`
+        fn get_order(&self, key: Unit, order_id: Self::OrderId) -> Option<Order<Unit, Account, BlockNumber>>;
+
+        /// Remove the whole order of given `order_id` placed at `key`. Return the removed order.
+        fn remove_order(
+            &mut self,
+            key: Unit,
+            order_id: Self::OrderId,
+        ) -> Result<Order<Unit, Account, BlockNumber>, Self::Error>;
+
+        /// Get all orders placed at `key` which are expired at `now`
+        fn expired_orders_at(
+            &self,
+            key: Unit,
+            now: &BlockNumber,
+        ) -> Vec<(Self::OrderId, Order<Unit, Account, BlockNumber>)>;
     }
 
     /// Index trait for the critbit tree.
@@ -777,6 +1514,40 @@ pub mod traits {
     impl_orderbook_index!(u64, u128);
     impl_orderbook_index!(u128, U256);
 
+    /// Price levels of the orderbook aggregated per price
+    pub trait OrderBookDepth<Unit> {
+        /// Return `(price, total quantity, number of orders)` of up to `max_levels` price levels
+        /// from the lowest price, or from the highest price if `descending`.
+        fn depth(&self, descending: bool, max_levels: u32) -> Vec<(Unit, Unit, u32)>;
+    }
+
+    /// Price feed of the pools for other pallets, e.g. lending or margin
+    pub trait PriceFeed<AssetKind, BlockNumber> {
+        /// Prices of `base_asset` in `quote_asset` with the block of the last trade. `None` if the
+        /// pool doesn't exist or the price of the configured source is not available.
+        fn price(
+            base_asset: &AssetKind,
+            quote_asset: &AssetKind,
+        ) -> Option<PriceInfo<BlockNumber>>;
+
+        /// Upper bound of the weight of [`PriceFeed::price`], which the caller should account for
+        fn price_weight() -> Weight;
+    }
+
+    /// Manipulation resistant price of the pools, which could be used by other pallets
+    pub trait PriceOracle<AssetKind, Unit, BlockNumber> {
+        /// Time weighted average pool price of `base_asset` in `quote_asset` scaled by
+        /// `pool_decimals` over the last `window` blocks. Fails if `window` is zero.
+        fn twap(
+            base_asset: &AssetKind,
+            quote_asset: &AssetKind,
+            window: BlockNumber,
+        ) -> Result<Unit, DispatchError>;
+
+        /// Upper bound of the weight of [`PriceOracle::twap`], which the caller should account for
+        fn twap_weight() -> Weight;
+    }
+
     /// Abstraction for type of order should implement
     pub trait OrderInterface<Account, Unit, BlockNumber> {
         /// Type of order id(e.g `u64`)
@@ -784,13 +1555,15 @@ pub mod traits {
         /// Type of order error
         type Error;
 
-        /// Create new instance of `Order`
+        /// Create new instance of `Order`. Fails if the order could not be stored.
         fn new(
             order_id: Self::OrderId,
             owner: Account,
             quantity: Unit,
             expired_at: BlockNumber,
-        ) -> Self;
+        ) -> Result<Self, Self::Error>
+        where
+            Self: Sized;
 
         fn find_order_of(&self, owner: &Account) -> Option<Orders<Unit, Account, BlockNumber>>;
 
@@ -799,17 +1572,21 @@ pub mod traits {
         /// Return `true` if there are no open orders
         fn is_empty(&self) -> bool;
 
-        /// Create new instance of order and return `OrderId` of that order.
+        /// Return the number of open orders
+        fn len(&self) -> usize;
+
+        /// Create new instance of order of `order_id`. Fails if there are too many open orders.
         fn placed(
             &mut self,
             order_id: Self::OrderId,
             owner: &Account,
             quantity: Unit,
             expired_at: BlockNumber,
-        );
+        ) -> Result<(), Self::Error>;
 
-        /// Fill the order with the given `quantity`
-        fn filled(&mut self, quantity: Unit) -> Option<Vec<(Account, Unit)>>;
+        /// Fill the order with the given `quantity`. Return (OrderId, Account, Filled, Remain) of
+        /// each order filled.
+        fn filled(&mut self, quantity: Unit) -> Option<Vec<(Self::OrderId, Account, Unit, Unit)>>;
 
         /// Add the quantity to the order of the given order id
         fn added(
@@ -826,6 +1603,15 @@ pub mod traits {
             order_id: Self::OrderId,
             quantity: Unit,
         ) -> Result<(), Self::Error>;
+
+        /// Get the order of the given order id
+        fn get_order(&self, order_id: &Self::OrderId) -> Option<Order<Unit, Account, BlockNumber>>;
+
+        /// Remove the whole order of the given order id. Return `None` if order doesn't exist
+        fn removed(&mut self, order_id: &Self::OrderId) -> Option<Order<Unit, Account, BlockNumber>>;
+
+        /// Return all orders which are expired at `now`
+        fn expired_orders(&self, now: &BlockNumber) -> Vec<(Self::OrderId, Order<Unit, Account, BlockNumber>)>;
     }
 
     /// Error type for order
@@ -839,14 +1625,17 @@ pub mod traits {
         Underflow,
         /// Quantity or OrderId might overflow
         Overflow,
+        /// The number of open orders exceeds the bound
+        TooManyOrders,
     }
 
-    impl<Unit, Account, BlockNumber> OrderInterface<Account, Unit, BlockNumber>
-        for Tick<Unit, Account, BlockNumber>
+    impl<Unit, Account, BlockNumber, MaxOrders> OrderInterface<Account, Unit, BlockNumber>
+        for Tick<Unit, Account, BlockNumber, MaxOrders>
     where
         Account: PartialEq + Clone,
         Unit: AtLeast32BitUnsigned + Copy,
-        BlockNumber: Clone,
+        BlockNumber: Clone + PartialOrd,
+        MaxOrders: Get<u32>,
     {
         type OrderId = OrderId;
         type Error = OrderError;
@@ -856,17 +1645,17 @@ pub mod traits {
             owner: Account,
             quantity: Unit,
             expired_at: BlockNumber,
-        ) -> Self {
+        ) -> Result<Self, Self::Error> {
             Self::new(order_id, owner, quantity, expired_at)
         }
 
         // for test only
         fn find_order_of(&self, owner: &Account) -> Option<Orders<Unit, Account, BlockNumber>> {
             let matched = self
-                .to_owned()
                 .open_orders
-                .into_values()
+                .values()
                 .filter(|o| o.owner() == *owner)
+                .cloned()
                 .collect::<Orders<Unit, Account, BlockNumber>>();
             if matched.is_empty() {
                 return None;
@@ -883,39 +1672,42 @@ pub mod traits {
             self.open_orders.is_empty()
         }
 
+        fn len(&self) -> usize {
+            self.open_orders.len()
+        }
+
         fn placed(
             &mut self,
             order_id: Self::OrderId,
             owner: &Account,
             quantity: Unit,
             expired_at: BlockNumber,
-        ) {
+        ) -> Result<(), Self::Error> {
             self.open_orders
-                .insert(order_id, Order::new(owner.clone(), quantity, expired_at));
+                .try_insert(order_id, Order::new(owner.clone(), quantity, expired_at))
+                .map_err(|_| OrderError::TooManyOrders)?;
+            Ok(())
         }
 
-        fn filled(&mut self, quantity: Unit) -> Option<Vec<(Account, Unit)>> {
+        fn filled(&mut self, quantity: Unit) -> Option<Vec<(Self::OrderId, Account, Unit, Unit)>> {
             let mut filled = Zero::zero();
             let mut to_remove = Vec::new();
-            let mut res: Vec<(Account, Unit)> = Vec::new();
+            let mut res: Vec<(Self::OrderId, Account, Unit, Unit)> = Vec::new();
             for (id, order) in self.open_orders.iter_mut() {
-                if_std! {
-                println!("👀 OrderId => {:?} is currently filled.", id);
-                    }
                 // All orders are filled
                 let remain = quantity - filled;
                 if remain == Zero::zero() {
                     break;
                 }
-                if order.quantity >= remain {
+                if order.quantity > remain {
                     order.quantity -= remain;
                     filled += remain;
-                    res.push((order.owner(), remain));
+                    res.push((*id, order.owner(), remain, order.quantity));
                 } else {
                     filled += order.quantity;
                     // Order of `id` is fully filled and should be removed
                     to_remove.push(*id);
-                    res.push((order.owner(), order.quantity));
+                    res.push((*id, order.owner(), order.quantity, Zero::zero()));
                 }
             }
             // Remove the fully filled orders
@@ -968,6 +1760,22 @@ pub mod traits {
                 return Err(OrderError::OrderNotExist);
             }
         }
+
+        fn get_order(&self, order_id: &Self::OrderId) -> Option<Order<Unit, Account, BlockNumber>> {
+            self.open_orders.get(order_id).cloned()
+        }
+
+        fn removed(&mut self, order_id: &Self::OrderId) -> Option<Order<Unit, Account, BlockNumber>> {
+            self.open_orders.remove(order_id)
+        }
+
+        fn expired_orders(&self, now: &BlockNumber) -> Vec<(Self::OrderId, Order<Unit, Account, BlockNumber>)> {
+            self.open_orders
+                .iter()
+                .filter(|(_, order)| &order.expired_at <= now)
+                .map(|(id, order)| (*id, order.clone()))
+                .collect()
+        }
     }
 }
 
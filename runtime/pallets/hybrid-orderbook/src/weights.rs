@@ -55,6 +55,19 @@ pub trait WeightInfo {
 	fn swap_exact_tokens_for_tokens(n: u32, ) -> Weight;
 	fn swap_tokens_for_exact_tokens(n: u32, ) -> Weight;
 	fn touch(n: u32, ) -> Weight;
+	fn market_order(l: u32, o: u32, ) -> Weight;
+	fn limit_order(l: u32, o: u32, ) -> Weight;
+	fn cancel_order() -> Weight;
+	fn get_pool_query(l: u32, ) -> Weight;
+	fn stop_limit_order(s: u32, ) -> Weight;
+	fn cancel_stop_limit_order(s: u32, ) -> Weight;
+	fn cancel_order_by_id() -> Weight;
+	fn cancel_all_orders(n: u32, ) -> Weight;
+	fn amend_order() -> Weight;
+	fn expire_order() -> Weight;
+	fn open_position(b: u32, ) -> Weight;
+	fn close_position(b: u32, ) -> Weight;
+	fn swap_concentrated(b: u32, ) -> Weight;
 }
 
 /// Weights for `pallet_asset_conversion` using the Substrate node and recommended hardware.
@@ -179,6 +192,256 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(8_u64))
 			.saturating_add(T::DbWeight::get().writes((2_u64).saturating_mul(n.into())))
 	}
+	/// Storage: `HybridOrderbook::Pools` (r:1 w:1)
+	/// Storage: `HybridOrderbook::TriggerBooks` (r:1 w:0)
+	/// Storage: `HybridOrderbook::InternalNodes` (r:1 w:1)
+	/// Storage: `HybridOrderbook::LeafNodes` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OpenOrders` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OpenOrdersCount` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OrderExpiries` (r:0 w:1)
+	/// Storage: `HybridOrderbook::PriceObservationCount` (r:1 w:1)
+	/// Storage: `HybridOrderbook::PriceObservations` (r:1 w:1)
+	/// Storage: `HybridOrderbook::TradeCount` (r:1 w:1)
+	/// Storage: `HybridOrderbook::RecentTrades` (r:0 w:1)
+	/// Storage: `HybridOrderbook::Candles` (r:4 w:4)
+	/// Storage: `Assets::Asset` (r:2 w:2)
+	/// Storage: `Assets::Account` (r:4 w:4)
+	/// Storage: `AssetsFreezer::Freezes` (r:1 w:1)
+	/// Storage: `AssetsFreezer::FrozenBalances` (r:1 w:1)
+	/// Storage: `System::Account` (r:2 w:2)
+	/// The range of component `l` is `[1, 100]`.
+	/// The range of component `o` is `[1, 100]`.
+	fn market_order(l: u32, o: u32, ) -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(160_000_000, 11426)
+			.saturating_add(Weight::from_parts(12_000_000, 0).saturating_mul(l.into()))
+			.saturating_add(Weight::from_parts(38_000_000, 0).saturating_mul(o.into()))
+			.saturating_add(T::DbWeight::get().reads(17_u64))
+			.saturating_add(T::DbWeight::get().writes(16_u64))
+			.saturating_add(T::DbWeight::get().reads((2_u64).saturating_mul(l.into())))
+			.saturating_add(T::DbWeight::get().writes((2_u64).saturating_mul(l.into())))
+			.saturating_add(T::DbWeight::get().reads((11_u64).saturating_mul(o.into())))
+			.saturating_add(T::DbWeight::get().writes((12_u64).saturating_mul(o.into())))
+			.saturating_add(Weight::from_parts(0, 2641).saturating_mul(l.into()))
+			.saturating_add(Weight::from_parts(0, 5298).saturating_mul(o.into()))
+	}
+	/// Storage: `HybridOrderbook::Pools` (r:1 w:1)
+	/// Storage: `HybridOrderbook::TriggerBooks` (r:1 w:0)
+	/// Storage: `HybridOrderbook::InternalNodes` (r:1 w:1)
+	/// Storage: `HybridOrderbook::LeafNodes` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OpenOrders` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OpenOrdersCount` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OrderExpiries` (r:0 w:1)
+	/// Storage: `HybridOrderbook::PriceObservationCount` (r:1 w:1)
+	/// Storage: `HybridOrderbook::PriceObservations` (r:1 w:1)
+	/// Storage: `HybridOrderbook::TradeCount` (r:1 w:1)
+	/// Storage: `HybridOrderbook::RecentTrades` (r:0 w:1)
+	/// Storage: `HybridOrderbook::Candles` (r:4 w:4)
+	/// Storage: `HybridOrderbook::NextOrderBookId` (r:1 w:0)
+	/// Storage: `Assets::Asset` (r:2 w:2)
+	/// Storage: `Assets::Account` (r:4 w:4)
+	/// Storage: `AssetsFreezer::Freezes` (r:1 w:1)
+	/// Storage: `AssetsFreezer::FrozenBalances` (r:1 w:1)
+	/// Storage: `System::Account` (r:2 w:2)
+	/// The range of component `l` is `[0, 100]`.
+	/// The range of component `o` is `[0, 100]`.
+	fn limit_order(l: u32, o: u32, ) -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(210_000_000, 11426)
+			.saturating_add(Weight::from_parts(12_000_000, 0).saturating_mul(l.into()))
+			.saturating_add(Weight::from_parts(38_000_000, 0).saturating_mul(o.into()))
+			.saturating_add(T::DbWeight::get().reads(23_u64))
+			.saturating_add(T::DbWeight::get().writes(23_u64))
+			.saturating_add(T::DbWeight::get().reads((2_u64).saturating_mul(l.into())))
+			.saturating_add(T::DbWeight::get().writes((2_u64).saturating_mul(l.into())))
+			.saturating_add(T::DbWeight::get().reads((11_u64).saturating_mul(o.into())))
+			.saturating_add(T::DbWeight::get().writes((12_u64).saturating_mul(o.into())))
+			.saturating_add(Weight::from_parts(0, 2641).saturating_mul(l.into()))
+			.saturating_add(Weight::from_parts(0, 5298).saturating_mul(o.into()))
+	}
+	/// Storage: `HybridOrderbook::Pools` (r:1 w:1)
+	/// Storage: `HybridOrderbook::InternalNodes` (r:1 w:1)
+	/// Storage: `HybridOrderbook::LeafNodes` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OpenOrders` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OpenOrdersCount` (r:1 w:1)
+	/// Storage: `AssetsFreezer::Freezes` (r:1 w:1)
+	/// Storage: `AssetsFreezer::FrozenBalances` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	fn cancel_order() -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(85_000_000, 8124)
+			.saturating_add(T::DbWeight::get().reads(8_u64))
+			.saturating_add(T::DbWeight::get().writes(8_u64))
+	}
+	/// Storage: `HybridOrderbook::Pools` (r:1 w:0)
+	/// Storage: `HybridOrderbook::InternalNodes` (r:1 w:0)
+	/// Storage: `HybridOrderbook::LeafNodes` (r:1 w:0)
+	/// Storage: `Assets::Asset` (r:2 w:0)
+	/// Storage: `Assets::Account` (r:2 w:0)
+	/// The range of component `l` is `[1, 1000]`.
+	fn get_pool_query(l: u32, ) -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(40_000_000, 6360)
+			.saturating_add(Weight::from_parts(9_000_000, 0).saturating_mul(l.into()))
+			.saturating_add(T::DbWeight::get().reads(5_u64))
+			.saturating_add(T::DbWeight::get().reads((2_u64).saturating_mul(l.into())))
+			.saturating_add(Weight::from_parts(0, 2641).saturating_mul(l.into()))
+	}
+	/// Storage: `HybridOrderbook::Pools` (r:1 w:0)
+	/// Storage: `HybridOrderbook::TriggerBooks` (r:1 w:1)
+	/// Storage: `HybridOrderbook::StopOrders` (r:0 w:1)
+	/// Storage: `HybridOrderbook::OpenOrdersCount` (r:1 w:1)
+	/// Storage: `Assets::Asset` (r:2 w:0)
+	/// Storage: `Assets::Account` (r:2 w:0)
+	/// Storage: `AssetsFreezer::Freezes` (r:1 w:1)
+	/// Storage: `AssetsFreezer::FrozenBalances` (r:1 w:1)
+	/// Storage: `Balances::Holds` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// The range of component `s` is `[0, 999]`.
+	fn stop_limit_order(s: u32, ) -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(70_000_000, 6360)
+			.saturating_add(Weight::from_parts(60_000, 0).saturating_mul(s.into()))
+			.saturating_add(T::DbWeight::get().reads(11_u64))
+			.saturating_add(T::DbWeight::get().writes(7_u64))
+			.saturating_add(Weight::from_parts(0, 24).saturating_mul(s.into()))
+	}
+	/// Storage: `HybridOrderbook::Pools` (r:1 w:0)
+	/// Storage: `HybridOrderbook::StopOrders` (r:1 w:1)
+	/// Storage: `HybridOrderbook::TriggerBooks` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OpenOrdersCount` (r:1 w:1)
+	/// Storage: `AssetsFreezer::Freezes` (r:1 w:1)
+	/// Storage: `AssetsFreezer::FrozenBalances` (r:1 w:1)
+	/// Storage: `Balances::Holds` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// The range of component `s` is `[1, 1000]`.
+	fn cancel_stop_limit_order(s: u32, ) -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(55_000_000, 6360)
+			.saturating_add(Weight::from_parts(60_000, 0).saturating_mul(s.into()))
+			.saturating_add(T::DbWeight::get().reads(8_u64))
+			.saturating_add(T::DbWeight::get().writes(7_u64))
+			.saturating_add(Weight::from_parts(0, 24).saturating_mul(s.into()))
+	}
+	/// Storage: `HybridOrderbook::OpenOrders` (r:1 w:1)
+	/// Storage: `HybridOrderbook::Pools` (r:1 w:1)
+	/// Storage: `HybridOrderbook::InternalNodes` (r:1 w:1)
+	/// Storage: `HybridOrderbook::LeafNodes` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OpenOrdersCount` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OrderExpiries` (r:0 w:1)
+	/// Storage: `AssetsFreezer::Freezes` (r:1 w:1)
+	/// Storage: `AssetsFreezer::FrozenBalances` (r:1 w:1)
+	/// Storage: `Balances::Holds` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	fn cancel_order_by_id() -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(90_000_000, 8124)
+			.saturating_add(T::DbWeight::get().reads(9_u64))
+			.saturating_add(T::DbWeight::get().writes(10_u64))
+	}
+	/// Storage: `HybridOrderbook::OpenOrders` (r:201 w:200)
+	/// Storage: `HybridOrderbook::Pools` (r:1 w:1)
+	/// Storage: `HybridOrderbook::InternalNodes` (r:200 w:200)
+	/// Storage: `HybridOrderbook::LeafNodes` (r:200 w:200)
+	/// Storage: `HybridOrderbook::OpenOrdersCount` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OrderExpiries` (r:0 w:200)
+	/// Storage: `AssetsFreezer::Freezes` (r:1 w:1)
+	/// Storage: `AssetsFreezer::FrozenBalances` (r:1 w:1)
+	/// Storage: `Balances::Holds` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// The range of component `n` is `[1, 200]`.
+	fn cancel_all_orders(n: u32, ) -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(45_000_000, 6360)
+			.saturating_add(Weight::from_parts(60_000_000, 0).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(6_u64))
+			.saturating_add(T::DbWeight::get().writes(6_u64))
+			.saturating_add(T::DbWeight::get().reads((3_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes((4_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 2641).saturating_mul(n.into()))
+	}
+	/// Storage: `HybridOrderbook::Pools` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OpenOrders` (r:1 w:2)
+	/// Storage: `HybridOrderbook::InternalNodes` (r:2 w:2)
+	/// Storage: `HybridOrderbook::LeafNodes` (r:2 w:2)
+	/// Storage: `HybridOrderbook::OpenOrdersCount` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OrderExpiries` (r:0 w:2)
+	/// Storage: `HybridOrderbook::NextOrderBookId` (r:1 w:0)
+	/// Storage: `Assets::Asset` (r:2 w:0)
+	/// Storage: `Assets::Account` (r:2 w:0)
+	/// Storage: `AssetsFreezer::Freezes` (r:1 w:1)
+	/// Storage: `AssetsFreezer::FrozenBalances` (r:1 w:1)
+	/// Storage: `Balances::Holds` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	fn amend_order() -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(150_000_000, 8124)
+			.saturating_add(T::DbWeight::get().reads(17_u64))
+			.saturating_add(T::DbWeight::get().writes(14_u64))
+	}
+	/// Storage: `HybridOrderbook::ExpirySweepCursor` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OrderExpiries` (r:2 w:1)
+	/// Storage: `HybridOrderbook::Pools` (r:1 w:1)
+	/// Storage: `HybridOrderbook::InternalNodes` (r:1 w:1)
+	/// Storage: `HybridOrderbook::LeafNodes` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OpenOrders` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OpenOrdersCount` (r:1 w:1)
+	/// Storage: `AssetsFreezer::Freezes` (r:1 w:1)
+	/// Storage: `AssetsFreezer::FrozenBalances` (r:1 w:1)
+	/// Storage: `Balances::Holds` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	fn expire_order() -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(95_000_000, 8124)
+			.saturating_add(T::DbWeight::get().reads(12_u64))
+			.saturating_add(T::DbWeight::get().writes(11_u64))
+	}
+	/// Storage: `HybridOrderbook::Pools` (r:1 w:0)
+	/// Storage: `HybridOrderbook::ConcentratedLiquidities` (r:1 w:1)
+	/// Storage: `HybridOrderbook::NextPositionId` (r:1 w:1)
+	/// Storage: `HybridOrderbook::PriceObservationCount` (r:1 w:1)
+	/// Storage: `HybridOrderbook::PriceObservations` (r:1 w:1)
+	/// Storage: `HybridOrderbook::Positions` (r:0 w:1)
+	/// Storage: `Assets::Asset` (r:2 w:2)
+	/// Storage: `Assets::Account` (r:4 w:4)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// The range of component `b` is `[2, 200]`.
+	fn open_position(b: u32, ) -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(110_000_000, 11526)
+			.saturating_add(Weight::from_parts(400_000, 0).saturating_mul(b.into()))
+			.saturating_add(T::DbWeight::get().reads(12_u64))
+			.saturating_add(T::DbWeight::get().writes(12_u64))
+			.saturating_add(Weight::from_parts(0, 96).saturating_mul(b.into()))
+	}
+	/// Storage: `HybridOrderbook::Pools` (r:1 w:0)
+	/// Storage: `HybridOrderbook::Positions` (r:1 w:1)
+	/// Storage: `HybridOrderbook::ConcentratedLiquidities` (r:1 w:1)
+	/// Storage: `HybridOrderbook::PriceObservationCount` (r:1 w:1)
+	/// Storage: `HybridOrderbook::PriceObservations` (r:1 w:1)
+	/// Storage: `Assets::Asset` (r:2 w:2)
+	/// Storage: `Assets::Account` (r:4 w:4)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// The range of component `b` is `[2, 200]`.
+	fn close_position(b: u32, ) -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(105_000_000, 11526)
+			.saturating_add(Weight::from_parts(400_000, 0).saturating_mul(b.into()))
+			.saturating_add(T::DbWeight::get().reads(12_u64))
+			.saturating_add(T::DbWeight::get().writes(11_u64))
+			.saturating_add(Weight::from_parts(0, 96).saturating_mul(b.into()))
+	}
+	/// Storage: `HybridOrderbook::Pools` (r:2 w:0)
+	/// Storage: `HybridOrderbook::ConcentratedLiquidities` (r:2 w:1)
+	/// The range of component `b` is `[2, 200]`.
+	fn swap_concentrated(b: u32, ) -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(20_000_000, 3593)
+			.saturating_add(Weight::from_parts(1_500_000, 0).saturating_mul(b.into()))
+			.saturating_add(T::DbWeight::get().reads(4_u64))
+			.saturating_add(T::DbWeight::get().writes(1_u64))
+			.saturating_add(Weight::from_parts(0, 192).saturating_mul(b.into()))
+	}
 }
 
 // For backwards compatibility and tests.
@@ -302,4 +565,254 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(8_u64))
 			.saturating_add(RocksDbWeight::get().writes((2_u64).saturating_mul(n.into())))
 	}
+	/// Storage: `HybridOrderbook::Pools` (r:1 w:1)
+	/// Storage: `HybridOrderbook::TriggerBooks` (r:1 w:0)
+	/// Storage: `HybridOrderbook::InternalNodes` (r:1 w:1)
+	/// Storage: `HybridOrderbook::LeafNodes` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OpenOrders` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OpenOrdersCount` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OrderExpiries` (r:0 w:1)
+	/// Storage: `HybridOrderbook::PriceObservationCount` (r:1 w:1)
+	/// Storage: `HybridOrderbook::PriceObservations` (r:1 w:1)
+	/// Storage: `HybridOrderbook::TradeCount` (r:1 w:1)
+	/// Storage: `HybridOrderbook::RecentTrades` (r:0 w:1)
+	/// Storage: `HybridOrderbook::Candles` (r:4 w:4)
+	/// Storage: `Assets::Asset` (r:2 w:2)
+	/// Storage: `Assets::Account` (r:4 w:4)
+	/// Storage: `AssetsFreezer::Freezes` (r:1 w:1)
+	/// Storage: `AssetsFreezer::FrozenBalances` (r:1 w:1)
+	/// Storage: `System::Account` (r:2 w:2)
+	/// The range of component `l` is `[1, 100]`.
+	/// The range of component `o` is `[1, 100]`.
+	fn market_order(l: u32, o: u32, ) -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(160_000_000, 11426)
+			.saturating_add(Weight::from_parts(12_000_000, 0).saturating_mul(l.into()))
+			.saturating_add(Weight::from_parts(38_000_000, 0).saturating_mul(o.into()))
+			.saturating_add(RocksDbWeight::get().reads(17_u64))
+			.saturating_add(RocksDbWeight::get().writes(16_u64))
+			.saturating_add(RocksDbWeight::get().reads((2_u64).saturating_mul(l.into())))
+			.saturating_add(RocksDbWeight::get().writes((2_u64).saturating_mul(l.into())))
+			.saturating_add(RocksDbWeight::get().reads((11_u64).saturating_mul(o.into())))
+			.saturating_add(RocksDbWeight::get().writes((12_u64).saturating_mul(o.into())))
+			.saturating_add(Weight::from_parts(0, 2641).saturating_mul(l.into()))
+			.saturating_add(Weight::from_parts(0, 5298).saturating_mul(o.into()))
+	}
+	/// Storage: `HybridOrderbook::Pools` (r:1 w:1)
+	/// Storage: `HybridOrderbook::TriggerBooks` (r:1 w:0)
+	/// Storage: `HybridOrderbook::InternalNodes` (r:1 w:1)
+	/// Storage: `HybridOrderbook::LeafNodes` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OpenOrders` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OpenOrdersCount` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OrderExpiries` (r:0 w:1)
+	/// Storage: `HybridOrderbook::PriceObservationCount` (r:1 w:1)
+	/// Storage: `HybridOrderbook::PriceObservations` (r:1 w:1)
+	/// Storage: `HybridOrderbook::TradeCount` (r:1 w:1)
+	/// Storage: `HybridOrderbook::RecentTrades` (r:0 w:1)
+	/// Storage: `HybridOrderbook::Candles` (r:4 w:4)
+	/// Storage: `HybridOrderbook::NextOrderBookId` (r:1 w:0)
+	/// Storage: `Assets::Asset` (r:2 w:2)
+	/// Storage: `Assets::Account` (r:4 w:4)
+	/// Storage: `AssetsFreezer::Freezes` (r:1 w:1)
+	/// Storage: `AssetsFreezer::FrozenBalances` (r:1 w:1)
+	/// Storage: `System::Account` (r:2 w:2)
+	/// The range of component `l` is `[0, 100]`.
+	/// The range of component `o` is `[0, 100]`.
+	fn limit_order(l: u32, o: u32, ) -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(210_000_000, 11426)
+			.saturating_add(Weight::from_parts(12_000_000, 0).saturating_mul(l.into()))
+			.saturating_add(Weight::from_parts(38_000_000, 0).saturating_mul(o.into()))
+			.saturating_add(RocksDbWeight::get().reads(23_u64))
+			.saturating_add(RocksDbWeight::get().writes(23_u64))
+			.saturating_add(RocksDbWeight::get().reads((2_u64).saturating_mul(l.into())))
+			.saturating_add(RocksDbWeight::get().writes((2_u64).saturating_mul(l.into())))
+			.saturating_add(RocksDbWeight::get().reads((11_u64).saturating_mul(o.into())))
+			.saturating_add(RocksDbWeight::get().writes((12_u64).saturating_mul(o.into())))
+			.saturating_add(Weight::from_parts(0, 2641).saturating_mul(l.into()))
+			.saturating_add(Weight::from_parts(0, 5298).saturating_mul(o.into()))
+	}
+	/// Storage: `HybridOrderbook::Pools` (r:1 w:1)
+	/// Storage: `HybridOrderbook::InternalNodes` (r:1 w:1)
+	/// Storage: `HybridOrderbook::LeafNodes` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OpenOrders` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OpenOrdersCount` (r:1 w:1)
+	/// Storage: `AssetsFreezer::Freezes` (r:1 w:1)
+	/// Storage: `AssetsFreezer::FrozenBalances` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	fn cancel_order() -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(85_000_000, 8124)
+			.saturating_add(RocksDbWeight::get().reads(8_u64))
+			.saturating_add(RocksDbWeight::get().writes(8_u64))
+	}
+	/// Storage: `HybridOrderbook::Pools` (r:1 w:0)
+	/// Storage: `HybridOrderbook::InternalNodes` (r:1 w:0)
+	/// Storage: `HybridOrderbook::LeafNodes` (r:1 w:0)
+	/// Storage: `Assets::Asset` (r:2 w:0)
+	/// Storage: `Assets::Account` (r:2 w:0)
+	/// The range of component `l` is `[1, 1000]`.
+	fn get_pool_query(l: u32, ) -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(40_000_000, 6360)
+			.saturating_add(Weight::from_parts(9_000_000, 0).saturating_mul(l.into()))
+			.saturating_add(RocksDbWeight::get().reads(5_u64))
+			.saturating_add(RocksDbWeight::get().reads((2_u64).saturating_mul(l.into())))
+			.saturating_add(Weight::from_parts(0, 2641).saturating_mul(l.into()))
+	}
+	/// Storage: `HybridOrderbook::Pools` (r:1 w:0)
+	/// Storage: `HybridOrderbook::TriggerBooks` (r:1 w:1)
+	/// Storage: `HybridOrderbook::StopOrders` (r:0 w:1)
+	/// Storage: `HybridOrderbook::OpenOrdersCount` (r:1 w:1)
+	/// Storage: `Assets::Asset` (r:2 w:0)
+	/// Storage: `Assets::Account` (r:2 w:0)
+	/// Storage: `AssetsFreezer::Freezes` (r:1 w:1)
+	/// Storage: `AssetsFreezer::FrozenBalances` (r:1 w:1)
+	/// Storage: `Balances::Holds` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// The range of component `s` is `[0, 999]`.
+	fn stop_limit_order(s: u32, ) -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(70_000_000, 6360)
+			.saturating_add(Weight::from_parts(60_000, 0).saturating_mul(s.into()))
+			.saturating_add(RocksDbWeight::get().reads(11_u64))
+			.saturating_add(RocksDbWeight::get().writes(7_u64))
+			.saturating_add(Weight::from_parts(0, 24).saturating_mul(s.into()))
+	}
+	/// Storage: `HybridOrderbook::Pools` (r:1 w:0)
+	/// Storage: `HybridOrderbook::StopOrders` (r:1 w:1)
+	/// Storage: `HybridOrderbook::TriggerBooks` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OpenOrdersCount` (r:1 w:1)
+	/// Storage: `AssetsFreezer::Freezes` (r:1 w:1)
+	/// Storage: `AssetsFreezer::FrozenBalances` (r:1 w:1)
+	/// Storage: `Balances::Holds` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// The range of component `s` is `[1, 1000]`.
+	fn cancel_stop_limit_order(s: u32, ) -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(55_000_000, 6360)
+			.saturating_add(Weight::from_parts(60_000, 0).saturating_mul(s.into()))
+			.saturating_add(RocksDbWeight::get().reads(8_u64))
+			.saturating_add(RocksDbWeight::get().writes(7_u64))
+			.saturating_add(Weight::from_parts(0, 24).saturating_mul(s.into()))
+	}
+	/// Storage: `HybridOrderbook::OpenOrders` (r:1 w:1)
+	/// Storage: `HybridOrderbook::Pools` (r:1 w:1)
+	/// Storage: `HybridOrderbook::InternalNodes` (r:1 w:1)
+	/// Storage: `HybridOrderbook::LeafNodes` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OpenOrdersCount` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OrderExpiries` (r:0 w:1)
+	/// Storage: `AssetsFreezer::Freezes` (r:1 w:1)
+	/// Storage: `AssetsFreezer::FrozenBalances` (r:1 w:1)
+	/// Storage: `Balances::Holds` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	fn cancel_order_by_id() -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(90_000_000, 8124)
+			.saturating_add(RocksDbWeight::get().reads(9_u64))
+			.saturating_add(RocksDbWeight::get().writes(10_u64))
+	}
+	/// Storage: `HybridOrderbook::OpenOrders` (r:201 w:200)
+	/// Storage: `HybridOrderbook::Pools` (r:1 w:1)
+	/// Storage: `HybridOrderbook::InternalNodes` (r:200 w:200)
+	/// Storage: `HybridOrderbook::LeafNodes` (r:200 w:200)
+	/// Storage: `HybridOrderbook::OpenOrdersCount` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OrderExpiries` (r:0 w:200)
+	/// Storage: `AssetsFreezer::Freezes` (r:1 w:1)
+	/// Storage: `AssetsFreezer::FrozenBalances` (r:1 w:1)
+	/// Storage: `Balances::Holds` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// The range of component `n` is `[1, 200]`.
+	fn cancel_all_orders(n: u32, ) -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(45_000_000, 6360)
+			.saturating_add(Weight::from_parts(60_000_000, 0).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(6_u64))
+			.saturating_add(RocksDbWeight::get().writes(6_u64))
+			.saturating_add(RocksDbWeight::get().reads((3_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes((4_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 2641).saturating_mul(n.into()))
+	}
+	/// Storage: `HybridOrderbook::Pools` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OpenOrders` (r:1 w:2)
+	/// Storage: `HybridOrderbook::InternalNodes` (r:2 w:2)
+	/// Storage: `HybridOrderbook::LeafNodes` (r:2 w:2)
+	/// Storage: `HybridOrderbook::OpenOrdersCount` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OrderExpiries` (r:0 w:2)
+	/// Storage: `HybridOrderbook::NextOrderBookId` (r:1 w:0)
+	/// Storage: `Assets::Asset` (r:2 w:0)
+	/// Storage: `Assets::Account` (r:2 w:0)
+	/// Storage: `AssetsFreezer::Freezes` (r:1 w:1)
+	/// Storage: `AssetsFreezer::FrozenBalances` (r:1 w:1)
+	/// Storage: `Balances::Holds` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	fn amend_order() -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(150_000_000, 8124)
+			.saturating_add(RocksDbWeight::get().reads(17_u64))
+			.saturating_add(RocksDbWeight::get().writes(14_u64))
+	}
+	/// Storage: `HybridOrderbook::ExpirySweepCursor` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OrderExpiries` (r:2 w:1)
+	/// Storage: `HybridOrderbook::Pools` (r:1 w:1)
+	/// Storage: `HybridOrderbook::InternalNodes` (r:1 w:1)
+	/// Storage: `HybridOrderbook::LeafNodes` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OpenOrders` (r:1 w:1)
+	/// Storage: `HybridOrderbook::OpenOrdersCount` (r:1 w:1)
+	/// Storage: `AssetsFreezer::Freezes` (r:1 w:1)
+	/// Storage: `AssetsFreezer::FrozenBalances` (r:1 w:1)
+	/// Storage: `Balances::Holds` (r:1 w:1)
+	/// Storage: `System::Account` (r:1 w:1)
+	fn expire_order() -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(95_000_000, 8124)
+			.saturating_add(RocksDbWeight::get().reads(12_u64))
+			.saturating_add(RocksDbWeight::get().writes(11_u64))
+	}
+	/// Storage: `HybridOrderbook::Pools` (r:1 w:0)
+	/// Storage: `HybridOrderbook::ConcentratedLiquidities` (r:1 w:1)
+	/// Storage: `HybridOrderbook::NextPositionId` (r:1 w:1)
+	/// Storage: `HybridOrderbook::PriceObservationCount` (r:1 w:1)
+	/// Storage: `HybridOrderbook::PriceObservations` (r:1 w:1)
+	/// Storage: `HybridOrderbook::Positions` (r:0 w:1)
+	/// Storage: `Assets::Asset` (r:2 w:2)
+	/// Storage: `Assets::Account` (r:4 w:4)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// The range of component `b` is `[2, 200]`.
+	fn open_position(b: u32, ) -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(110_000_000, 11526)
+			.saturating_add(Weight::from_parts(400_000, 0).saturating_mul(b.into()))
+			.saturating_add(RocksDbWeight::get().reads(12_u64))
+			.saturating_add(RocksDbWeight::get().writes(12_u64))
+			.saturating_add(Weight::from_parts(0, 96).saturating_mul(b.into()))
+	}
+	/// Storage: `HybridOrderbook::Pools` (r:1 w:0)
+	/// Storage: `HybridOrderbook::Positions` (r:1 w:1)
+	/// Storage: `HybridOrderbook::ConcentratedLiquidities` (r:1 w:1)
+	/// Storage: `HybridOrderbook::PriceObservationCount` (r:1 w:1)
+	/// Storage: `HybridOrderbook::PriceObservations` (r:1 w:1)
+	/// Storage: `Assets::Asset` (r:2 w:2)
+	/// Storage: `Assets::Account` (r:4 w:4)
+	/// Storage: `System::Account` (r:1 w:1)
+	/// The range of component `b` is `[2, 200]`.
+	fn close_position(b: u32, ) -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(105_000_000, 11526)
+			.saturating_add(Weight::from_parts(400_000, 0).saturating_mul(b.into()))
+			.saturating_add(RocksDbWeight::get().reads(12_u64))
+			.saturating_add(RocksDbWeight::get().writes(11_u64))
+			.saturating_add(Weight::from_parts(0, 96).saturating_mul(b.into()))
+	}
+	/// Storage: `HybridOrderbook::Pools` (r:2 w:0)
+	/// Storage: `HybridOrderbook::ConcentratedLiquidities` (r:2 w:1)
+	/// The range of component `b` is `[2, 200]`.
+	fn swap_concentrated(b: u32, ) -> Weight {
+		// Not measured yet, estimated from the storage accessed until regenerated.
+		Weight::from_parts(20_000_000, 3593)
+			.saturating_add(Weight::from_parts(1_500_000, 0).saturating_mul(b.into()))
+			.saturating_add(RocksDbWeight::get().reads(4_u64))
+			.saturating_add(RocksDbWeight::get().writes(1_u64))
+			.saturating_add(Weight::from_parts(0, 192).saturating_mul(b.into()))
+	}
 }
@@ -40,7 +40,11 @@ use sp_runtime::{
     ApplyExtrinsicResult,
 };
 use sp_version::RuntimeVersion;
-use pallet_hybrid_orderbook::{CritbitTree, PoolQuery, PoolMetadata, Tick};
+use pallet_hybrid_orderbook::{
+    Candle, CritbitTree, OpenOrder, PoolDepth, PoolQuery, PoolMetadata, PoolReserves, Tick,
+    TradeRecord,
+};
+use super::configs::MaxOrdersPerTick;
 
 // Local module imports
 use super::{
@@ -212,14 +216,44 @@ impl_runtime_apis! {
         }
     }
 
-    impl pallet_hybrid_orderbook::HybridOrderbookApi<Block, Balance, NativeOrWithId<u32>, CritbitTree<Balance, Tick<Balance, AccountId, BlockNumber>>> for Runtime {
-        fn get_pool_query(base: NativeOrWithId<u32>, quote: NativeOrWithId<u32>) -> Option<PoolQuery<CritbitTree<Balance, Tick<Balance, AccountId, BlockNumber>>, Balance>> {
+    #[api_version(2)]
+    impl pallet_hybrid_orderbook::HybridOrderbookApi<Block, Balance, NativeOrWithId<u32>, CritbitTree<Balance, Tick<Balance, AccountId, BlockNumber, MaxOrdersPerTick>>, AccountId> for Runtime {
+        fn get_pool_query(base: NativeOrWithId<u32>, quote: NativeOrWithId<u32>) -> Option<PoolQuery<CritbitTree<Balance, Tick<Balance, AccountId, BlockNumber, MaxOrdersPerTick>>, Balance>> {
             HybridOrderbook::get_pool_query(&base, &quote).ok()
         }
 
         fn get_pool_metadata(base: NativeOrWithId<u32>, quote: NativeOrWithId<u32>) -> Option<PoolMetadata<Balance>> {
             HybridOrderbook::get_pool_metadata(&base, &quote).ok()
         }
+
+        fn get_pool_reserves(base: NativeOrWithId<u32>, quote: NativeOrWithId<u32>) -> Option<PoolReserves<Balance>> {
+            HybridOrderbook::get_pool_reserves(&base, &quote).ok()
+        }
+
+        fn get_depth(base: NativeOrWithId<u32>, quote: NativeOrWithId<u32>, levels: u32) -> Option<PoolDepth<Balance>> {
+            HybridOrderbook::get_depth(&base, &quote, levels).ok()
+        }
+
+        fn open_orders(account: AccountId, pool: Option<(NativeOrWithId<u32>, NativeOrWithId<u32>)>) -> Vec<OpenOrder<(NativeOrWithId<u32>, NativeOrWithId<u32>), Balance, BlockNumber>> {
+            HybridOrderbook::open_orders(&account, pool.as_ref().map(|(base, quote)| (base, quote)))
+                .unwrap_or_default()
+        }
+
+        fn recent_trades(base: NativeOrWithId<u32>, quote: NativeOrWithId<u32>, limit: u32) -> Option<Vec<TradeRecord<AccountId, Balance, BlockNumber>>> {
+            HybridOrderbook::recent_trades(&base, &quote, limit).ok()
+        }
+
+        fn twap(base: NativeOrWithId<u32>, quote: NativeOrWithId<u32>, window: BlockNumber) -> Option<Balance> {
+            HybridOrderbook::twap(&base, &quote, window).ok()
+        }
+
+        fn candles(base: NativeOrWithId<u32>, quote: NativeOrWithId<u32>, resolution: u32, from: BlockNumber, to: BlockNumber) -> Option<Vec<Candle<Balance, BlockNumber>>> {
+            HybridOrderbook::candles(&base, &quote, resolution, from, to).ok()
+        }
+
+        fn quote_market_order(base: NativeOrWithId<u32>, quote: NativeOrWithId<u32>, quantity: Balance, is_bid: bool) -> Option<Balance> {
+            HybridOrderbook::quote_market_order(&base, &quote, quantity, is_bid).ok()
+        }
     }
 
     impl cumulus_primitives_core::CollectCollationInfo<Block> for Runtime {
@@ -40,13 +40,13 @@ use frame_support::{
         TransformOrigin, VariantCountOf,
     },
     weights::{ConstantMultiplier, Weight},
-    PalletId,
+    BoundedVec, PalletId,
 };
 use frame_system::{
     limits::{BlockLength, BlockWeights},
     EnsureRoot, EnsureSigned, EnsureSignedBy,
 };
-use pallet_hybrid_orderbook::{BaseQuoteAsset, CritbitTree, Tick};
+use pallet_hybrid_orderbook::{BaseQuoteAsset, CritbitTree, OrderBookNodes, PriceSource, Tick};
 use pallet_xcm::{EnsureXcm, IsVoiceOfBody};
 use parachains_common::message_queue::{NarrowOriginToSibling, ParaIdToSibling};
 use polkadot_runtime_common::{
@@ -390,12 +390,29 @@ parameter_types! {
     pub const PoolSetupFee: Balance = 1 * MILLIUNIT; // should be more or equal to the existential deposit
     pub const MintMinLiquidity: Balance = 100;  // 100 is good enough when the main currency has 10-12 decimals.
     pub const LiquidityWithdrawalFee: Permill = Permill::from_percent(0);
+    pub const OrderDeposit: Balance = 1 * MILLIUNIT;
     pub const Native: NativeOrWithId<u32> = NativeOrWithId::Native;
 }
 
 ord_parameter_types! {
     pub const HybridOrderBookOrigin: AccountId = AccountIdConversion::<AccountId>::into_account_truncating(&HybridOrderBookPalletId::get());
     pub const OrderExpiration: BlockNumber = DAYS;
+    pub const MaxExpiredOrdersPerBlock: u32 = 100;
+    pub const MaxTriggeredStopOrders: u32 = 10;
+    pub const MaxStopOrders: u32 = 1000;
+    pub const MaxTicks: u32 = 1000;
+    pub const MaxOrdersPerTick: u32 = 100;
+    pub const MaxOpenOrders: u32 = 200;
+    pub const MaxOrdersFilled: u32 = 200;
+    pub const MaxRecentTrades: u32 = 100;
+    pub const MaxPriceObservations: u32 = 600;
+    pub const OraclePriceSource: PriceSource<BlockNumber> = PriceSource::Twap(600);
+    pub const MaxExpiredPriceLevels: u32 = 8;
+    pub const MaxCandleResolutions: u32 = 4;
+    pub CandleResolutions: BoundedVec<u32, MaxCandleResolutions> =
+        BoundedVec::truncate_from(vec![10, 600]);
+    pub const MaxCandles: u32 = 200;
+    pub const MaxLiquidityBoundaries: u32 = 200;
     pub const LPFee: u32 = 3;
     pub const StandardDecimals: u8 = 18;
     pub const MaxSwapPathLength: u32 = 4;
@@ -413,17 +430,36 @@ impl pallet_hybrid_orderbook::Config for Runtime {
     type AssetKind = NativeOrWithId<u32>;
     type Assets = NativeAndAssets;
     type AssetsFreezer = NativeAndAssetsFreezer;
-    type OrderBook = CritbitTree<Balance, Tick<Balance, AccountId, BlockNumber>>;
+    type OrderBook = CritbitTree<Balance, Tick<Balance, AccountId, BlockNumber, MaxOrdersPerTick>, OrderBookNodes<Runtime>>;
     type PoolId = (Self::AssetKind, Self::AssetKind);
     type PoolLocator = BaseQuoteAsset<AccountId, NativeOrWithId<u32>>;
     type PoolAssetId = <Self as pallet_assets::Config<Instance2>>::AssetId;
     type PoolAssets = PoolAssets;
     type RuntimeFreezeReason = RuntimeFreezeReason;
+    type RuntimeHoldReason = RuntimeHoldReason;
+    type Currency = Balances;
+    type OrderDeposit = OrderDeposit;
     type PoolSetupFee = PoolSetupFee;
     type PoolSetupFeeAsset = Native;
     type PoolSetupFeeTarget = ResolveAssetTo<HybridOrderBookOrigin, Self::Assets>;
+    type TakerFeeTarget = ResolveAssetTo<HybridOrderBookOrigin, Self::Assets>;
     type PalletId = HybridOrderBookPalletId;
     type OrderExpiration = OrderExpiration;
+    type MaxExpiredOrdersPerBlock = MaxExpiredOrdersPerBlock;
+    type MaxTriggeredStopOrders = MaxTriggeredStopOrders;
+    type MaxStopOrders = MaxStopOrders;
+    type MaxTicks = MaxTicks;
+    type MaxOrdersPerTick = MaxOrdersPerTick;
+    type MaxOpenOrders = MaxOpenOrders;
+    type MaxOrdersFilled = MaxOrdersFilled;
+    type MaxRecentTrades = MaxRecentTrades;
+    type MaxPriceObservations = MaxPriceObservations;
+    type OraclePriceSource = OraclePriceSource;
+    type MaxExpiredPriceLevels = MaxExpiredPriceLevels;
+    type MaxCandleResolutions = MaxCandleResolutions;
+    type CandleResolutions = CandleResolutions;
+    type MaxCandles = MaxCandles;
+    type MaxLiquidityBoundaries = MaxLiquidityBoundaries;
     type LPFee = LPFee; // means 0.3%
     type StandardDecimals = StandardDecimals;
     type LiquidityWithdrawalFee = LiquidityWithdrawalFee;
@@ -47,6 +47,10 @@ pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::Account
 
 pub type AssetId = u32;
 
+/// Orderbook of the hybrid orderbook pool which holds all of its nodes in memory. Returned by
+/// `HybridOrderbookApi::get_pool_query`.
+pub type OrderBookSnapshot = pallet_hybrid_orderbook::OrderBookSnapshot<Runtime>;
+
 /// Balance of an account.
 pub type Balance = u128;
 
@@ -94,6 +98,9 @@ pub type TxExtension = cumulus_pallet_weight_reclaim::StorageWeightReclaim<
 pub type UncheckedExtrinsic =
     generic::UncheckedExtrinsic<Address, RuntimeCall, Signature, TxExtension>;
 
+/// Migrations to apply on runtime upgrade.
+pub type Migrations = (pallet_hybrid_orderbook::migration::v1::MigrateToV1<Runtime>,);
+
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
     Runtime,
@@ -101,6 +108,7 @@ pub type Executive = frame_executive::Executive<
     frame_system::ChainContext<Runtime>,
     Runtime,
     AllPalletsWithSystem,
+    Migrations,
 >;
 
 /// Handles converting a weight scalar to a fee value, based on the scale and granularity of the
@@ -163,7 +171,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     spec_name: alloc::borrow::Cow::Borrowed("warpx-runtime"),
     impl_name: alloc::borrow::Cow::Borrowed("warpx-runtime"),
     authoring_version: 1,
-    spec_version: 1,
+    spec_version: 2,
     impl_version: 0,
     apis: apis::RUNTIME_API_VERSIONS,
     transaction_version: 1,